@@ -1,13 +1,18 @@
 use crate::core::path_utils;
 use crate::error::Result;
 use crate::services::{
-    cli_service, file_search_service, git_service, project_service, pty_service, review_service,
-    watcher_service,
+    cli_service, file_search_service, formatter_service, git_service, history_service,
+    project_service, pty_service, review_service, sandbox_service, session_service,
+    verification_service, watcher_service,
 };
 use crate::types::{
-    ChangeOperation, CliInstallResult, CliStatusResult, CommandStreamEvent, Commit, DiffOption,
-    FileNode, FileTokenInfo, GitStatus, IgnoreSettings, PromptEstimateInput, PromptEstimateResult,
-    SearchResult,
+    BackupManifest, ChangeOperation, ChangeSummary, CliInstallResult, CliStatusResult,
+    CommandStreamEvent, Commit, DiffOption, EditFormat, FileNode, FileTokenInfo, FormattedChanges,
+    FormatterConfig, FormatterResult, GitStatus, HistoryStepResult, IgnoreSettings,
+    ProjectConfigStatus, ProjectHistory, ProjectSettings, PromptEstimateInput,
+    PromptEstimateResult, ProtectedPathRule, RepairPrompt, ResolvedProjectSettings, ReviewResult,
+    ReviewSession, ReviewSessionInfo, SandboxFileDiff, SandboxInfo, SandboxKind, SandboxPromotion,
+    SearchResult, SymlinkPolicy, VerificationResult, WatcherInfo,
 };
 use base64::{engine::general_purpose, Engine as _};
 use log::debug;
@@ -120,14 +125,41 @@ pub async fn close_window(window: tauri::Window) -> Result<()> {
     {
         reg.set_project_for_label(window.label(), None);
     }
+    watcher_service::stop_watchers_for_window(window.label());
     window.close()?;
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn list_directory_recursive(path: String, settings: IgnoreSettings) -> Result<FileNode> {
-    Ok(project_service::list_directory_recursive(&PathBuf::from(path), settings).await?)
+pub async fn list_directory_recursive(
+    path: String,
+    settings: IgnoreSettings,
+    with_metadata: Option<bool>,
+) -> Result<FileNode> {
+    Ok(project_service::list_directory_recursive(
+        &PathBuf::from(path),
+        settings,
+        with_metadata.unwrap_or(false),
+    )
+    .await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_directory(
+    root_path: String,
+    path: String,
+    settings: IgnoreSettings,
+    depth: Option<u32>,
+) -> Result<FileNode> {
+    Ok(project_service::list_directory(
+        &PathBuf::from(root_path),
+        &PathBuf::from(path),
+        settings,
+        depth,
+    )
+    .await?)
 }
 
 #[tauri::command]
@@ -190,6 +222,28 @@ pub async fn write_file_content(path: String, content: String, root_path: String
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn set_symlink_policy(root_path: String, policy: SymlinkPolicy) -> Result<()> {
+    project_service::set_symlink_policy(&PathBuf::from(root_path), policy);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_protected_paths(root_path: String, rules: Vec<ProtectedPathRule>) -> Result<()> {
+    project_service::set_protected_paths(&PathBuf::from(root_path), rules)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_protected_paths(root_path: String) -> Result<Vec<ProtectedPathRule>> {
+    Ok(project_service::get_protected_paths(&PathBuf::from(
+        root_path,
+    )))
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn delete_file(file_path: String, root_path: String) -> Result<()> {
@@ -211,10 +265,14 @@ pub async fn move_file(from: String, to: String, root_path: String) -> Result<()
 
 #[tauri::command]
 #[specta::specta]
-pub async fn backup_files(root_path: String, file_paths: Vec<String>) -> Result<String> {
+pub async fn backup_files(
+    root_path: String,
+    file_paths: Vec<String>,
+    summary: Option<ChangeSummary>,
+) -> Result<String> {
     let root = PathBuf::from(root_path);
     let paths = file_paths.into_iter().map(PathBuf::from).collect();
-    Ok(review_service::backup_files(&root, paths).await?)
+    Ok(review_service::backup_files(&root, paths, summary).await?)
 }
 
 #[tauri::command]
@@ -246,13 +304,285 @@ pub async fn delete_backup(backup_id: String) -> Result<()> {
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn list_backups(root_path: String) -> Result<Vec<BackupManifest>> {
+    Ok(review_service::list_backups(&PathBuf::from(root_path)).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_backup(backup_id: String) -> Result<BackupManifest> {
+    Ok(review_service::get_backup(&backup_id).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn revert_backup(backup_id: String) -> Result<Vec<String>> {
+    Ok(review_service::revert_backup(&backup_id).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn record_history_entry(
+    app_handle: tauri::AppHandle,
+    root_path: String,
+    backup_id: String,
+    summary: Option<ChangeSummary>,
+) -> Result<ProjectHistory> {
+    let data_dir = app_handle.path().app_data_dir()?;
+    Ok(
+        history_service::record_applied(&data_dir, &PathBuf::from(root_path), &backup_id, summary)
+            .await?,
+    )
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_history(
+    app_handle: tauri::AppHandle,
+    root_path: String,
+) -> Result<ProjectHistory> {
+    let data_dir = app_handle.path().app_data_dir()?;
+    Ok(history_service::get_history(&data_dir, &PathBuf::from(root_path)).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn undo_change_set(
+    app_handle: tauri::AppHandle,
+    root_path: String,
+) -> Result<HistoryStepResult> {
+    let data_dir = app_handle.path().app_data_dir()?;
+    Ok(history_service::undo(&data_dir, &PathBuf::from(root_path)).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn redo_change_set(
+    app_handle: tauri::AppHandle,
+    root_path: String,
+) -> Result<HistoryStepResult> {
+    let data_dir = app_handle.path().app_data_dir()?;
+    Ok(history_service::redo(&data_dir, &PathBuf::from(root_path)).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_review_session(
+    app_handle: tauri::AppHandle,
+    root_path: String,
+    raw_response: String,
+    operations: Vec<ChangeOperation>,
+    backup_id: Option<String>,
+) -> Result<ReviewSession> {
+    let data_dir = app_handle.path().app_data_dir()?;
+    Ok(
+        session_service::create_session(&data_dir, &root_path, raw_response, operations, backup_id)
+            .await?,
+    )
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn save_review_session(
+    app_handle: tauri::AppHandle,
+    session: ReviewSession,
+) -> Result<ReviewSession> {
+    let data_dir = app_handle.path().app_data_dir()?;
+    Ok(session_service::save_session(&data_dir, session).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_review_sessions(
+    app_handle: tauri::AppHandle,
+    root_path: String,
+) -> Result<Vec<ReviewSessionInfo>> {
+    let data_dir = app_handle.path().app_data_dir()?;
+    Ok(session_service::list_sessions(&data_dir, &root_path).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn resume_review_session(
+    app_handle: tauri::AppHandle,
+    root_path: String,
+    session_id: String,
+) -> Result<ReviewSession> {
+    let data_dir = app_handle.path().app_data_dir()?;
+    Ok(session_service::resume_session(&data_dir, &root_path, &session_id).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn discard_review_session(
+    app_handle: tauri::AppHandle,
+    root_path: String,
+    session_id: String,
+) -> Result<()> {
+    let data_dir = app_handle.path().app_data_dir()?;
+    session_service::discard_session(&data_dir, &root_path, &session_id).await?;
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn parse_changes_from_markdown(
     markdown: String,
     root_path: String,
+) -> Result<ReviewResult> {
+    Ok(review_service::review_changes(&markdown, &root_path).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn track_review(
+    root_path: String,
+    markdown: String,
+    operations: Vec<ChangeOperation>,
+) -> Result<()> {
+    review_service::track_review(&PathBuf::from(root_path), &markdown, &operations);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn untrack_review(root_path: String) -> Result<()> {
+    review_service::untrack_review(&PathBuf::from(root_path));
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn rerun_review(
+    root_path: String,
+    file_paths: Vec<String>,
 ) -> Result<Vec<ChangeOperation>> {
-    Ok(review_service::process_markdown_changes(&markdown, &root_path).await?)
+    Ok(review_service::rerun_review(&root_path, &file_paths).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn apply_selected_hunks(
+    root_path: String,
+    operation: ChangeOperation,
+    hunk_ids: Vec<String>,
+) -> Result<String> {
+    Ok(
+        review_service::apply_selected_hunks(&PathBuf::from(root_path), operation, hunk_ids)
+            .await?,
+    )
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn format_proposed_changes(
+    root_path: String,
+    operations: Vec<ChangeOperation>,
+    formatters: Vec<FormatterConfig>,
+) -> Result<FormattedChanges> {
+    Ok(
+        review_service::format_proposed_changes(&PathBuf::from(root_path), operations, formatters)
+            .await?,
+    )
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn format_files(
+    root_path: String,
+    paths: Vec<String>,
+    formatters: Vec<FormatterConfig>,
+) -> Result<Vec<FormatterResult>> {
+    Ok(formatter_service::format_files(&PathBuf::from(root_path), paths, formatters).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn run_verification(
+    root_path: String,
+    command: String,
+    timeout_secs: u32,
+    changed_files: Vec<String>,
+) -> Result<VerificationResult> {
+    Ok(verification_service::run_verification(
+        &PathBuf::from(root_path),
+        &command,
+        timeout_secs,
+        changed_files,
+    )
+    .await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_sandbox(root_path: String, kind: SandboxKind) -> Result<SandboxInfo> {
+    Ok(sandbox_service::create_sandbox(&PathBuf::from(root_path), kind).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn apply_to_sandbox(sandbox_id: String, operations: Vec<ChangeOperation>) -> Result<()> {
+    sandbox_service::apply_to_sandbox(&sandbox_id, operations).await?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn run_in_sandbox(
+    sandbox_id: String,
+    command: String,
+    timeout_secs: u32,
+) -> Result<VerificationResult> {
+    Ok(sandbox_service::run_in_sandbox(&sandbox_id, &command, timeout_secs).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn diff_sandbox(sandbox_id: String) -> Result<Vec<SandboxFileDiff>> {
+    Ok(sandbox_service::diff_sandbox(&sandbox_id).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn promote_sandbox(sandbox_id: String) -> Result<SandboxPromotion> {
+    Ok(sandbox_service::promote_sandbox(&sandbox_id).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn discard_sandbox(sandbox_id: String) -> Result<()> {
+    sandbox_service::discard_sandbox(&sandbox_id)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn build_continuation_prompt(markdown: String) -> Result<String> {
+    Ok(review_service::build_continuation_prompt(&markdown)?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn merge_continuation(markdown: String, continuation: String) -> Result<String> {
+    Ok(review_service::merge_continuation(
+        &markdown,
+        &continuation,
+    )?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn build_repair_prompt(
+    root_path: String,
+    operations: Vec<ChangeOperation>,
+    edit_format: EditFormat,
+) -> Result<RepairPrompt> {
+    Ok(
+        review_service::build_repair_prompt(&PathBuf::from(root_path), operations, edit_format)
+            .await?,
+    )
 }
 
 #[tauri::command]
@@ -335,24 +665,71 @@ pub async fn install_cli_shim() -> Result<CliInstallResult> {
     Ok(cli_service::install_cli_shim().await?)
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn load_project_config(root_path: String) -> Result<ProjectConfigStatus> {
+    Ok(project_service::load_project_config(&PathBuf::from(
+        root_path,
+    )))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn resolve_project_settings(
+    root_path: String,
+    settings: ProjectSettings,
+) -> Result<ResolvedProjectSettings> {
+    Ok(project_service::resolve_project_settings(
+        &PathBuf::from(root_path),
+        settings,
+    ))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn approve_project_formatters(root_path: String, fingerprint: String) -> Result<()> {
+    Ok(project_service::approve_project_formatters(
+        &PathBuf::from(root_path),
+        &fingerprint,
+    )?)
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn start_watching(
-    app_handle: tauri::AppHandle,
+    window: tauri::Window,
     root_path: String,
     settings: IgnoreSettings,
 ) -> Result<()> {
-    watcher_service::start_watching(app_handle, &PathBuf::from(root_path), settings)?;
+    watcher_service::start_watching(
+        window.app_handle().clone(),
+        window.label(),
+        &PathBuf::from(root_path),
+        settings,
+    )?;
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn stop_watching(root_path: String) -> Result<()> {
-    watcher_service::stop_watching(&PathBuf::from(root_path));
+pub async fn stop_watching(window: tauri::Window, root_path: String) -> Result<()> {
+    watcher_service::stop_watching(window.label(), &PathBuf::from(root_path));
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn restart_watcher(app_handle: tauri::AppHandle, root_path: String) -> Result<()> {
+    watcher_service::restart_watcher(app_handle, &PathBuf::from(root_path))?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_watchers() -> Result<Vec<WatcherInfo>> {
+    Ok(watcher_service::list_watchers())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn search_files(
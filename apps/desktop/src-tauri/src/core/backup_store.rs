@@ -0,0 +1,399 @@
+use crate::core::fs_utils;
+use crate::types::{BackupFile, BackupManifest, ChangeSummary};
+use anyhow::{anyhow, Result};
+use once_cell::sync::{Lazy, OnceCell};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+const MANIFESTS_DIR: &str = "manifests";
+const BLOBS_DIR: &str = "blobs";
+
+static BACKUP_ROOT: OnceCell<PathBuf> = OnceCell::new();
+
+/// Serializes backup creation and deletion, so garbage collection never sees a blob whose
+/// manifest has not been written yet.
+static STORE_LOCK: Lazy<Mutex<()>> = Lazy::new(Default::default);
+
+/// Places the store under the app data dir. Until this is called (e.g. in tests), backups go
+/// to the temp dir.
+pub fn init(app_data_dir: &Path) {
+    let _ = BACKUP_ROOT.set(app_data_dir.join("backups"));
+}
+
+fn backup_root_dir() -> PathBuf {
+    BACKUP_ROOT
+        .get()
+        .cloned()
+        .unwrap_or_else(|| std::env::temp_dir().join("repo-wizard-backups"))
+}
+
+fn manifest_path(backup_id: &str) -> Result<PathBuf> {
+    Uuid::parse_str(backup_id).map_err(|_| anyhow!("Invalid backup id: {}", backup_id))?;
+    Ok(backup_root_dir()
+        .join(MANIFESTS_DIR)
+        .join(format!("{backup_id}.json")))
+}
+
+/// File contents are stored once per SHA-256, however many backups refer to them.
+fn blob_path(hash: &str) -> PathBuf {
+    backup_root_dir().join(BLOBS_DIR).join(hash)
+}
+
+fn normalize_root(root_path: &Path) -> String {
+    root_path
+        .to_string_lossy()
+        .replace('\\', "/")
+        .trim_end_matches('/')
+        .to_string()
+}
+
+fn normalize_relative(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+async fn write_blob(bytes: &[u8]) -> Result<String> {
+    let hash = fs_utils::content_hash(bytes);
+    let path = blob_path(&hash);
+    if !path.exists() {
+        fs::create_dir_all(backup_root_dir().join(BLOBS_DIR)).await?;
+        fs_utils::write_atomic(&path, bytes.to_vec()).await?;
+    }
+    Ok(hash)
+}
+
+/// Records the pre-apply state of every path in `paths`: the content of files that exist and
+/// the absence of those that do not. Directories are skipped.
+pub async fn create_backup(
+    root_path: &Path,
+    paths: Vec<PathBuf>,
+    summary: Option<ChangeSummary>,
+) -> Result<String> {
+    // The paths come from responses and the UI, so they are confined like the writes they precede.
+    let root = root_path.to_path_buf();
+    let full_paths = tokio::task::spawn_blocking(move || {
+        paths
+            .iter()
+            .map(|path| fs_utils::resolve_relative(&root, &path.to_string_lossy()))
+            .collect::<Result<Vec<_>>>()
+            .map(|full_paths| paths.into_iter().zip(full_paths).collect::<Vec<_>>())
+    })
+    .await??;
+
+    let _guard = STORE_LOCK.lock().await;
+    let backup_id = Uuid::new_v4().to_string();
+
+    let mut files = Vec::new();
+    for (path, full_path) in full_paths {
+        let hash = if full_path.is_file() {
+            Some(write_blob(&fs::read(&full_path).await?).await?)
+        } else if full_path.exists() {
+            continue;
+        } else {
+            None
+        };
+        files.push(BackupFile {
+            path: normalize_relative(&path),
+            hash,
+        });
+    }
+
+    let manifest = BackupManifest {
+        id: backup_id.clone(),
+        root_path: normalize_root(root_path),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as f64)
+            .unwrap_or_default(),
+        summary,
+        files,
+    };
+    write_manifest(&manifest).await?;
+    Ok(backup_id)
+}
+
+async fn write_manifest(manifest: &BackupManifest) -> Result<()> {
+    let manifest_path = manifest_path(&manifest.id)?;
+    if let Some(parent) = manifest_path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    fs_utils::write_atomic(&manifest_path, serde_json::to_vec_pretty(manifest)?).await?;
+    Ok(())
+}
+
+pub async fn get_backup(backup_id: &str) -> Result<BackupManifest> {
+    let manifest_path = manifest_path(backup_id)?;
+    if !manifest_path.exists() {
+        return Err(anyhow!("Backup not found: {}", backup_id));
+    }
+    Ok(serde_json::from_slice(&fs::read(&manifest_path).await?)?)
+}
+
+/// Creates a new backup with the same contents as `backup_id`, sharing its blobs.
+pub async fn clone_backup(backup_id: &str) -> Result<String> {
+    let _guard = STORE_LOCK.lock().await;
+    let mut manifest = get_backup(backup_id).await?;
+    manifest.id = Uuid::new_v4().to_string();
+    write_manifest(&manifest).await?;
+    Ok(manifest.id)
+}
+
+/// The hash a backup taken now would record for `relative_path`.
+pub async fn current_hash(root_path: &Path, relative_path: &str) -> Result<Option<String>> {
+    let path = root_path.join(relative_path);
+    if path.is_file() {
+        Ok(Some(fs_utils::content_hash(&fs::read(&path).await?)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Every readable manifest in the store at `store_dir`, and the paths of those that are not.
+async fn all_backups(store_dir: &Path) -> Result<(Vec<BackupManifest>, Vec<PathBuf>)> {
+    let manifests_dir = store_dir.join(MANIFESTS_DIR);
+    if !manifests_dir.is_dir() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    let mut backups = Vec::new();
+    let mut unreadable = Vec::new();
+    let mut entries = fs::read_dir(&manifests_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        match fs::read(&path)
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(serde_json::from_slice::<BackupManifest>(&bytes)?))
+        {
+            Ok(manifest) => backups.push(manifest),
+            Err(e) => {
+                log::warn!("Unreadable backup manifest {}: {e}", path.display());
+                unreadable.push(path);
+            }
+        }
+    }
+    Ok((backups, unreadable))
+}
+
+/// Backups of a project, newest first.
+pub async fn list_backups(root_path: &Path) -> Result<Vec<BackupManifest>> {
+    let root = normalize_root(root_path);
+    let mut backups: Vec<_> = all_backups(&backup_root_dir())
+        .await?
+        .0
+        .into_iter()
+        .filter(|manifest| manifest.root_path == root)
+        .collect();
+    backups.sort_by(|a, b| b.created_at.total_cmp(&a.created_at));
+    Ok(backups)
+}
+
+async fn read_backup_bytes(backup_id: &str, relative_path: &Path) -> Result<Vec<u8>> {
+    let manifest = get_backup(backup_id).await?;
+    let relative_path = normalize_relative(relative_path);
+    let hash = manifest
+        .files
+        .iter()
+        .find(|file| file.path == relative_path)
+        .and_then(|file| file.hash.as_ref())
+        .ok_or_else(|| anyhow!("File {} not found in backup {}", relative_path, backup_id))?;
+    fs::read(blob_path(hash))
+        .await
+        .map_err(|e| anyhow!("Backup blob for {} is missing: {}", relative_path, e))
+}
+
+/// Puts a single path back into its recorded state, deleting it if it did not exist.
+pub async fn revert_file_from_backup(
+    root_path: &Path,
+    backup_id: &str,
+    relative_path: &Path,
+) -> Result<()> {
+    let manifest = get_backup(backup_id).await?;
+    let normalized = normalize_relative(relative_path);
+    let file = manifest
+        .files
+        .into_iter()
+        .find(|file| file.path == normalized)
+        .ok_or_else(|| {
+            anyhow!(
+                "Backup for file {} not found in backup {}",
+                relative_path.display(),
+                backup_id
+            )
+        })?;
+    restore_file(root_path, &file).await
+}
+
+async fn restore_file(root_path: &Path, file: &BackupFile) -> Result<()> {
+    let dest_path = root_path.join(&file.path);
+    let (path, root) = (dest_path.clone(), root_path.to_path_buf());
+    tokio::task::spawn_blocking(move || fs_utils::ensure_safe_path(&path, &root)).await??;
+    match &file.hash {
+        Some(hash) => {
+            let bytes = fs::read(blob_path(hash))
+                .await
+                .map_err(|e| anyhow!("Backup blob for {} is missing: {}", file.path, e))?;
+            if let Some(parent) = dest_path.parent() {
+                if !parent.exists() {
+                    fs::create_dir_all(parent).await?;
+                }
+            }
+            fs_utils::write_atomic(&dest_path, bytes).await?;
+        }
+        None => {
+            if dest_path.is_file() {
+                fs::remove_file(&dest_path).await?;
+            }
+            remove_empty_parents(root_path, &dest_path).await;
+        }
+    }
+    Ok(())
+}
+
+/// Removes directories left empty by deleting `path`, up to the project root.
+async fn remove_empty_parents(root_path: &Path, path: &Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == root_path || !current.starts_with(root_path) {
+            break;
+        }
+        if fs::remove_dir(current).await.is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+/// Returns the project the backup was taken from to its recorded state: files are restored,
+/// files that did not exist are removed (which also undoes moves). Returns the affected paths.
+pub async fn revert_backup(backup_id: &str) -> Result<Vec<String>> {
+    let manifest = get_backup(backup_id).await?;
+    let root_path = PathBuf::from(&manifest.root_path);
+    let mut reverted = Vec::new();
+    // Write recorded content before removing paths, so a move is never half undone.
+    let (present, absent): (Vec<_>, Vec<_>) = manifest
+        .files
+        .into_iter()
+        .partition(|file| file.hash.is_some());
+    for file in present.into_iter().chain(absent) {
+        restore_file(&root_path, &file).await?;
+        reverted.push(file.path);
+    }
+    Ok(reverted)
+}
+
+pub async fn read_file_from_backup(backup_id: &str, relative_path: &Path) -> Result<String> {
+    let bytes = read_backup_bytes(backup_id, relative_path).await?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// Deletes a backup's manifest, then any blobs no remaining backup refers to.
+pub async fn delete_backup(backup_id: &str) -> Result<()> {
+    let manifest_path = manifest_path(backup_id)?;
+    let _guard = STORE_LOCK.lock().await;
+    if manifest_path.exists() {
+        fs::remove_file(&manifest_path).await?;
+    }
+    collect_garbage(&backup_root_dir()).await
+}
+
+/// Removes the blobs no manifest refers to. Skipped entirely while any manifest cannot be read,
+/// since the blobs it refers to are unknown.
+async fn collect_garbage(store_dir: &Path) -> Result<()> {
+    let blobs_dir = store_dir.join(BLOBS_DIR);
+    if !blobs_dir.is_dir() {
+        return Ok(());
+    }
+    let (backups, unreadable) = all_backups(store_dir).await?;
+    if !unreadable.is_empty() {
+        log::warn!(
+            "Skipping backup garbage collection: {} manifest(s) could not be read",
+            unreadable.len()
+        );
+        return Ok(());
+    }
+    let referenced: HashSet<String> = backups
+        .into_iter()
+        .flat_map(|manifest| manifest.files.into_iter().filter_map(|file| file.hash))
+        .collect();
+
+    let mut entries = fs::read_dir(&blobs_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !referenced.contains(&name) {
+            fs::remove_file(entry.path()).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_blobs_are_shared_and_collected() {
+        let project = tempfile::tempdir().unwrap();
+        let root = project.path();
+        let content = format!("generated {}\n", Uuid::new_v4());
+        std::fs::write(root.join("a.txt"), &content).unwrap();
+        std::fs::write(root.join("copy.txt"), &content).unwrap();
+        let hash = fs_utils::content_hash(content.as_bytes());
+
+        let first = create_backup(root, vec![PathBuf::from("a.txt")], None)
+            .await
+            .unwrap();
+        let second = create_backup(
+            root,
+            vec![PathBuf::from("a.txt"), PathBuf::from("copy.txt")],
+            None,
+        )
+        .await
+        .unwrap();
+        let manifest = get_backup(&second).await.unwrap();
+        assert!(manifest
+            .files
+            .iter()
+            .all(|file| file.hash.as_deref() == Some(hash.as_str())));
+        assert!(blob_path(&hash).exists());
+
+        delete_backup(&first).await.unwrap();
+        assert!(blob_path(&hash).exists());
+        assert_eq!(
+            read_file_from_backup(&second, Path::new("copy.txt"))
+                .await
+                .unwrap(),
+            content
+        );
+
+        delete_backup(&second).await.unwrap();
+        assert!(!blob_path(&hash).exists());
+    }
+
+    #[tokio::test]
+    async fn test_garbage_collection_keeps_blobs_when_a_manifest_is_unreadable() {
+        let store = tempfile::tempdir().unwrap();
+        let store_dir = store.path();
+        std::fs::create_dir_all(store_dir.join(MANIFESTS_DIR)).unwrap();
+        std::fs::create_dir_all(store_dir.join(BLOBS_DIR)).unwrap();
+        let blob = store_dir.join(BLOBS_DIR).join("0123abcd");
+        std::fs::write(&blob, "content").unwrap();
+        let manifest = store_dir
+            .join(MANIFESTS_DIR)
+            .join(format!("{}.json", Uuid::new_v4()));
+        std::fs::write(&manifest, "{\"id\": ").unwrap();
+
+        collect_garbage(store_dir).await.unwrap();
+        assert!(blob.exists());
+
+        std::fs::remove_file(&manifest).unwrap();
+        collect_garbage(store_dir).await.unwrap();
+        assert!(!blob.exists());
+    }
+}
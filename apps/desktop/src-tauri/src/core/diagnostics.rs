@@ -0,0 +1,129 @@
+use crate::types::{Diagnostic, DiagnosticSeverity};
+use serde_json::Value;
+use std::path::Path;
+
+/// Extracts diagnostics from check output in the formats we understand: cargo's
+/// `--message-format=json` lines and tsc's `file(line,col): error TSxxxx: message` lines.
+pub(crate) fn parse_diagnostics(output: &str, root_path: &Path) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for line in output.lines() {
+        let Some(diagnostic) =
+            parse_cargo_line(line, root_path).or_else(|| parse_tsc_line(line, root_path))
+        else {
+            continue;
+        };
+        if !diagnostics.contains(&diagnostic) {
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+fn parse_cargo_line(line: &str, root_path: &Path) -> Option<Diagnostic> {
+    let value: Value = serde_json::from_str(line.trim()).ok()?;
+    if value.get("reason")?.as_str()? != "compiler-message" {
+        return None;
+    }
+    let message = value.get("message")?;
+    let severity = match message.get("level")?.as_str()? {
+        "error" => DiagnosticSeverity::Error,
+        "warning" => DiagnosticSeverity::Warning,
+        _ => return None,
+    };
+    let spans = message.get("spans")?.as_array()?;
+    let span = spans
+        .iter()
+        .find(|span| span.get("is_primary").and_then(Value::as_bool) == Some(true))
+        .or_else(|| spans.first())?;
+
+    Some(Diagnostic {
+        file_path: relative_to_root(span.get("file_name")?.as_str()?, root_path),
+        line: span
+            .get("line_start")
+            .and_then(Value::as_u64)
+            .map(|n| n as u32),
+        column: span
+            .get("column_start")
+            .and_then(Value::as_u64)
+            .map(|n| n as u32),
+        severity,
+        message: message.get("message")?.as_str()?.to_string(),
+        in_changed_file: false,
+    })
+}
+
+fn parse_tsc_line(line: &str, root_path: &Path) -> Option<Diagnostic> {
+    let (location, rest) = line.split_once("): ")?;
+    let (file_path, position) = location.rsplit_once('(')?;
+    let (line_number, column) = position.split_once(',')?;
+    let (severity, rest) = if let Some(rest) = rest.strip_prefix("error ") {
+        (DiagnosticSeverity::Error, rest)
+    } else {
+        (DiagnosticSeverity::Warning, rest.strip_prefix("warning ")?)
+    };
+    if !rest.starts_with("TS") {
+        return None;
+    }
+
+    Some(Diagnostic {
+        file_path: relative_to_root(file_path.trim(), root_path),
+        line: Some(line_number.trim().parse().ok()?),
+        column: Some(column.trim().parse().ok()?),
+        severity,
+        message: rest.to_string(),
+        in_changed_file: false,
+    })
+}
+
+fn relative_to_root(file_path: &str, root_path: &Path) -> String {
+    let path = Path::new(file_path);
+    path.strip_prefix(root_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cargo_messages() {
+        let output = concat!(
+            r#"{"reason":"compiler-artifact","target":{"name":"app"}}"#,
+            "\n",
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"mismatched types","spans":[{"file_name":"src/other.rs","line_start":1,"column_start":1,"is_primary":false},{"file_name":"src/lib.rs","line_start":12,"column_start":9,"is_primary":true}]}}"#,
+            "\n",
+            r#"{"reason":"compiler-message","message":{"level":"warning","message":"unused variable: `x`","spans":[{"file_name":"/repo/src/main.rs","line_start":3,"column_start":5,"is_primary":true}]}}"#,
+            "\n",
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"aborting due to 1 previous error","spans":[]}}"#,
+            "\n",
+            r#"{"reason":"build-finished","success":false}"#,
+        );
+
+        let diagnostics = parse_diagnostics(output, Path::new("/repo"));
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].file_path, "src/lib.rs");
+        assert_eq!(diagnostics[0].line, Some(12));
+        assert_eq!(diagnostics[0].column, Some(9));
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+        assert_eq!(diagnostics[0].message, "mismatched types");
+        assert_eq!(diagnostics[1].file_path, "src/main.rs");
+        assert_eq!(diagnostics[1].severity, DiagnosticSeverity::Warning);
+    }
+
+    #[test]
+    fn test_parse_tsc_messages() {
+        let output = "src/app.ts(4,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\nFound 1 error in src/app.ts:4\n";
+
+        let diagnostics = parse_diagnostics(output, Path::new("/repo"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file_path, "src/app.ts");
+        assert_eq!(diagnostics[0].line, Some(4));
+        assert_eq!(diagnostics[0].column, Some(7));
+        assert_eq!(
+            diagnostics[0].message,
+            "TS2322: Type 'string' is not assignable to type 'number'."
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use crate::core::{binary_utils, token_counter};
+use crate::types::{FileMetadata, FileNode};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// How much of a file is read to decide whether it is binary.
+const BINARY_SNIFF_LEN: u64 = 8192;
+
+/// Fills in `metadata` on `node` and everything below it, rolling file values up into their
+/// directories. `git_statuses` is keyed by path relative to `root_path`.
+pub fn annotate(node: &mut FileNode, root_path: &Path, git_statuses: &HashMap<String, String>) {
+    if !node.is_directory {
+        node.metadata = Some(file_metadata(
+            Path::new(&node.path),
+            root_path,
+            git_statuses,
+        ));
+        return;
+    }
+
+    let mut totals = FileMetadata {
+        file_count: Some(0),
+        dirty_count: Some(0),
+        ..Default::default()
+    };
+    for child in node.children.iter_mut().flatten() {
+        annotate(child, root_path, git_statuses);
+        if let Some(metadata) = &child.metadata {
+            roll_up(&mut totals, metadata);
+        }
+    }
+    node.metadata = Some(totals);
+}
+
+fn roll_up(totals: &mut FileMetadata, child: &FileMetadata) {
+    totals.size += child.size;
+    totals.modified_at = match (totals.modified_at, child.modified_at) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+    if let Some(tokens) = child.tokens {
+        totals.tokens = Some(totals.tokens.unwrap_or(0) + tokens);
+    }
+    // Files have no counts of their own; they count as one, and as dirty if git lists them.
+    let file_count = child.file_count.unwrap_or(1);
+    let dirty_count = child
+        .dirty_count
+        .unwrap_or(child.git_status.is_some() as u32);
+    totals.file_count = Some(totals.file_count.unwrap_or(0) + file_count);
+    totals.dirty_count = Some(totals.dirty_count.unwrap_or(0) + dirty_count);
+}
+
+fn file_metadata(
+    path: &Path,
+    root_path: &Path,
+    git_statuses: &HashMap<String, String>,
+) -> FileMetadata {
+    let Ok(metadata) = fs::metadata(path) else {
+        return FileMetadata::default();
+    };
+    let relative_path = path
+        .strip_prefix(root_path)
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_default();
+    FileMetadata {
+        size: metadata.len() as f64,
+        modified_at: metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as f64),
+        is_binary: is_binary(path),
+        git_status: git_statuses.get(&relative_path).cloned(),
+        tokens: token_counter::cached_tokens(path, &metadata),
+        file_count: None,
+        dirty_count: None,
+    }
+}
+
+fn is_binary(path: &Path) -> Option<bool> {
+    let mut head = Vec::new();
+    fs::File::open(path)
+        .ok()?
+        .take(BINARY_SNIFF_LEN)
+        .read_to_end(&mut head)
+        .ok()?;
+    Some(binary_utils::is_binary_bytes(&head))
+}
@@ -0,0 +1,314 @@
+use crate::core::parser::{self, IntermediateOperation};
+use crate::core::prompt_estimator::{DIFF_FORMATTING_RULES, WHOLE_FORMATTING_RULES};
+use crate::core::shell::ShellOutput;
+use crate::types::{Diagnostic, DiagnosticSeverity, EditFormat, PatchBlock, Truncation};
+use std::ops::Range;
+
+/// Files up to this many lines are sent in full; larger ones as a window per failed block.
+const FULL_FILE_MAX_LINES: usize = 200;
+const WINDOW_CONTEXT_LINES: usize = 15;
+/// Lines sent from each end of a large file when no part of it resembles the failed blocks.
+const HEAD_TAIL_LINES: usize = 100;
+
+/// The operation a response was cut off in, as needed to ask for and merge a continuation.
+pub(crate) struct TruncatedOperation {
+    pub command: &'static str,
+    pub file_path: String,
+    pub truncation: Truncation,
+    /// Whether the code block was opened before the cut-off point.
+    pub block_opened: bool,
+}
+
+pub(crate) fn find_truncated_operation(
+    operations: &[IntermediateOperation],
+) -> Option<TruncatedOperation> {
+    operations.iter().find_map(|op| {
+        let (command, file_path, truncation) = match op {
+            IntermediateOperation::Patch {
+                file_path,
+                truncation: Some(truncation),
+                ..
+            } => ("PATCH", file_path, truncation),
+            IntermediateOperation::Overwrite {
+                file_path,
+                is_new_file,
+                truncation: Some(truncation),
+                ..
+            } => {
+                let command = if *is_new_file { "CREATE" } else { "OVERWRITE" };
+                (command, file_path, truncation)
+            }
+            _ => return None,
+        };
+        Some(TruncatedOperation {
+            command,
+            file_path: file_path.clone(),
+            truncation: truncation.clone(),
+            block_opened: !is_command_for(&truncation.last_complete_line, file_path),
+        })
+    })
+}
+
+fn is_command_for(line: &str, file_path: &str) -> bool {
+    parser::parse_command_line(line)
+        .is_ok_and(|(_, (_, args))| parser::sanitize_path(args) == file_path)
+}
+
+pub(crate) fn build_continuation_prompt(op: &TruncatedOperation) -> String {
+    if !op.block_opened {
+        return format!(
+            "Your previous response was cut off right after the {} command for `{}`, before its code block.\n\nWrite that code block now, starting with its opening code fence, without repeating the command. Then continue with any remaining operations using the same file editing format.",
+            op.command, op.file_path
+        );
+    }
+    let mut prompt = format!(
+        "Your previous response was cut off while writing the {} block for `{}`.\n\n",
+        op.command, op.file_path
+    );
+    prompt.push_str("The last complete line I received was:\n\n````\n");
+    prompt.push_str(&op.truncation.last_complete_line);
+    prompt.push_str("\n````\n\n");
+    prompt.push_str("Continue from the line right after it. ");
+    prompt.push_str("Do not repeat anything that was already written, and do not repeat the ");
+    prompt.push_str(op.command);
+    prompt.push_str(" command or open a new code fence.");
+    if op.command == "PATCH" {
+        prompt.push_str(
+            " If you were in the middle of a search/replace block, write that block again in full, starting from `<<<<<<< SEARCH`.",
+        );
+    }
+    prompt.push_str(
+        "\n\nClose the code fence when this file is finished, then continue with any remaining operations using the same file editing format.",
+    );
+    prompt
+}
+
+/// Appends `continuation` to the complete part of `markdown`, dropping anything the model
+/// repeated from before the cut-off point.
+pub(crate) fn merge_continuation(
+    markdown: &str,
+    op: &TruncatedOperation,
+    continuation: &str,
+) -> String {
+    let kept: Vec<&str> = markdown
+        .split('\n')
+        .take(op.truncation.resume_after_line as usize)
+        .collect();
+
+    let mut lines = continuation.split('\n').peekable();
+    let skip_blank = |lines: &mut std::iter::Peekable<std::str::Split<'_, char>>| {
+        while lines.next_if(|line| line.trim().is_empty()).is_some() {}
+    };
+
+    skip_blank(&mut lines);
+    let repeated_command = lines
+        .next_if(|line| is_command_for(line, &op.file_path))
+        .is_some();
+    if repeated_command {
+        skip_blank(&mut lines);
+    }
+    // Without an opened block the continuation's own fence opens it.
+    if op.block_opened {
+        // A bare fence without a repeated command may legitimately close the block.
+        lines.next_if(|line| {
+            let line = line.trim();
+            line.starts_with("```") && (repeated_command || line.len() > 3)
+        });
+        let last_complete_line = op.truncation.last_complete_line.trim();
+        if !last_complete_line.is_empty() && !last_complete_line.starts_with("```") {
+            lines.next_if(|line| line.trim() == last_complete_line);
+        }
+    }
+
+    let mut merged = kept.join("\n");
+    merged.push('\n');
+    merged.push_str(&lines.collect::<Vec<_>>().join("\n"));
+    merged
+}
+
+/// A patched file with blocks that could not be applied, alongside what is on disk now.
+pub(crate) struct FailedPatch {
+    pub file_path: String,
+    pub current_content: Option<String>,
+    pub failed_blocks: Vec<PatchBlock>,
+}
+
+pub(crate) fn build_repair_prompt(failures: &[FailedPatch], edit_format: &EditFormat) -> String {
+    let mut prompt = String::from(
+        "Some of the changes in your previous response could not be applied because their SEARCH text does not match the current files.\n\n",
+    );
+
+    for failure in failures {
+        prompt.push_str(&format!("## {}\n\n", failure.file_path));
+        prompt.push_str("Failed blocks:\n\n````\n");
+        for block in &failure.failed_blocks {
+            prompt.push_str("<<<<<<< SEARCH\n");
+            push_line_block(&mut prompt, &block.search);
+            prompt.push_str("=======\n");
+            push_line_block(&mut prompt, &block.replace);
+            prompt.push_str(">>>>>>> REPLACE\n");
+        }
+        prompt.push_str("````\n\n");
+
+        let Some(content) = failure.current_content.as_deref() else {
+            prompt.push_str("This file does not exist.\n\n");
+            continue;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        if matches!(edit_format, EditFormat::Whole) || lines.len() <= FULL_FILE_MAX_LINES {
+            prompt.push_str("Current content:\n\n````\n");
+            push_line_block(&mut prompt, content);
+            prompt.push_str("````\n\n");
+            continue;
+        }
+        let mut windows = merged_windows(&lines, &failure.failed_blocks);
+        if windows.is_empty() {
+            prompt.push_str(&format!(
+                "No part of the current content, {} lines long, resembles the failed blocks, so here are its first and last {HEAD_TAIL_LINES} lines.\n\n",
+                lines.len()
+            ));
+            windows = vec![
+                0..HEAD_TAIL_LINES,
+                lines.len().saturating_sub(HEAD_TAIL_LINES)..lines.len(),
+            ];
+        }
+        for window in windows {
+            prompt.push_str(&format!(
+                "Current content, lines {}-{} of {}:\n\n````\n",
+                window.start + 1,
+                window.end,
+                lines.len()
+            ));
+            push_line_block(&mut prompt, &lines[window].join("\n"));
+            prompt.push_str("````\n\n");
+        }
+    }
+
+    prompt.push_str(match edit_format {
+        EditFormat::Diff => "Rewrite only the failed blocks so that each SEARCH section matches the current content exactly, character for character. Blocks that were already applied must not be repeated.\n\n",
+        EditFormat::Whole => "Output the complete updated content of each file above, with the intended changes applied to its current content.\n\n",
+    });
+    prompt.push_str("--- BEGIN File Editing Rules ---\n");
+    prompt.push_str(match edit_format {
+        EditFormat::Diff => DIFF_FORMATTING_RULES,
+        EditFormat::Whole => WHOLE_FORMATTING_RULES,
+    });
+    prompt.push_str("\n--- END File Editing Rules ---\n");
+    prompt
+}
+
+fn push_line_block(prompt: &mut String, text: &str) {
+    prompt.push_str(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        prompt.push('\n');
+    }
+}
+
+/// Line ranges around the closest match of each block, merged where they overlap. Blocks that
+/// match nowhere get no window.
+fn merged_windows(lines: &[&str], blocks: &[PatchBlock]) -> Vec<Range<usize>> {
+    let mut windows: Vec<Range<usize>> = blocks
+        .iter()
+        .filter_map(|block| {
+            let (start, len) = nearest_match(lines, &block.search)?;
+            Some(
+                start.saturating_sub(WINDOW_CONTEXT_LINES)
+                    ..(start + len + WINDOW_CONTEXT_LINES).min(lines.len()),
+            )
+        })
+        .collect();
+    windows.sort_by_key(|window| window.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for window in windows {
+        match merged.last_mut() {
+            Some(last) if window.start <= last.end => last.end = last.end.max(window.end),
+            _ => merged.push(window),
+        }
+    }
+    merged
+}
+
+/// Start line and length of the region whose lines agree with `search` at the most positions,
+/// or `None` if no line agrees.
+fn nearest_match(lines: &[&str], search: &str) -> Option<(usize, usize)> {
+    let search_lines: Vec<&str> = search.lines().map(str::trim).collect();
+    let len = search_lines.len().clamp(1, lines.len().max(1));
+
+    let mut best = (0, 0);
+    for start in 0..lines.len().saturating_sub(len) + 1 {
+        let score = lines[start..]
+            .iter()
+            .zip(&search_lines)
+            .filter(|(line, search_line)| !search_line.is_empty() && line.trim() == **search_line)
+            .count();
+        if score > best.1 {
+            best = (start, score);
+        }
+    }
+    (best.1 > 0).then_some((best.0, len))
+}
+
+/// Output lines kept when a failed check produced no diagnostics we could parse.
+const MAX_OUTPUT_TAIL_LINES: usize = 50;
+
+/// Describes a check command's outcome so it can be pasted into the next prompt.
+pub(crate) fn build_verification_context(
+    command: &str,
+    output: &ShellOutput,
+    timeout_secs: u32,
+    diagnostics: &[Diagnostic],
+) -> String {
+    let mut context = if output.timed_out {
+        format!("The check command `{command}` timed out after {timeout_secs}s.\n")
+    } else if output.success() {
+        format!("The check command `{command}` succeeded.\n")
+    } else {
+        format!(
+            "The check command `{command}` failed with exit code {}.\n",
+            output.exit_code.unwrap_or(-1)
+        )
+    };
+
+    let (changed, other): (Vec<&Diagnostic>, Vec<&Diagnostic>) =
+        diagnostics.iter().partition(|d| d.in_changed_file);
+    for (title, group) in [
+        ("Diagnostics in the files that were just changed", changed),
+        ("Other diagnostics", other),
+    ] {
+        if group.is_empty() {
+            continue;
+        }
+        context.push_str(&format!("\n{title}:\n"));
+        for diagnostic in group {
+            context.push_str(&format!("- {}\n", format_diagnostic(diagnostic)));
+        }
+    }
+
+    if diagnostics.is_empty() && !output.success() {
+        let combined = format!("{}{}", output.stdout, output.stderr);
+        let lines: Vec<&str> = combined.lines().collect();
+        let tail = &lines[lines.len().saturating_sub(MAX_OUTPUT_TAIL_LINES)..];
+        if !tail.is_empty() {
+            context.push_str("\nOutput:\n\n````\n");
+            context.push_str(&tail.join("\n"));
+            context.push_str("\n````\n");
+        }
+    }
+    context
+}
+
+fn format_diagnostic(diagnostic: &Diagnostic) -> String {
+    let mut location = diagnostic.file_path.clone();
+    if let Some(line) = diagnostic.line {
+        location.push_str(&format!(":{line}"));
+        if let Some(column) = diagnostic.column {
+            location.push_str(&format!(":{column}"));
+        }
+    }
+    let severity = match diagnostic.severity {
+        DiagnosticSeverity::Error => "error",
+        DiagnosticSeverity::Warning => "warning",
+    };
+    format!("{location}: {severity}: {}", diagnostic.message)
+}
@@ -1,13 +1,15 @@
-use crate::core::binary_utils;
-use crate::types::{FileNode, IgnoreSettings};
+use crate::core::{binary_utils, protected_paths};
+use crate::types::{FileNode, IgnoreSettings, SymlinkPolicy};
 use anyhow::{anyhow, Result};
 use ignore::{DirEntry, WalkBuilder};
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::io::Write;
 use std::path::{Component, Path, PathBuf};
-use std::sync::mpsc;
+use std::sync::{mpsc, RwLock};
 use tokio::fs;
 use tokio::io::AsyncReadExt;
-use uuid::Uuid;
 
 fn normalize_path_str(p: &Path) -> String {
     p.to_string_lossy().replace('\\', "/")
@@ -79,14 +81,7 @@ pub async fn list_directory_recursive(
                         .map(|child_path| build_tree_from_map(child_path, parent_map, is_dir_map))
                         .collect();
 
-                    children_nodes.sort_by(|a, b| {
-                        if a.is_directory != b.is_directory {
-                            b.is_directory.cmp(&a.is_directory)
-                        } else {
-                            a.name.to_lowercase().cmp(&b.name.to_lowercase())
-                        }
-                    });
-
+                    sort_nodes(&mut children_nodes);
                     children_nodes
                 })
             } else {
@@ -102,31 +97,144 @@ pub async fn list_directory_recursive(
                     .to_string(),
                 children,
                 is_directory,
+                child_count: None,
+                metadata: None,
             }
         }
 
         let mut root_node = build_tree_from_map(root_path, &parent_map, &is_dir_map);
-
-        root_node.name = root_path
-            .file_name()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_else(|| root_path.to_string_lossy().to_string());
+        root_node.name = root_name(root_path);
 
         Ok(root_node)
     })
     .await?
 }
 
+/// Lists `dir_path` down to `depth` levels below it, with the same ignore rules as
+/// [`list_directory_recursive`]. Directories on the last level come back without children but
+/// with `child_count` set, so the tree can be expanded on demand.
+pub async fn list_directory(
+    root_path: &Path,
+    dir_path: &Path,
+    settings: IgnoreSettings,
+    depth: u32,
+) -> Result<FileNode> {
+    ensure_safe_path(dir_path, root_path)?;
+    let root_path = normalize_path(root_path);
+    let dir_path = normalize_path(dir_path);
+    if !dir_path.is_dir() {
+        return Err(anyhow!("Not a directory: {}", dir_path.display()));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        // Walk from the root so that ignore rules apply exactly as in a full listing, but only
+        // descend along the way to `dir_path` and, below it, one level past `depth` to count
+        // the children of the last level.
+        let base_depth = dir_path.strip_prefix(&root_path)?.components().count();
+        let mut builder = WalkBuilder::new(&root_path);
+        builder
+            .hidden(false)
+            .git_ignore(settings.respect_gitignore)
+            .max_depth(Some(base_depth + depth as usize + 1));
+        if !settings.custom_ignore_patterns.is_empty() {
+            builder.add_custom_ignore_patterns(&settings.custom_ignore_patterns)?;
+        }
+        let target = dir_path.clone();
+        builder.filter_entry(move |entry| {
+            target.starts_with(entry.path()) || entry.path().starts_with(&target)
+        });
+
+        let mut children_map: HashMap<PathBuf, Vec<(PathBuf, bool)>> = HashMap::new();
+        for entry in builder.build().flatten() {
+            let path = entry.path();
+            if path == dir_path || !path.starts_with(&dir_path) {
+                continue;
+            }
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            if let Some(parent) = path.parent() {
+                children_map
+                    .entry(parent.to_path_buf())
+                    .or_default()
+                    .push((path.to_path_buf(), is_dir));
+            }
+        }
+
+        fn build_level(
+            path: &Path,
+            is_directory: bool,
+            remaining: u32,
+            children_map: &HashMap<PathBuf, Vec<(PathBuf, bool)>>,
+        ) -> FileNode {
+            let entries = children_map
+                .get(path)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let children = (is_directory && remaining > 0).then(|| {
+                let mut nodes: Vec<FileNode> = entries
+                    .iter()
+                    .map(|(child, is_dir)| build_level(child, *is_dir, remaining - 1, children_map))
+                    .collect();
+                sort_nodes(&mut nodes);
+                nodes
+            });
+            FileNode {
+                path: normalize_path_str(path),
+                name: path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string(),
+                children,
+                is_directory,
+                child_count: is_directory.then_some(entries.len() as u32),
+                metadata: None,
+            }
+        }
+
+        let mut node = build_level(&dir_path, true, depth, &children_map);
+        if dir_path == root_path {
+            node.name = root_name(&root_path);
+        }
+        Ok(node)
+    })
+    .await?
+}
+
+/// Directories first, then by case-insensitive name.
+fn sort_nodes(nodes: &mut [FileNode]) {
+    nodes.sort_by(|a, b| {
+        if a.is_directory != b.is_directory {
+            b.is_directory.cmp(&a.is_directory)
+        } else {
+            a.name.to_lowercase().cmp(&b.name.to_lowercase())
+        }
+    });
+}
+
+fn root_name(root_path: &Path) -> String {
+    root_path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| root_path.to_string_lossy().to_string())
+}
+
 pub async fn read_file_content(path: &Path) -> Result<String> {
     let bytes = fs::read(path).await?;
     Ok(String::from_utf8_lossy(&bytes).to_string())
 }
 
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
 pub async fn read_file_bytes(path: &Path) -> Result<Vec<u8>> {
     fs::read(path).await.map_err(anyhow::Error::from)
 }
 
-fn normalize_path(path: &Path) -> PathBuf {
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
     let mut result = PathBuf::new();
     for component in path.components() {
         match component {
@@ -142,37 +250,177 @@ fn normalize_path(path: &Path) -> PathBuf {
     result
 }
 
-fn ensure_safe_path(path: &Path, root_path: &Path) -> Result<()> {
+static SYMLINK_POLICIES: Lazy<RwLock<HashMap<PathBuf, SymlinkPolicy>>> =
+    Lazy::new(Default::default);
+
+pub fn set_symlink_policy(root_path: &Path, policy: SymlinkPolicy) {
+    SYMLINK_POLICIES
+        .write()
+        .unwrap()
+        .insert(normalize_path(root_path), policy);
+}
+
+fn symlink_policy(root_path: &Path) -> SymlinkPolicy {
+    SYMLINK_POLICIES
+        .read()
+        .unwrap()
+        .get(root_path)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Confines `path` to `root_path`: lexically, and by resolving the real path of its deepest
+/// existing ancestor, so a symlink inside the project cannot lead outside it.
+pub(crate) fn ensure_safe_path(path: &Path, root_path: &Path) -> Result<()> {
     let normalized_path = normalize_path(path);
     let normalized_root = normalize_path(root_path);
-    if !normalized_path.starts_with(normalized_root) {
+    if !normalized_path.starts_with(&normalized_root) {
         return Err(anyhow!(
             "Security Error: Access denied. Path {:?} is outside of project root.",
             path
         ));
     }
+
+    let policy = symlink_policy(&normalized_root);
+    let canonical_root = std::fs::canonicalize(&normalized_root)?;
+    let mut current = normalized_root.clone();
+    let mut deepest_existing = normalized_root.clone();
+    for component in normalized_path.strip_prefix(&normalized_root)?.components() {
+        current.push(component);
+        let Ok(metadata) = std::fs::symlink_metadata(&current) else {
+            break;
+        };
+        if metadata.file_type().is_symlink() && policy == SymlinkPolicy::Deny {
+            return Err(anyhow!(
+                "Security Error: Access denied. Path {:?} goes through symlink {:?}.",
+                path,
+                current
+            ));
+        }
+        deepest_existing = current.clone();
+    }
+
+    let real_path = std::fs::canonicalize(&deepest_existing).map_err(|_| {
+        anyhow!(
+            "Security Error: Access denied. Cannot resolve {:?}.",
+            deepest_existing
+        )
+    })?;
+    if !real_path.starts_with(&canonical_root) {
+        return Err(anyhow!(
+            "Security Error: Access denied. Path {:?} resolves outside of project root.",
+            path
+        ));
+    }
     Ok(())
 }
 
+/// Joins a path taken from outside input, such as a model response, onto `root_path`. Absolute
+/// paths and `..` are refused outright, and the result is confined with [`ensure_safe_path`].
+pub(crate) fn resolve_relative(root_path: &Path, relative_path: &str) -> Result<PathBuf> {
+    let is_relative = Path::new(relative_path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !is_relative {
+        return Err(anyhow!(
+            "Security Error: Access denied. Path {:?} is not relative to the project root.",
+            relative_path
+        ));
+    }
+    let path = root_path.join(relative_path);
+    ensure_safe_path(&path, root_path)?;
+    Ok(path)
+}
+
+pub(crate) fn ensure_not_protected(path: &Path, root_path: &Path) -> Result<()> {
+    protected_paths::ensure_not_denied(path, root_path)
+}
+
+/// Runs [`ensure_safe_path`] and [`ensure_not_protected`] for each of `paths` on the blocking
+/// pool, since both touch the file system.
+async fn check_writable(paths: &[&Path], root_path: &Path) -> Result<()> {
+    let paths: Vec<PathBuf> = paths.iter().map(|path| path.to_path_buf()).collect();
+    let root_path = root_path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        for path in &paths {
+            ensure_safe_path(path, &root_path)?;
+            ensure_not_protected(path, &root_path)?;
+        }
+        Ok(())
+    })
+    .await?
+}
+
 pub async fn write_file_content(path: &Path, content: &str, root_path: &Path) -> Result<()> {
-    ensure_safe_path(path, root_path)?;
+    write_file_bytes(path, content.as_bytes().to_vec(), root_path).await
+}
+
+pub async fn write_file_bytes(path: &Path, bytes: Vec<u8>, root_path: &Path) -> Result<()> {
+    check_writable(&[path], root_path).await?;
 
     if let Some(parent) = path.parent() {
         if !parent.exists() {
             fs::create_dir_all(parent).await?;
         }
     }
-    fs::write(path, content).await.map_err(anyhow::Error::from)
+    write_atomic(path, bytes).await
+}
+
+/// Replaces the file at `path` without ever leaving it partially written: the content goes to a
+/// synced sibling temp file, which takes over the original's permissions and is renamed into
+/// place. A symlink is written through rather than replaced.
+pub async fn write_atomic(path: &Path, bytes: Vec<u8>) -> Result<()> {
+    let path = path.to_owned();
+    tokio::task::spawn_blocking(move || write_atomic_sync(&path, &bytes)).await?
+}
+
+fn write_atomic_sync(path: &Path, bytes: &[u8]) -> Result<()> {
+    let is_symlink = std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink());
+    let target = if is_symlink {
+        std::fs::canonicalize(path)?
+    } else {
+        path.to_path_buf()
+    };
+    let parent = target
+        .parent()
+        .ok_or_else(|| anyhow!("Path {:?} has no parent directory", target))?;
+    let prefix = format!(
+        ".{}.",
+        target.file_name().unwrap_or_default().to_string_lossy()
+    );
+
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(&prefix).suffix(".tmp");
+    #[cfg(unix)]
+    {
+        // New files get the usual mode (subject to umask) instead of tempfile's 0600.
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(std::fs::Permissions::from_mode(0o666));
+    }
+    let mut temp_file = builder.tempfile_in(parent)?;
+    temp_file.write_all(bytes)?;
+    if let Ok(metadata) = std::fs::metadata(&target) {
+        temp_file
+            .as_file()
+            .set_permissions(metadata.permissions())?;
+    }
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(&target).map_err(|e| e.error)?;
+
+    #[cfg(unix)]
+    if let Ok(dir) = std::fs::File::open(parent) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
 }
 
 pub async fn delete_file(path: &Path, root_path: &Path) -> Result<()> {
-    ensure_safe_path(path, root_path)?;
+    check_writable(&[path], root_path).await?;
     fs::remove_file(path).await.map_err(anyhow::Error::from)
 }
 
 pub async fn move_file(from: &Path, to: &Path, root_path: &Path) -> Result<()> {
-    ensure_safe_path(from, root_path)?;
-    ensure_safe_path(to, root_path)?;
+    check_writable(&[from, to], root_path).await?;
 
     if let Some(parent) = to.parent() {
         if !parent.exists() {
@@ -192,81 +440,3 @@ pub async fn is_binary(path: &Path) -> Result<bool> {
 
     Ok(binary_utils::is_binary_bytes(&buffer[..n]))
 }
-
-fn get_backup_root_dir() -> PathBuf {
-    std::env::temp_dir().join("repo-wizard-backups")
-}
-
-fn get_backup_dir(backup_id: &str) -> PathBuf {
-    get_backup_root_dir().join(backup_id)
-}
-
-pub async fn backup_files(root_path: &Path, paths: Vec<PathBuf>) -> Result<String> {
-    let backup_id = Uuid::new_v4().to_string();
-    let backup_root = get_backup_dir(&backup_id);
-    fs::create_dir_all(&backup_root).await?;
-
-    for path in paths {
-        let full_path = root_path.join(&path);
-        if !full_path.is_file() {
-            continue;
-        }
-
-        let backup_path = backup_root.join(&path);
-
-        if let Some(parent) = backup_path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent).await?;
-            }
-        }
-        let content = fs::read(&full_path).await?;
-        fs::write(&backup_path, &content).await?;
-    }
-    Ok(backup_id)
-}
-
-pub async fn revert_file_from_backup(
-    root_path: &Path,
-    backup_id: &str,
-    relative_path: &Path,
-) -> Result<()> {
-    let backup_file_path = get_backup_dir(backup_id).join(relative_path);
-    if !backup_file_path.exists() {
-        return Err(anyhow!(
-            "Backup for file {} not found in backup {}",
-            relative_path.display(),
-            backup_id
-        ));
-    }
-
-    let dest_path = root_path.join(relative_path);
-    if let Some(parent) = dest_path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent).await?;
-        }
-    }
-    fs::copy(&backup_file_path, &dest_path).await?;
-    Ok(())
-}
-
-pub async fn read_file_from_backup(backup_id: &str, relative_path: &Path) -> Result<String> {
-    let backup_file_path = get_backup_dir(backup_id).join(relative_path);
-    if !backup_file_path.exists() {
-        return Err(anyhow!(
-            "File {} not found in backup {}",
-            relative_path.display(),
-            backup_id
-        ));
-    }
-    fs::read_to_string(&backup_file_path)
-        .await
-        .map_err(anyhow::Error::from)
-}
-
-pub async fn delete_backup(backup_id: &str) -> Result<()> {
-    let backup_root = get_backup_dir(backup_id);
-    if backup_root.exists() {
-        fs::remove_dir_all(backup_root).await?;
-    }
-    Ok(())
-}
@@ -0,0 +1,224 @@
+use crate::types::{DiffHunk, DiffLine, DiffLineKind, Hunk, LineStats, PatchBlock};
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use similar::{ChangeTag, DiffTag, TextDiff};
+
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// A replacement of `old_len` lines starting at 0-based line `old_index` of the original.
+struct LineEdit {
+    old_index: usize,
+    old_len: usize,
+    new_text: String,
+}
+
+/// Hunks for a patch, one per applied SEARCH/REPLACE block. Returns `None` when the blocks
+/// cannot be expressed as independent edits of `original` that reproduce `content`.
+pub(crate) fn block_hunks(
+    file_path: &str,
+    original: &str,
+    content: &str,
+    blocks: &[PatchBlock],
+) -> Option<Vec<Hunk>> {
+    let lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let mut edits = blocks
+        .iter()
+        .filter(|block| block.applied)
+        .map(|block| locate_block(original, &lines, block))
+        .collect::<Option<Vec<_>>>()?;
+    edits.sort_by_key(|edit| edit.old_index);
+    if edits
+        .windows(2)
+        .any(|pair| pair[0].old_index + pair[0].old_len > pair[1].old_index)
+    {
+        return None;
+    }
+
+    let hunks = build_hunks(file_path, &lines, edits);
+    (apply_hunks(original, &hunks).ok()? == content).then_some(hunks)
+}
+
+/// Hunks for the line diff between `original` and `content`, without context lines.
+pub(crate) fn diff_hunks(file_path: &str, original: &str, content: &str) -> Vec<Hunk> {
+    let old_lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let diff = TextDiff::from_lines(original, content);
+
+    let mut edits: Vec<LineEdit> = Vec::new();
+    let mut last_old_end = None;
+    for op in diff.ops() {
+        if op.tag() == DiffTag::Equal {
+            continue;
+        }
+        let new_text = new_lines[op.new_range()].concat();
+        match edits.last_mut() {
+            // Adjacent delete/insert ops form a single change.
+            Some(edit) if last_old_end == Some(op.old_range().start) => {
+                edit.old_len += op.old_range().len();
+                edit.new_text.push_str(&new_text);
+            }
+            _ => edits.push(LineEdit {
+                old_index: op.old_range().start,
+                old_len: op.old_range().len(),
+                new_text,
+            }),
+        }
+        last_old_end = Some(op.old_range().end);
+    }
+
+    build_hunks(file_path, &old_lines, edits)
+}
+
+/// The line diff between `original` and `content` for display, with added/removed totals.
+pub(crate) fn line_diff(original: &str, content: &str) -> (Vec<DiffHunk>, LineStats) {
+    let diff = TextDiff::from_lines(original, content);
+    let mut stats = LineStats::default();
+    let hunks = diff
+        .grouped_ops(DIFF_CONTEXT_LINES)
+        .iter()
+        .filter_map(|group| {
+            let first = group.first()?;
+            let last = group.last()?;
+            let old_range = first.old_range().start..last.old_range().end;
+            let new_range = first.new_range().start..last.new_range().end;
+            let lines = group
+                .iter()
+                .flat_map(|op| diff.iter_changes(op))
+                .map(|change| {
+                    let kind = match change.tag() {
+                        ChangeTag::Equal => DiffLineKind::Context,
+                        ChangeTag::Insert => {
+                            stats.added += 1;
+                            DiffLineKind::Added
+                        }
+                        ChangeTag::Delete => {
+                            stats.removed += 1;
+                            DiffLineKind::Removed
+                        }
+                    };
+                    let value = change.value();
+                    DiffLine {
+                        kind,
+                        content: value.strip_suffix('\n').unwrap_or(value).to_string(),
+                    }
+                })
+                .collect();
+            Some(DiffHunk {
+                old_start: old_range.start as u32 + 1,
+                old_lines: old_range.len() as u32,
+                new_start: new_range.start as u32 + 1,
+                new_lines: new_range.len() as u32,
+                lines,
+            })
+        })
+        .collect();
+    (hunks, stats)
+}
+
+/// Rebuilds a file from `original` with only `hunks` applied. Hunks refer to lines of the
+/// original, so any non-overlapping subset can be applied.
+pub(crate) fn apply_hunks(original: &str, hunks: &[Hunk]) -> Result<String> {
+    let lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let mut sorted: Vec<&Hunk> = hunks.iter().collect();
+    sorted.sort_by_key(|hunk| hunk.old_start);
+
+    let mut result = String::new();
+    let mut next_line = 0;
+    for hunk in sorted {
+        let start = hunk.old_start.saturating_sub(1) as usize;
+        let end = start + hunk.old_lines as usize;
+        if start < next_line {
+            return Err(anyhow!("Hunk {} overlaps another selected hunk", hunk.id));
+        }
+        if end > lines.len() || lines[start..end].concat() != hunk.old_text {
+            return Err(anyhow!(
+                "Hunk {} no longer matches the original file",
+                hunk.id
+            ));
+        }
+        result.push_str(&lines[next_line..start].concat());
+        result.push_str(&hunk.new_text);
+        next_line = end;
+    }
+    result.push_str(&lines[next_line..].concat());
+    Ok(result)
+}
+
+fn locate_block(original: &str, lines: &[&str], block: &PatchBlock) -> Option<LineEdit> {
+    let search = block.search.replace("\r\n", "\n");
+    if search.trim().is_empty() {
+        return None;
+    }
+
+    // Same matching order as `apply_patch`: exact text first, then trimmed lines.
+    if let Some(offset) = original.find(&search) {
+        let end = offset + search.len();
+        let at_line_start = offset == 0 || original.as_bytes()[offset - 1] == b'\n';
+        // The parser strips the final newline of SEARCH/REPLACE, so the match usually stops
+        // just before the newline that ends its last line.
+        let before_newline = original.as_bytes().get(end) == Some(&b'\n');
+        if !at_line_start || !(search.ends_with('\n') || before_newline || end == original.len()) {
+            return None;
+        }
+        let mut new_text = block.replace.clone();
+        if before_newline && !search.ends_with('\n') {
+            new_text.push('\n');
+        }
+        return Some(LineEdit {
+            old_index: original[..offset].matches('\n').count(),
+            old_len: search.split_inclusive('\n').count(),
+            new_text,
+        });
+    }
+
+    let search_lines: Vec<&str> = search.trim().lines().map(str::trim).collect();
+    if search_lines.len() > lines.len() {
+        return None;
+    }
+    let trimmed: Vec<&str> = lines.iter().map(|line| line.trim()).collect();
+    let old_index = (0..=lines.len() - search_lines.len())
+        .find(|&i| trimmed[i..i + search_lines.len()] == search_lines[..])?;
+    let mut new_text = block.replace.clone();
+    if !new_text.ends_with('\n') {
+        new_text.push('\n');
+    }
+    Some(LineEdit {
+        old_index,
+        old_len: search_lines.len(),
+        new_text,
+    })
+}
+
+fn build_hunks(file_path: &str, lines: &[&str], edits: Vec<LineEdit>) -> Vec<Hunk> {
+    let mut offset: i64 = 0;
+    edits
+        .into_iter()
+        .map(|edit| {
+            let old_text = lines[edit.old_index..edit.old_index + edit.old_len].concat();
+            let new_lines = edit.new_text.split_inclusive('\n').count();
+            let hunk = Hunk {
+                id: hunk_id(file_path, edit.old_index, &old_text, &edit.new_text),
+                old_start: edit.old_index as u32 + 1,
+                old_lines: edit.old_len as u32,
+                new_start: (edit.old_index as i64 + offset) as u32 + 1,
+                new_lines: new_lines as u32,
+                old_text,
+                new_text: edit.new_text,
+            };
+            offset += new_lines as i64 - edit.old_len as i64;
+            hunk
+        })
+        .collect()
+}
+
+fn hunk_id(file_path: &str, old_index: usize, old_text: &str, new_text: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [file_path, &old_index.to_string(), old_text, new_text] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hasher.finalize()[..6]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
@@ -0,0 +1,185 @@
+use crate::types::IgnoreSettings;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{Match, WalkBuilder};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const IGNORE_FILE_NAMES: &[&str] = &[".ignore", ".gitignore"];
+
+/// The ignore rules of a project, compiled once, so that paths can be tested without walking
+/// the file system. Follows the same precedence as the walker: custom patterns first, then the
+/// ignore files of the closest directory outward (`.ignore` before `.gitignore`), then the
+/// repository's exclude file and the global gitignore.
+pub struct IgnoreMatcher {
+    root_path: PathBuf,
+    custom: Option<Gitignore>,
+    by_dir: HashMap<PathBuf, Vec<Gitignore>>,
+    fallback: Vec<Gitignore>,
+}
+
+impl IgnoreMatcher {
+    pub fn new(root_path: &Path, settings: &IgnoreSettings) -> Self {
+        let custom = (!settings.custom_ignore_patterns.trim().is_empty())
+            .then(|| {
+                let mut builder = GitignoreBuilder::new(root_path);
+                for line in settings.custom_ignore_patterns.lines() {
+                    if let Err(e) = builder.add_line(None, line) {
+                        log::error!("Failed to apply custom ignore pattern {line:?}: {e}");
+                    }
+                }
+                builder.build().ok()
+            })
+            .flatten();
+
+        let repo_root = settings
+            .respect_gitignore
+            .then(|| root_path.ancestors().find(|dir| dir.join(".git").exists()))
+            .flatten();
+        let mut by_dir: HashMap<PathBuf, Vec<Gitignore>> = HashMap::new();
+        let mut add_ignore_files = |dir: &Path| {
+            for name in IGNORE_FILE_NAMES {
+                if *name == ".gitignore" && repo_root.is_none() {
+                    continue;
+                }
+                let path = dir.join(name);
+                if path.is_file() {
+                    let (matcher, error) = Gitignore::new(&path);
+                    if let Some(e) = error {
+                        log::warn!("Problem reading {}: {e}", path.display());
+                    }
+                    by_dir.entry(dir.to_path_buf()).or_default().push(matcher);
+                }
+            }
+        };
+
+        // Ignore files above the root still apply, up to the repository root.
+        if let Some(repo_root) = repo_root {
+            for dir in root_path.ancestors().skip(1) {
+                if !dir.starts_with(repo_root) {
+                    break;
+                }
+                add_ignore_files(dir);
+            }
+        }
+        // Ignore files in ignored directories never take effect, so the walk can skip them.
+        let mut builder = WalkBuilder::new(root_path);
+        builder.hidden(false).git_ignore(settings.respect_gitignore);
+        if !settings.custom_ignore_patterns.is_empty() {
+            if let Err(e) = builder.add_custom_ignore_patterns(&settings.custom_ignore_patterns) {
+                log::error!("Failed to apply custom ignore patterns: {e}");
+            }
+        }
+        for entry in builder.build().flatten() {
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                add_ignore_files(entry.path());
+            }
+        }
+
+        let mut fallback = Vec::new();
+        if let Some(repo_root) = repo_root {
+            let exclude = repo_root.join(".git/info/exclude");
+            if exclude.is_file() {
+                fallback.push(Gitignore::new(&exclude).0);
+            }
+            fallback.push(Gitignore::global().0);
+        }
+
+        Self {
+            root_path: root_path.to_path_buf(),
+            custom,
+            by_dir,
+            fallback,
+        }
+    }
+
+    /// Whether `path`, or any directory it is in, is ignored. Paths outside the root never are.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root_path) else {
+            return false;
+        };
+        let components: Vec<_> = relative.components().collect();
+        let mut current = self.root_path.clone();
+        for (index, component) in components.iter().enumerate() {
+            current.push(component);
+            let is_last = index + 1 == components.len();
+            if self.matches(&current, !is_last || is_dir) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        let dir_matchers = path
+            .ancestors()
+            .skip(1)
+            .filter_map(|dir| self.by_dir.get(dir))
+            .flatten();
+        for matcher in self.custom.iter().chain(dir_matchers).chain(&self.fallback) {
+            match matcher.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+}
+
+/// Whether a change to `path` can change what is ignored.
+pub fn is_ignore_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| IGNORE_FILE_NAMES.contains(&name))
+        || path.ends_with(".git/info/exclude")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_matches_like_the_walker() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".git/info")).unwrap();
+        fs::create_dir_all(root.join("app/gen")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n*.log\n!keep.log\n").unwrap();
+        fs::write(root.join("app/.gitignore"), "gen/\n").unwrap();
+        fs::write(root.join("app/.ignore"), "!gen/\nlocal.txt\n").unwrap();
+        fs::write(root.join(".git/info/exclude"), "scratch/\n").unwrap();
+
+        let settings = IgnoreSettings {
+            respect_gitignore: true,
+            custom_ignore_patterns: "node_modules\n".to_string(),
+        };
+        let matcher = IgnoreMatcher::new(root, &settings);
+        let ignored = |path: &str, is_dir: bool| matcher.is_ignored(&root.join(path), is_dir);
+
+        assert!(ignored("target/debug/app", false));
+        assert!(!ignored("target", false));
+        assert!(ignored("error.log", false));
+        assert!(!ignored("keep.log", false));
+        assert!(ignored("node_modules/pkg/index.js", false));
+        assert!(ignored("scratch/notes.md", false));
+        // `.ignore` takes precedence over `.gitignore` in the same directory.
+        assert!(!ignored("app/gen/out.rs", false));
+        assert!(ignored("app/local.txt", false));
+        assert!(!ignored("src/main.rs", false));
+
+        let matcher = IgnoreMatcher::new(
+            root,
+            &IgnoreSettings {
+                respect_gitignore: false,
+                custom_ignore_patterns: String::new(),
+            },
+        );
+        assert!(!matcher.is_ignored(&root.join("target/debug/app"), false));
+        assert!(matcher.is_ignored(&root.join("app/local.txt"), false));
+
+        assert!(is_ignore_file(&root.join("app/.gitignore")));
+        assert!(is_ignore_file(&root.join(".git/info/exclude")));
+        assert!(!is_ignore_file(&root.join("app/main.rs")));
+    }
+}
@@ -1,6 +1,17 @@
+pub mod backup_store;
 pub mod binary_utils;
+pub mod diagnostics;
+pub mod file_metadata;
+pub mod followup_prompt;
 pub mod fs_utils;
+pub mod hunks;
+pub mod ignore_matcher;
 pub mod parser;
 pub mod path_utils;
+pub mod project_config;
 pub mod prompt_estimator;
+pub mod protected_paths;
+pub mod review_targets;
+pub mod shell;
+pub mod syntax_check;
 pub mod token_counter;
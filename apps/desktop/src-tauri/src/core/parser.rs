@@ -1,33 +1,48 @@
+use crate::types::{OperationSource, SourceSpan, Truncation};
 use anyhow::Result;
 use nom::{
     branch::alt,
     bytes::complete::{tag, tag_no_case, take_until},
     character::complete::{line_ending, space1},
-    combinator::rest,
+    combinator::{consumed, rest},
     multi::many0,
     sequence::{preceded, terminated},
     IResult, Parser,
 };
 use std::collections::HashMap;
+use std::ops::Range;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SearchReplaceBlock {
+    pub search: String,
+    pub replace: String,
+    pub span: SourceSpan,
+}
 
 #[derive(Debug, PartialEq)]
 pub enum IntermediateOperation {
     Patch {
         file_path: String,
-        search_replace_blocks: Vec<(String, String)>,
+        search_replace_blocks: Vec<SearchReplaceBlock>,
         is_new_file: bool,
+        source: OperationSource,
+        truncation: Option<Truncation>,
     },
     Overwrite {
         file_path: String,
         content: String,
         is_new_file: bool,
+        source: OperationSource,
+        truncation: Option<Truncation>,
     },
     Delete {
         file_path: String,
+        source: OperationSource,
     },
     Move {
         from_path: String,
         to_path: String,
+        source: OperationSource,
     },
 }
 
@@ -42,7 +57,7 @@ fn parse_command_word(input: &str) -> IResult<&str, &str> {
     .parse(input)
 }
 
-fn parse_command_line(input: &str) -> IResult<&str, (&str, &str)> {
+pub(crate) fn parse_command_line(input: &str) -> IResult<&str, (&str, &str)> {
     let (input, command) = parse_command_word(input)?;
     let (input, _) = space1(input)?;
     let (input, args) = rest(input)?;
@@ -68,33 +83,78 @@ fn parse_search_replace_block(i: &str) -> IResult<&str, (&str, &str)> {
     Ok((i, (search, replace)))
 }
 
-fn parse_all_search_replace_blocks(content: &str) -> IResult<&str, Vec<(&str, &str)>> {
+/// A whole `<<<<<<< SEARCH ... >>>>>>> REPLACE` block together with its (search, replace) parts.
+type RawBlock<'a> = (&'a str, (&'a str, &'a str));
+
+fn parse_all_search_replace_blocks(content: &str) -> IResult<&str, Vec<RawBlock<'_>>> {
     many0(preceded(
         take_until("<<<<<<< SEARCH"),
-        parse_search_replace_block,
+        consumed(parse_search_replace_block),
     ))
     .parse(content)
 }
 
-fn sanitize_path(path: &str) -> String {
+fn offset_in(outer: &str, inner: &str) -> usize {
+    inner.as_ptr() as usize - outer.as_ptr() as usize
+}
+
+pub(crate) fn sanitize_path(path: &str) -> String {
     path.trim()
         .trim_matches(|c| c == '`' || c == '\'' || c == '"')
         .to_string()
 }
 
+/// A command line seen while scanning, waiting for its fenced block.
+struct PendingCommand {
+    command: String,
+    args: String,
+    line_index: usize,
+    fence_line_index: Option<usize>,
+    explanation: String,
+}
+
 struct MarkdownParser<'a> {
-    markdown: &'a str,
+    lines: Vec<&'a str>,
+    line_starts: Vec<usize>,
+    ends_with_newline: bool,
     operations: Vec<IntermediateOperation>,
 }
 
 impl<'a> MarkdownParser<'a> {
     fn new(markdown: &'a str) -> Self {
+        let lines: Vec<&str> = markdown.lines().collect();
+        let line_starts = std::iter::once(0)
+            .chain(
+                markdown
+                    .match_indices('\n')
+                    .map(|(i, _)| i + 1)
+                    .filter(|&i| i < markdown.len()),
+            )
+            .collect();
         Self {
-            markdown,
+            lines,
+            line_starts,
+            ends_with_newline: markdown.ends_with('\n'),
             operations: Vec::new(),
         }
     }
 
+    /// Span covering whole lines `lines.start..lines.end` of the markdown.
+    fn span_for_lines(&self, lines: Range<usize>) -> SourceSpan {
+        let last = lines.end.max(lines.start + 1) - 1;
+        SourceSpan {
+            start_byte: self.line_starts[lines.start] as u32,
+            end_byte: (self.line_starts[last] + self.lines[last].len()) as u32,
+            start_line: lines.start as u32 + 1,
+            end_line: last as u32 + 1,
+        }
+    }
+
+    /// Prose the model wrote between the previous operation and this one.
+    fn explanation_for_lines(&self, lines: Range<usize>) -> String {
+        self.lines[lines].join("\n").trim().to_string()
+    }
+
     fn run(mut self) -> Result<Vec<IntermediateOperation>> {
         let command_blocks = self.parse_command_blocks();
         let mut operations_map = HashMap::new();
@@ -103,15 +163,15 @@ impl<'a> MarkdownParser<'a> {
             let key = match &op {
                 IntermediateOperation::Patch { file_path, .. } => file_path.clone(),
                 IntermediateOperation::Overwrite { file_path, .. } => file_path.clone(),
-                IntermediateOperation::Delete { file_path } => file_path.clone(),
+                IntermediateOperation::Delete { file_path, .. } => file_path.clone(),
                 IntermediateOperation::Move { from_path, .. } => from_path.clone(),
             };
             operations_map.entry(key).or_insert(op);
         }
 
         self.operations = self
-            .markdown
-            .lines()
+            .lines
+            .iter()
             .filter_map(|line| {
                 if let Ok((_, (command, args))) = parse_command_line(line) {
                     let args = args.trim();
@@ -135,11 +195,12 @@ impl<'a> MarkdownParser<'a> {
 
     fn parse_command_blocks(&self) -> Vec<IntermediateOperation> {
         let mut operations = Vec::new();
-        let mut last_command: Option<(String, String)> = None;
+        let mut last_command: Option<PendingCommand> = None;
         let mut current_block_content = String::new();
         let mut fence_nesting = 0;
+        let mut prose_start = 0;
 
-        for line in self.markdown.lines() {
+        for (line_index, line) in self.lines.iter().enumerate() {
             let trimmed_line = line.trim();
             let is_fence = trimmed_line.starts_with("```");
 
@@ -156,49 +217,169 @@ impl<'a> MarkdownParser<'a> {
                 }
 
                 if fence_nesting == 0 {
-                    if let Some((command, args)) = last_command.take() {
+                    if let Some(pending) = last_command.take() {
                         let content_without_last_fence = current_block_content
                             .lines()
                             .take(current_block_content.lines().count() - 1)
                             .collect::<Vec<_>>()
                             .join("\n");
-                        if let Some(op) =
-                            self.process_command_block(&command, &args, &content_without_last_fence)
-                        {
+                        let source = OperationSource {
+                            span: self.span_for_lines(pending.line_index..line_index + 1),
+                            explanation: pending.explanation,
+                        };
+                        // The block content starts on the line after the opening fence.
+                        let content_first_line =
+                            line_index + 1 - current_block_content.lines().count();
+                        if let Some(op) = self.process_command_block(
+                            &pending.command,
+                            &pending.args,
+                            &content_without_last_fence,
+                            content_first_line,
+                            source,
+                        ) {
                             operations.push(op);
                         }
+                        prose_start = line_index + 1;
                     }
                     current_block_content.clear();
                 }
             } else if is_fence {
-                if last_command.is_some() {
+                if let Some(pending) = last_command.as_mut() {
+                    pending.fence_line_index = Some(line_index);
                     fence_nesting += 1;
                 }
             } else if let Ok((_, (command, args))) = parse_command_line(line) {
                 let command = command.to_uppercase();
                 let args = args.trim().to_string();
+                let explanation = self.explanation_for_lines(prose_start..line_index);
 
                 if command == "DELETE" || command == "MOVE" {
-                    if let Some(op) = self.process_command_block(&command, &args, "") {
+                    let source = OperationSource {
+                        span: self.span_for_lines(line_index..line_index + 1),
+                        explanation,
+                    };
+                    if let Some(op) = self.process_command_block(&command, &args, "", 0, source) {
                         operations.push(op);
                     }
+                    prose_start = line_index + 1;
                 } else {
-                    last_command = Some((command, args));
+                    last_command = Some(PendingCommand {
+                        command,
+                        args,
+                        line_index,
+                        fence_line_index: None,
+                        explanation,
+                    });
                 }
             }
         }
+
+        // A command still waiting for its block to close at the end means the response was cut
+        // off mid-operation, whether inside the block or before its opening fence.
+        if let Some(pending) = last_command {
+            if let Some(op) = self.process_truncated_block(pending) {
+                operations.push(op);
+            }
+        }
         operations
     }
 
+    fn process_truncated_block(&self, pending: PendingCommand) -> Option<IntermediateOperation> {
+        // Content starts after the opening fence, or would have after the command line.
+        let opening_line = pending.fence_line_index.unwrap_or(pending.line_index);
+        let complete_lines = if pending.fence_line_index.is_none() {
+            opening_line + 1
+        } else if self.ends_with_newline {
+            self.lines.len()
+        } else {
+            // Without a trailing newline the final line may have been cut mid-way.
+            self.lines.len() - 1
+        }
+        .max(opening_line + 1);
+
+        let content_first_line = opening_line + 1;
+        let content = self.lines[content_first_line..complete_lines].join("\n");
+        let source = OperationSource {
+            span: self.span_for_lines(pending.line_index..self.lines.len()),
+            explanation: pending.explanation,
+        };
+        let file_path = sanitize_path(&pending.args);
+
+        match pending.command.as_str() {
+            "CREATE" | "OVERWRITE" => {
+                let resume_after = complete_lines - 1;
+                Some(IntermediateOperation::Overwrite {
+                    file_path,
+                    content,
+                    is_new_file: pending.command == "CREATE",
+                    source,
+                    truncation: Some(self.truncation_after_line(resume_after)),
+                })
+            }
+            "PATCH" => {
+                let search_replace_blocks =
+                    self.search_replace_blocks(&content, content_first_line);
+                let resume_after = search_replace_blocks
+                    .last()
+                    .map(|block| block.span.end_line as usize - 1)
+                    .unwrap_or(opening_line);
+                Some(IntermediateOperation::Patch {
+                    file_path,
+                    is_new_file: search_replace_blocks
+                        .first()
+                        .is_some_and(|block| block.search.trim().is_empty()),
+                    search_replace_blocks,
+                    source,
+                    truncation: Some(self.truncation_after_line(resume_after)),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn truncation_after_line(&self, line_index: usize) -> Truncation {
+        Truncation {
+            resume_after_line: line_index as u32 + 1,
+            last_complete_line: self.lines[line_index].to_string(),
+        }
+    }
+
+    fn search_replace_blocks(
+        &self,
+        content: &str,
+        content_first_line: usize,
+    ) -> Vec<SearchReplaceBlock> {
+        let Ok((_, captures)) = parse_all_search_replace_blocks(content) else {
+            return Vec::new();
+        };
+        captures
+            .iter()
+            .map(|&(block, (search_block, replace_block))| {
+                let block_start = offset_in(content, block);
+                let first_line = content[..block_start].matches('\n').count();
+                let line_count = block.matches('\n').count() + 1;
+                let start_line = content_first_line + first_line;
+                SearchReplaceBlock {
+                    search: search_block.to_string(),
+                    replace: replace_block.to_string(),
+                    span: self.span_for_lines(start_line..start_line + line_count),
+                }
+            })
+            .collect()
+    }
+
     fn process_command_block(
         &self,
         command: &str,
         args: &str,
         content: &str,
+        content_first_line: usize,
+        source: OperationSource,
     ) -> Option<IntermediateOperation> {
         match command {
             "DELETE" => Some(IntermediateOperation::Delete {
                 file_path: sanitize_path(args),
+                source,
             }),
             "MOVE" => {
                 let rest_lower = args.to_lowercase();
@@ -206,7 +387,11 @@ impl<'a> MarkdownParser<'a> {
                     let from_path = sanitize_path(&args[..to_index]);
                     let to_path = sanitize_path(&args[to_index + 4..]);
                     if !from_path.is_empty() && !to_path.is_empty() {
-                        return Some(IntermediateOperation::Move { from_path, to_path });
+                        return Some(IntermediateOperation::Move {
+                            from_path,
+                            to_path,
+                            source,
+                        });
                     }
                 }
                 None
@@ -215,34 +400,19 @@ impl<'a> MarkdownParser<'a> {
                 file_path: sanitize_path(args),
                 content: content.to_string(),
                 is_new_file: command == "CREATE",
+                source,
+                truncation: None,
             }),
             "PATCH" => {
-                let mut is_new_file = false;
-                let mut search_replace_blocks = Vec::new();
-
-                if let Ok((_, captures)) = parse_all_search_replace_blocks(content) {
-                    if captures.is_empty() {
-                        return None;
-                    }
-                    for (i, &(search_block, replace_block)) in captures.iter().enumerate() {
-                        if i == 0 {
-                            is_new_file = search_block.trim().is_empty();
-                        }
-                        search_replace_blocks
-                            .push((search_block.to_string(), replace_block.to_string()));
-                    }
-                } else {
-                    return None;
-                }
-
-                if !search_replace_blocks.is_empty() {
-                    return Some(IntermediateOperation::Patch {
-                        file_path: sanitize_path(args),
-                        search_replace_blocks,
-                        is_new_file,
-                    });
-                }
-                None
+                let search_replace_blocks = self.search_replace_blocks(content, content_first_line);
+                let first_block = search_replace_blocks.first()?;
+                Some(IntermediateOperation::Patch {
+                    file_path: sanitize_path(args),
+                    is_new_file: first_block.search.trim().is_empty(),
+                    search_replace_blocks,
+                    source,
+                    truncation: None,
+                })
             }
             _ => None,
         }
@@ -1,6 +1,7 @@
+use crate::core::fs_utils;
 use anyhow::{Context, Result};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 pub fn get_relative_path(full_path: &Path, root_path: &Path) -> Result<String> {
     let relative_path = full_path.strip_prefix(root_path).with_context(|| {
@@ -30,3 +31,26 @@ pub fn resolve_path(path_str: &str, cwd: Option<String>) -> Result<String> {
 
     Ok(canonical_path.to_string_lossy().replace('\\', "/"))
 }
+
+/// A short, filesystem-safe key identifying a project root, for per-project app data.
+pub fn project_key(root_path: &str) -> String {
+    let root = root_path.trim_end_matches(['/', '\\']);
+    fs_utils::content_hash(root.as_bytes())[..16].to_string()
+}
+
+/// `relative_path` with `.` and `..` resolved and `/` as separator, or `None` if it is absolute
+/// or climbs out of the directory it is relative to.
+pub fn normalize_relative(relative_path: &str) -> Option<String> {
+    let mut parts: Vec<String> = Vec::new();
+    for component in Path::new(&relative_path.replace('\\', "/")).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(parts.join("/"))
+}
@@ -0,0 +1,487 @@
+use crate::core::{fs_utils, protected_paths};
+use crate::types::{
+    FormatterConfig, FormatterConsent, IgnoreSettings, MetaPrompt, ProjectConfig,
+    ProjectConfigError, ProjectConfigStatus, ProjectIgnoreConfig, ProjectSettings, PromptMode,
+    PromptType, ProtectedPathRule, ProtectionLevel,
+};
+use anyhow::{anyhow, Result};
+use ignore::gitignore::GitignoreBuilder;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use toml::Spanned;
+
+pub const CONFIG_FILE_NAME: &str = ".repowizard.toml";
+
+/// The file as written by hand: snake_case keys, unknown keys rejected, and spans kept on the
+/// values that need checking after parsing.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    #[serde(default)]
+    ignore: RawIgnore,
+    formatters: Option<Vec<RawFormatter>>,
+    protected_paths: Option<Vec<RawProtectedPath>>,
+    #[serde(default)]
+    meta_prompts: Vec<RawMetaPrompt>,
+    token_budget: Option<Spanned<u32>>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct RawIgnore {
+    respect_gitignore: Option<bool>,
+    #[serde(default)]
+    patterns: Vec<Spanned<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawFormatter {
+    glob: Spanned<String>,
+    command: Spanned<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawProtectedPath {
+    pattern: Spanned<String>,
+    level: ProtectionLevel,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawMetaPrompt {
+    name: Spanned<String>,
+    content: String,
+    #[serde(default = "default_prompt_mode")]
+    mode: PromptMode,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+}
+
+fn default_prompt_mode() -> PromptMode {
+    PromptMode::Universal
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+static CONFIGS: Lazy<RwLock<HashMap<PathBuf, ProjectConfigStatus>>> = Lazy::new(Default::default);
+
+/// Fingerprints of the config-file formatters each project's user has approved.
+static APPROVED_FORMATTERS: Lazy<RwLock<HashMap<PathBuf, String>>> = Lazy::new(Default::default);
+
+pub fn config_path(root_path: &Path) -> PathBuf {
+    root_path.join(CONFIG_FILE_NAME)
+}
+
+/// Reads and validates the project's config file, replacing the cached copy.
+pub fn load(root_path: &Path) -> ProjectConfigStatus {
+    let path = config_path(root_path);
+    let status = match std::fs::read_to_string(&path) {
+        Ok(content) => match parse(root_path, &content) {
+            Ok(config) => ProjectConfigStatus {
+                path: path.to_string_lossy().to_string(),
+                exists: true,
+                config: Some(config),
+                errors: Vec::new(),
+            },
+            Err(errors) => ProjectConfigStatus {
+                path: path.to_string_lossy().to_string(),
+                exists: true,
+                config: None,
+                errors,
+            },
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => ProjectConfigStatus {
+            path: path.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+        Err(e) => ProjectConfigStatus {
+            path: path.to_string_lossy().to_string(),
+            exists: true,
+            config: None,
+            errors: vec![ProjectConfigError {
+                line: 0,
+                column: 0,
+                message: format!("Failed to read {CONFIG_FILE_NAME}: {e}"),
+            }],
+        },
+    };
+    for error in &status.errors {
+        log::warn!(
+            "{}:{}:{}: {}",
+            status.path,
+            error.line,
+            error.column,
+            error.message
+        );
+    }
+    CONFIGS
+        .write()
+        .unwrap()
+        .insert(root_path.to_path_buf(), status.clone());
+    protected_paths::invalidate(root_path);
+    status
+}
+
+/// The cached status, loading the file the first time a project asks for it.
+pub fn status(root_path: &Path) -> ProjectConfigStatus {
+    if let Some(status) = CONFIGS.read().unwrap().get(root_path) {
+        return status.clone();
+    }
+    load(root_path)
+}
+
+pub fn config(root_path: &Path) -> Option<ProjectConfig> {
+    status(root_path).config
+}
+
+/// Parses `content`, reporting every problem found with its 1-based line and column.
+pub fn parse(root_path: &Path, content: &str) -> Result<ProjectConfig, Vec<ProjectConfigError>> {
+    let raw: RawConfig = toml::from_str(content).map_err(|e| {
+        let (line, column) = e
+            .span()
+            .map(|span| line_col(content, span.start))
+            .unwrap_or((0, 0));
+        vec![ProjectConfigError {
+            line,
+            column,
+            message: e.message().to_string(),
+        }]
+    })?;
+
+    let mut errors = Vec::new();
+    let mut error = |span: Range<usize>, message: String| {
+        let (line, column) = line_col(content, span.start);
+        errors.push(ProjectConfigError {
+            line,
+            column,
+            message,
+        });
+    };
+    let check_glob = |glob: &Spanned<String>, error: &mut dyn FnMut(Range<usize>, String)| {
+        let mut builder = GitignoreBuilder::new(root_path);
+        if let Err(e) = builder.add_line(None, glob.get_ref()) {
+            error(
+                glob.span(),
+                format!("Invalid pattern `{}`: {e}", glob.get_ref()),
+            );
+        }
+    };
+
+    for pattern in &raw.ignore.patterns {
+        check_glob(pattern, &mut error);
+    }
+    for formatter in raw.formatters.iter().flatten() {
+        check_glob(&formatter.glob, &mut error);
+        if formatter.command.get_ref().trim().is_empty() {
+            error(
+                formatter.command.span(),
+                "Formatter command cannot be empty".to_string(),
+            );
+        }
+    }
+    for rule in raw.protected_paths.iter().flatten() {
+        check_glob(&rule.pattern, &mut error);
+    }
+    let mut names = Vec::new();
+    for prompt in &raw.meta_prompts {
+        let name = prompt.name.get_ref().trim();
+        if name.is_empty() {
+            error(
+                prompt.name.span(),
+                "Meta prompt name cannot be empty".to_string(),
+            );
+        } else if names.contains(&name) {
+            error(
+                prompt.name.span(),
+                format!("Duplicate meta prompt `{name}`"),
+            );
+        }
+        names.push(name);
+    }
+    if let Some(budget) = &raw.token_budget {
+        if *budget.get_ref() == 0 {
+            error(
+                budget.span(),
+                "Token budget must be greater than 0".to_string(),
+            );
+        }
+    }
+
+    if !errors.is_empty() {
+        errors.sort_by_key(|e| (e.line, e.column));
+        return Err(errors);
+    }
+    Ok(ProjectConfig {
+        ignore: ProjectIgnoreConfig {
+            respect_gitignore: raw.ignore.respect_gitignore,
+            patterns: raw
+                .ignore
+                .patterns
+                .into_iter()
+                .map(Spanned::into_inner)
+                .collect(),
+        },
+        formatters: raw.formatters.map(|formatters| {
+            formatters
+                .into_iter()
+                .map(|formatter| FormatterConfig {
+                    glob: formatter.glob.into_inner(),
+                    command: formatter.command.into_inner(),
+                })
+                .collect()
+        }),
+        protected_paths: raw.protected_paths.map(|rules| {
+            rules
+                .into_iter()
+                .map(|rule| ProtectedPathRule {
+                    pattern: rule.pattern.into_inner(),
+                    level: rule.level,
+                })
+                .collect()
+        }),
+        meta_prompts: raw
+            .meta_prompts
+            .into_iter()
+            .map(|prompt| {
+                let name = prompt.name.into_inner().trim().to_string();
+                MetaPrompt {
+                    id: format!("project:{name}"),
+                    name,
+                    content: prompt.content,
+                    mode: prompt.mode,
+                    prompt_type: PromptType::Meta,
+                    magic_type: None,
+                    file_tree_config: None,
+                    git_diff_config: None,
+                    terminal_command_config: None,
+                    enabled: prompt.enabled,
+                }
+            })
+            .collect(),
+        token_budget: raw.token_budget.map(Spanned::into_inner),
+    })
+}
+
+fn line_col(content: &str, offset: usize) -> (u32, u32) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map(|last| last.chars().count())
+        .unwrap_or(0)
+        + 1;
+    (line as u32, column as u32)
+}
+
+/// Applies the project's config on top of the user's settings. Values the file sets win;
+/// ignore patterns are added to the user's, and meta prompts replace user prompts of the same
+/// name.
+pub fn merge(mut settings: ProjectSettings, config: Option<&ProjectConfig>) -> ProjectSettings {
+    let Some(config) = config else {
+        return settings;
+    };
+    settings.ignore = merge_ignore(settings.ignore, config);
+    if let Some(formatters) = &config.formatters {
+        settings.formatters = formatters.clone();
+    }
+    settings.meta_prompts.retain(|prompt| {
+        !config
+            .meta_prompts
+            .iter()
+            .any(|project_prompt| project_prompt.name == prompt.name)
+    });
+    settings
+        .meta_prompts
+        .extend(config.meta_prompts.iter().cloned());
+    if config.token_budget.is_some() {
+        settings.token_budget = config.token_budget;
+    }
+    settings
+}
+
+fn merge_ignore(mut settings: IgnoreSettings, config: &ProjectConfig) -> IgnoreSettings {
+    if let Some(respect_gitignore) = config.ignore.respect_gitignore {
+        settings.respect_gitignore = respect_gitignore;
+    }
+    for pattern in &config.ignore.patterns {
+        if !settings.custom_ignore_patterns.is_empty()
+            && !settings.custom_ignore_patterns.ends_with('\n')
+        {
+            settings.custom_ignore_patterns.push('\n');
+        }
+        settings.custom_ignore_patterns.push_str(pattern);
+    }
+    settings
+}
+
+/// The ignore settings to use for `root_path`, given the ones the UI sent.
+pub fn ignore_settings(root_path: &Path, settings: IgnoreSettings) -> IgnoreSettings {
+    match config(root_path) {
+        Some(config) => merge_ignore(settings, &config),
+        None => settings,
+    }
+}
+
+fn formatters_fingerprint(formatters: &[FormatterConfig]) -> String {
+    let listed: String = formatters
+        .iter()
+        .map(|formatter| format!("{}\0{}\n", formatter.glob, formatter.command))
+        .collect();
+    fs_utils::content_hash(listed.as_bytes())
+}
+
+/// What the user is asked to approve before the config file's formatter commands may run.
+pub fn formatter_consent(root_path: &Path) -> Option<FormatterConsent> {
+    let formatters = config(root_path)?.formatters?;
+    let fingerprint = formatters_fingerprint(&formatters);
+    let approved = APPROVED_FORMATTERS.read().unwrap().get(root_path) == Some(&fingerprint);
+    Some(FormatterConsent {
+        fingerprint,
+        formatters,
+        approved,
+    })
+}
+
+/// Allows the config file's formatters to run, provided they are still the ones the user saw.
+pub fn approve_formatters(root_path: &Path, fingerprint: &str) -> Result<()> {
+    match formatter_consent(root_path) {
+        Some(consent) if consent.fingerprint == fingerprint => {
+            APPROVED_FORMATTERS
+                .write()
+                .unwrap()
+                .insert(root_path.to_path_buf(), consent.fingerprint);
+            Ok(())
+        }
+        Some(_) => Err(anyhow!(
+            "The formatters in {CONFIG_FILE_NAME} have changed and need to be approved again"
+        )),
+        None => Err(anyhow!("{CONFIG_FILE_NAME} does not define any formatters")),
+    }
+}
+
+/// The project's config without formatters the user has not approved.
+pub fn approved_config(root_path: &Path) -> Option<ProjectConfig> {
+    let mut config = config(root_path)?;
+    if formatter_consent(root_path).is_some_and(|consent| !consent.approved) {
+        config.formatters = None;
+    }
+    Some(config)
+}
+
+/// The formatters to use for `root_path`: the project's, if its config defines any and the user
+/// has approved them.
+pub fn formatters(root_path: &Path, formatters: Vec<FormatterConfig>) -> Vec<FormatterConfig> {
+    if formatter_consent(root_path).is_some_and(|consent| !consent.approved) {
+        log::warn!(
+            "Not running formatters from {} until they are approved",
+            config_path(root_path).display()
+        );
+    }
+    approved_config(root_path)
+        .and_then(|config| config.formatters)
+        .unwrap_or(formatters)
+}
+
+/// The protected-path rules the project's config defines, if any.
+pub fn protected_paths(root_path: &Path) -> Option<Vec<ProtectedPathRule>> {
+    config(root_path).and_then(|config| config.protected_paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_errors_point_at_the_offending_line() {
+        let root = Path::new("/project");
+        let errors = parse(root, "token_budget = 10\n[ignore]\nrespect = true\n").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].column), (3, 1));
+        assert!(errors[0].message.contains("unknown field `respect`"));
+
+        let content = indoc! {r#"
+            token_budget = 0
+
+            [[formatters]]
+            glob = "*.rs"
+            command = " "
+
+            [[meta_prompts]]
+            name = "Review"
+            content = "a"
+
+            [[meta_prompts]]
+            name = "Review"
+            content = "b"
+        "#};
+        let errors = parse(root, content).unwrap_err();
+        let lines: Vec<_> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![1, 5, 12]);
+    }
+
+    #[test]
+    fn test_project_values_take_precedence() {
+        let content = indoc! {r#"
+            token_budget = 50000
+
+            [ignore]
+            respect_gitignore = false
+            patterns = ["dist/"]
+
+            [[protected_paths]]
+            pattern = "migrations/"
+            level = "warn"
+
+            [[meta_prompts]]
+            name = "Style"
+            content = "Use tabs."
+        "#};
+        let config = parse(Path::new("/project"), content).unwrap();
+        let user_prompt = |name: &str| MetaPrompt {
+            id: name.to_lowercase(),
+            name: name.to_string(),
+            content: String::new(),
+            mode: PromptMode::Universal,
+            prompt_type: PromptType::Meta,
+            magic_type: None,
+            file_tree_config: None,
+            git_diff_config: None,
+            terminal_command_config: None,
+            enabled: false,
+        };
+        let user = ProjectSettings {
+            ignore: IgnoreSettings {
+                respect_gitignore: true,
+                custom_ignore_patterns: ".git".to_string(),
+            },
+            formatters: vec![FormatterConfig {
+                glob: "*.rs".to_string(),
+                command: "rustfmt".to_string(),
+            }],
+            meta_prompts: vec![user_prompt("Style"), user_prompt("Tests")],
+            token_budget: Some(100000),
+        };
+
+        let merged = merge(user, Some(&config));
+        assert!(!merged.ignore.respect_gitignore);
+        assert_eq!(merged.ignore.custom_ignore_patterns, ".git\ndist/");
+        assert_eq!(merged.formatters.len(), 1);
+        let prompts: Vec<_> = merged.meta_prompts.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(prompts, vec!["tests", "project:Style"]);
+        assert_eq!(merged.token_budget, Some(50000));
+        assert_eq!(
+            config.protected_paths.unwrap()[0].level,
+            ProtectionLevel::Warn
+        );
+    }
+}
@@ -7,7 +7,7 @@ use crate::types::{
 use std::collections::HashSet;
 use std::path::PathBuf;
 
-const DIFF_FORMATTING_RULES: &str = r#"# File editing rules:
+pub(crate) const DIFF_FORMATTING_RULES: &str = r#"# File editing rules:
 
 You can modify files using either search/replace blocks (for partial edits) or overwriting the entire file (for full content).
 
@@ -48,7 +48,7 @@ To move or rename a file:
 MOVE path/from/old.ext TO path/to/new.ext
 "#;
 
-const WHOLE_FORMATTING_RULES: &str = r#"# File editing rules:
+pub(crate) const WHOLE_FORMATTING_RULES: &str = r#"# File editing rules:
 
 For each file you need to modify, use a command (`CREATE` for new files, `OVERWRITE` for existing files) followed by the file path, and then the complete, updated content of the file within a fenced code block.
 
@@ -328,6 +328,8 @@ fn format_file_tree(node: &FileNode, config: Option<&FileTreeConfig>) -> String
                     path: String::new(),
                     is_directory: false,
                     children: None,
+                    child_count: None,
+                    metadata: None,
                 };
                 children_to_display = truncated
                     .into_iter()
@@ -378,6 +380,8 @@ fn filter_file_tree_by_selection(
             name: node.name.clone(),
             is_directory: node.is_directory,
             children: None,
+            child_count: None,
+            metadata: None,
         });
     }
 
@@ -393,6 +397,8 @@ fn filter_file_tree_by_selection(
                     name: node.name.clone(),
                     is_directory: true,
                     children: Some(new_children),
+                    child_count: None,
+                    metadata: None,
                 });
             }
         }
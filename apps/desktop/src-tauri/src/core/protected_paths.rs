@@ -0,0 +1,166 @@
+use crate::core::{fs_utils, path_utils, project_config};
+use crate::types::{ProtectedPathRule, ProtectedPathViolation, ProtectionLevel};
+use anyhow::Result;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+
+/// Applied to projects that have not configured their own rules.
+const DEFAULT_RULES: &[(&str, ProtectionLevel)] = &[
+    (".git/", ProtectionLevel::Deny),
+    (".env*", ProtectionLevel::Deny),
+    ("*.pem", ProtectionLevel::Deny),
+    ("*.key", ProtectionLevel::Deny),
+    ("Cargo.lock", ProtectionLevel::Deny),
+    (".github/workflows/", ProtectionLevel::Warn),
+    ("vendor/", ProtectionLevel::Warn),
+];
+
+/// Reported as the matching pattern for paths that are absolute or lead out of the project.
+const OUTSIDE_ROOT: &str = "<outside the project root>";
+
+#[derive(Debug, Error)]
+#[error("{} is protected by `{}` and cannot be changed", .0.file_path, .0.pattern)]
+pub struct ProtectedPathError(pub ProtectedPathViolation);
+
+/// Protected-path rules compiled against a project root.
+pub struct ProtectedPaths {
+    rules: Vec<(Gitignore, ProtectedPathRule)>,
+}
+
+impl ProtectedPaths {
+    pub fn new(root_path: &Path, rules: &[ProtectedPathRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let mut builder = GitignoreBuilder::new(root_path);
+                builder.add_line(None, &rule.pattern)?;
+                Ok((builder.build()?, rule.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { rules })
+    }
+
+    /// The strictest rule matching `relative_path`, if any. Paths that are not inside the
+    /// project are always denied.
+    pub fn check(&self, relative_path: &str) -> Option<ProtectedPathViolation> {
+        let Some(normalized) = path_utils::normalize_relative(relative_path) else {
+            return Some(ProtectedPathViolation {
+                file_path: relative_path.to_string(),
+                pattern: OUTSIDE_ROOT.to_string(),
+                level: ProtectionLevel::Deny,
+            });
+        };
+        self.rules
+            .iter()
+            .filter(|(matcher, _)| {
+                matcher
+                    .matched_path_or_any_parents(&normalized, false)
+                    .is_ignore()
+            })
+            .max_by_key(|(_, rule)| rule.level == ProtectionLevel::Deny)
+            .map(|(_, rule)| ProtectedPathViolation {
+                file_path: relative_path.to_string(),
+                pattern: rule.pattern.clone(),
+                level: rule.level.clone(),
+            })
+    }
+}
+
+static PROJECT_RULES: Lazy<RwLock<HashMap<PathBuf, Vec<ProtectedPathRule>>>> =
+    Lazy::new(Default::default);
+
+/// The compiled rules of each project, kept until its rules or config file change.
+static COMPILED: Lazy<RwLock<HashMap<PathBuf, Arc<ProtectedPaths>>>> = Lazy::new(Default::default);
+
+pub fn default_rules() -> Vec<ProtectedPathRule> {
+    DEFAULT_RULES
+        .iter()
+        .map(|(pattern, level)| ProtectedPathRule {
+            pattern: pattern.to_string(),
+            level: level.clone(),
+        })
+        .collect()
+}
+
+pub fn set_rules(root_path: &Path, rules: Vec<ProtectedPathRule>) -> Result<()> {
+    // Validate the globs before they replace a working policy.
+    ProtectedPaths::new(root_path, &rules)?;
+    PROJECT_RULES
+        .write()
+        .unwrap()
+        .insert(root_path.to_path_buf(), rules);
+    invalidate(root_path);
+    Ok(())
+}
+
+/// Drops the compiled rules of `root_path`, so the next check compiles them afresh.
+pub(crate) fn invalidate(root_path: &Path) {
+    COMPILED
+        .write()
+        .unwrap()
+        .remove(&fs_utils::normalize_path(root_path));
+}
+
+/// Rules from the project's config file take precedence over those set from the UI, which
+/// take precedence over the defaults.
+pub fn rules_for(root_path: &Path) -> Vec<ProtectedPathRule> {
+    if let Some(rules) = project_config::protected_paths(root_path) {
+        return rules;
+    }
+    PROJECT_RULES
+        .read()
+        .unwrap()
+        .get(root_path)
+        .cloned()
+        .unwrap_or_else(default_rules)
+}
+
+pub fn for_project(root_path: &Path) -> Result<Arc<ProtectedPaths>> {
+    let key = fs_utils::normalize_path(root_path);
+    if let Some(compiled) = COMPILED.read().unwrap().get(&key) {
+        return Ok(compiled.clone());
+    }
+    let compiled = Arc::new(ProtectedPaths::new(root_path, &rules_for(root_path))?);
+    COMPILED.write().unwrap().insert(key, compiled.clone());
+    Ok(compiled)
+}
+
+/// Fails with a [`ProtectedPathError`] if `path` is denied by its project's rules, or is not
+/// inside the project at all.
+pub fn ensure_not_denied(path: &Path, root_path: &Path) -> Result<()> {
+    let path = fs_utils::normalize_path(path);
+    let root_path = fs_utils::normalize_path(root_path);
+    let Ok(relative_path) = path.strip_prefix(&root_path) else {
+        return Err(ProtectedPathError(ProtectedPathViolation {
+            file_path: path.to_string_lossy().to_string(),
+            pattern: OUTSIDE_ROOT.to_string(),
+            level: ProtectionLevel::Deny,
+        })
+        .into());
+    };
+    let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+    match for_project(&root_path)?.check(&relative_path) {
+        Some(violation) if violation.level == ProtectionLevel::Deny => {
+            Err(ProtectedPathError(violation).into())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_outside_the_root_are_denied() {
+        let root = Path::new("/projects/app");
+        assert!(ensure_not_denied(&root.join("src/main.rs"), root).is_ok());
+        assert!(ensure_not_denied(&root.join(".env"), root).is_err());
+        assert!(ensure_not_denied(Path::new("/projects/other/main.rs"), root).is_err());
+        assert!(ensure_not_denied(&root.join("../app-old/main.rs"), root).is_err());
+    }
+}
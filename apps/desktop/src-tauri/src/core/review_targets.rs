@@ -0,0 +1,158 @@
+use crate::core::fs_utils;
+use crate::types::{ChangeOperation, ReviewTargetChange};
+use once_cell::sync::Lazy;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The states a file under review is expected to be in, by content hash, `None` meaning absent.
+struct Target {
+    original_hash: Option<String>,
+    proposed_hash: Option<String>,
+    /// The last unexpected state reported, so each edit is only reported once.
+    reported_hash: Option<Option<String>>,
+}
+
+struct Review {
+    markdown: String,
+    targets: HashMap<String, Target>,
+}
+
+/// The review open for each project.
+static REVIEWS: Lazy<Mutex<HashMap<PathBuf, Review>>> = Lazy::new(Default::default);
+
+/// Tracks the files a review for `root_path` touches, replacing any earlier review.
+pub fn track(root_path: &Path, markdown: &str, operations: &[ChangeOperation]) {
+    let review = Review {
+        markdown: markdown.to_string(),
+        targets: targets(root_path, operations),
+    };
+    REVIEWS
+        .lock()
+        .unwrap()
+        .insert(root_path.to_path_buf(), review);
+}
+
+/// Tracks re-parsed operations in place of the earlier ones for the same files.
+pub fn retrack(root_path: &Path, operations: &[ChangeOperation]) {
+    if let Some(review) = REVIEWS.lock().unwrap().get_mut(root_path) {
+        review.targets.extend(targets(root_path, operations));
+    }
+}
+
+pub fn untrack(root_path: &Path) {
+    REVIEWS.lock().unwrap().remove(root_path);
+}
+
+/// The response the open review was parsed from.
+pub fn markdown(root_path: &Path) -> Option<String> {
+    REVIEWS
+        .lock()
+        .unwrap()
+        .get(root_path)
+        .map(|review| review.markdown.clone())
+}
+
+/// Targets among `paths` that are in neither expected state, ordered by path.
+pub fn changed<'a>(
+    root_path: &Path,
+    paths: impl IntoIterator<Item = &'a Path>,
+) -> Vec<ReviewTargetChange> {
+    let mut reviews = REVIEWS.lock().unwrap();
+    let Some(review) = reviews.get_mut(root_path) else {
+        return Vec::new();
+    };
+
+    let file_paths: BTreeSet<String> = paths
+        .into_iter()
+        .filter_map(|path| path.strip_prefix(root_path).ok())
+        .map(|path| path.to_string_lossy().replace('\\', "/"))
+        .collect();
+    let mut changes = Vec::new();
+    for file_path in file_paths {
+        let Some(target) = review.targets.get_mut(&file_path) else {
+            continue;
+        };
+        let current_hash = hash_file(&root_path.join(&file_path));
+        if current_hash == target.original_hash || current_hash == target.proposed_hash {
+            target.reported_hash = None;
+            continue;
+        }
+        if target.reported_hash.as_ref() == Some(&current_hash) {
+            continue;
+        }
+        target.reported_hash = Some(current_hash.clone());
+        changes.push(ReviewTargetChange {
+            file_path,
+            expected_hash: target.original_hash.clone(),
+            current_hash,
+        });
+    }
+    changes
+}
+
+fn targets(root_path: &Path, operations: &[ChangeOperation]) -> HashMap<String, Target> {
+    operations
+        .iter()
+        .flat_map(|op| match op {
+            ChangeOperation::Patch {
+                file_path,
+                content,
+                original_hash,
+                ..
+            }
+            | ChangeOperation::Overwrite {
+                file_path,
+                content,
+                original_hash,
+                ..
+            } => vec![(
+                file_path.clone(),
+                Target {
+                    original_hash: original_hash.clone(),
+                    proposed_hash: Some(fs_utils::content_hash(content.as_bytes())),
+                    reported_hash: None,
+                },
+            )],
+            // Deletes carry no hash, so the file is hashed as it is now.
+            ChangeOperation::Delete { file_path, .. } => vec![(
+                file_path.clone(),
+                Target {
+                    original_hash: hash_file(&root_path.join(file_path)),
+                    proposed_hash: None,
+                    reported_hash: None,
+                },
+            )],
+            // A move takes the source's content to the destination and leaves the source absent.
+            ChangeOperation::Move {
+                from_path, to_path, ..
+            } => {
+                let moved_hash = hash_file(&root_path.join(from_path));
+                vec![
+                    (
+                        from_path.clone(),
+                        Target {
+                            original_hash: moved_hash.clone(),
+                            proposed_hash: None,
+                            reported_hash: None,
+                        },
+                    ),
+                    (
+                        to_path.clone(),
+                        Target {
+                            original_hash: hash_file(&root_path.join(to_path)),
+                            proposed_hash: moved_hash,
+                            reported_hash: None,
+                        },
+                    ),
+                ]
+            }
+        })
+        .collect()
+}
+
+fn hash_file(path: &Path) -> Option<String> {
+    std::fs::read(path)
+        .ok()
+        .map(|bytes| fs_utils::content_hash(&bytes))
+}
@@ -0,0 +1,151 @@
+use anyhow::Result;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
+use tokio::task::JoinHandle;
+
+/// How long output is still collected after a timed-out command was killed.
+const DRAIN_GRACE: Duration = Duration::from_millis(500);
+
+pub struct ShellOutput {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+}
+
+impl ShellOutput {
+    pub fn success(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+}
+
+/// Runs a command line through the platform shell. Once `timeout` elapses, the command and
+/// everything it started are killed, and the output produced so far is returned.
+pub async fn run(command: &str, cwd: &Path, timeout: Duration) -> Result<ShellOutput> {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    // Its own process group lets a timeout reach the processes the shell started too.
+    #[cfg(unix)]
+    cmd.process_group(0);
+    let mut child = cmd
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let (stdout, mut stdout_reader) = collect(child.stdout.take());
+    let (stderr, mut stderr_reader) = collect(child.stderr.take());
+
+    let finished = tokio::time::timeout(timeout, async {
+        let status = child.wait().await;
+        let _ = (&mut stdout_reader).await;
+        let _ = (&mut stderr_reader).await;
+        status
+    })
+    .await;
+
+    let (exit_code, timed_out) = match finished {
+        Ok(status) => (status?.code(), false),
+        Err(_) => {
+            kill_process_group(&mut child);
+            let _ = child.wait().await;
+            let _ = tokio::time::timeout(DRAIN_GRACE, async {
+                let _ = (&mut stdout_reader).await;
+                let _ = (&mut stderr_reader).await;
+            })
+            .await;
+            stdout_reader.abort();
+            stderr_reader.abort();
+            (None, true)
+        }
+    };
+
+    let text =
+        |buffer: &Mutex<Vec<u8>>| String::from_utf8_lossy(&buffer.lock().unwrap()).to_string();
+    Ok(ShellOutput {
+        exit_code,
+        stdout: text(&stdout),
+        stderr: text(&stderr),
+        timed_out,
+    })
+}
+
+/// Reads a pipe into a buffer as output arrives, so it survives the reader being cut short.
+fn collect(
+    pipe: Option<impl AsyncRead + Unpin + Send + 'static>,
+) -> (Arc<Mutex<Vec<u8>>>, JoinHandle<()>) {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let task_buffer = buffer.clone();
+    let reader = tokio::spawn(async move {
+        let Some(mut pipe) = pipe else {
+            return;
+        };
+        let mut chunk = [0; 8192];
+        while let Ok(n) = pipe.read(&mut chunk).await {
+            if n == 0 {
+                break;
+            }
+            task_buffer.lock().unwrap().extend_from_slice(&chunk[..n]);
+        }
+    });
+    (buffer, reader)
+}
+
+fn kill_process_group(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: `kill` has no memory-safety preconditions; a negative pid targets the group.
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+        }
+    }
+    let _ = child.start_kill();
+}
+
+pub fn quote_arg(arg: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("\"{}\"", arg.replace('"', "\"\""))
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_timeout_keeps_partial_output_and_kills_the_process_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = run(
+            "echo started; echo oops >&2; sleep 30 & echo $! > pid; wait",
+            dir.path(),
+            Duration::from_millis(500),
+        )
+        .await
+        .unwrap();
+
+        assert!(output.timed_out);
+        assert_eq!(output.exit_code, None);
+        assert_eq!(output.stdout, "started\n");
+        assert_eq!(output.stderr, "oops\n");
+
+        let pid = std::fs::read_to_string(dir.path().join("pid")).unwrap();
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid.trim()));
+        // Gone, or a zombie waiting to be reaped.
+        assert!(stat.is_err() || stat.unwrap().split_whitespace().nth(2) == Some("Z"));
+    }
+}
@@ -0,0 +1,489 @@
+use crate::types::{SyntaxCheck, SyntaxError};
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Language {
+    Rust,
+    TypeScript,
+    JavaScript,
+    Python,
+    Json,
+    /// JSON with comments and trailing commas, as read by editors and the TypeScript compiler.
+    Jsonc,
+    Yaml,
+    Toml,
+}
+
+impl Language {
+    pub(crate) fn from_path(file_path: &str) -> Option<Self> {
+        let extension = Path::new(file_path).extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "rs" => Some(Self::Rust),
+            "ts" | "tsx" | "mts" | "cts" => Some(Self::TypeScript),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+            "py" | "pyi" => Some(Self::Python),
+            "jsonc" => Some(Self::Jsonc),
+            "json" if is_jsonc_file(Path::new(file_path)) => Some(Self::Jsonc),
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::TypeScript => "typescript",
+            Self::JavaScript => "javascript",
+            Self::Python => "python",
+            Self::Json => "json",
+            Self::Jsonc => "jsonc",
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+        }
+    }
+}
+
+/// `.json` files that are conventionally read as JSONC.
+fn is_jsonc_file(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let in_vscode = path
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .is_some_and(|parent| parent == ".vscode");
+    in_vscode
+        || name.starts_with("tsconfig")
+        || name.starts_with("jsconfig")
+        || name == "devcontainer.json"
+        || name == ".devcontainer.json"
+}
+
+/// Checks proposed `content` for `file_path`, and whether the `original` file (if any) was
+/// already valid. Returns `None` for languages we cannot check.
+pub(crate) fn check_proposed(
+    file_path: &str,
+    content: &str,
+    original: Option<&str>,
+) -> Option<SyntaxCheck> {
+    let language = Language::from_path(file_path)?;
+    Some(SyntaxCheck {
+        language: language.name().to_string(),
+        errors: check(language, file_path, content),
+        original_valid: original.map(|original| check(language, file_path, original).is_empty()),
+    })
+}
+
+/// Returns the syntax errors in `content`, parsed as the language of `file_path`. Data formats
+/// and Rust are fully parsed; for TS/JS and Python only strings, comments and bracket
+/// balance are checked.
+pub(crate) fn check(language: Language, file_path: &str, content: &str) -> Vec<SyntaxError> {
+    let error = match language {
+        Language::Rust => check_rust(content),
+        Language::Json => check_json(content),
+        Language::Jsonc => check_json(&strip_jsonc(content)),
+        Language::Yaml => check_yaml(content),
+        Language::Toml => check_toml(content),
+        Language::TypeScript | Language::JavaScript => {
+            let jsx = file_path.ends_with('x');
+            check_delimiters(content, &LexRules::javascript(jsx))
+        }
+        Language::Python => check_delimiters(content, &LexRules::PYTHON),
+    };
+    error.into_iter().collect()
+}
+
+fn check_rust(content: &str) -> Option<SyntaxError> {
+    let error = syn::parse_file(content).err()?;
+    let start = error.span().start();
+    Some(SyntaxError {
+        line: start.line as u32,
+        column: start.column as u32 + 1,
+        message: error.to_string(),
+    })
+}
+
+fn check_json(content: &str) -> Option<SyntaxError> {
+    let error = serde_json::from_str::<serde_json::Value>(content).err()?;
+    Some(SyntaxError {
+        line: error.line() as u32,
+        column: error.column() as u32,
+        message: error.to_string(),
+    })
+}
+
+/// Blanks out comments and trailing commas, keeping every line and column where it was so that
+/// errors still point at the original content.
+fn strip_jsonc(content: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut out = chars.clone();
+    let blank = |out: &mut Vec<char>, i: usize| {
+        if out[i] != '\n' {
+            out[i] = ' ';
+        }
+    };
+    let mut pending_comma: Option<usize> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '"' => {
+                pending_comma = None;
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    blank(&mut out, i);
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                let start = i;
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                // An unterminated comment is left for the parser to report.
+                if i < chars.len() {
+                    for j in start..i + 2 {
+                        blank(&mut out, j);
+                    }
+                    i += 2;
+                }
+                continue;
+            }
+            ',' => pending_comma = Some(i),
+            ']' | '}' => {
+                if let Some(comma) = pending_comma.take() {
+                    out[comma] = ' ';
+                }
+            }
+            c if !c.is_whitespace() => pending_comma = None,
+            _ => {}
+        }
+        i += 1;
+    }
+    out.into_iter().collect()
+}
+
+fn check_yaml(content: &str) -> Option<SyntaxError> {
+    for document in serde_yaml::Deserializer::from_str(content) {
+        if let Err(error) = serde_yaml::Value::deserialize(document) {
+            let (line, column) = error
+                .location()
+                .map(|location| (location.line() as u32, location.column() as u32))
+                .unwrap_or((1, 1));
+            return Some(SyntaxError {
+                line,
+                column,
+                message: error.to_string(),
+            });
+        }
+    }
+    None
+}
+
+fn check_toml(content: &str) -> Option<SyntaxError> {
+    let error = content.parse::<toml::Table>().err()?;
+    let (line, column) = error
+        .span()
+        .map(|span| line_column(content, span.start))
+        .unwrap_or((1, 1));
+    Some(SyntaxError {
+        line,
+        column,
+        message: error.message().to_string(),
+    })
+}
+
+fn line_column(content: &str, offset: usize) -> (u32, u32) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    (line as u32, column as u32)
+}
+
+struct LexRules {
+    line_comment: &'static str,
+    block_comments: bool,
+    template_strings: bool,
+    triple_quotes: bool,
+    regex_literals: bool,
+    /// In JSX text, apostrophes are not string delimiters; an unclosed quote is ignored.
+    lenient_quotes: bool,
+}
+
+impl LexRules {
+    const PYTHON: Self = Self {
+        line_comment: "#",
+        block_comments: false,
+        template_strings: false,
+        triple_quotes: true,
+        regex_literals: false,
+        lenient_quotes: false,
+    };
+
+    fn javascript(jsx: bool) -> Self {
+        Self {
+            line_comment: "//",
+            block_comments: true,
+            template_strings: true,
+            triple_quotes: false,
+            regex_literals: true,
+            lenient_quotes: jsx,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Frame {
+    Bracket(char, usize),
+    Template(usize),
+    TemplateExpr,
+}
+
+/// Checks that strings and comments are terminated and brackets are balanced, reporting the
+/// first problem found.
+fn check_delimiters(content: &str, rules: &LexRules) -> Option<SyntaxError> {
+    let chars: Vec<(usize, char)> = content.char_indices().collect();
+    let at = |i: usize| chars.get(i).map(|&(_, c)| c);
+    let starts_with = |i: usize, s: &str| {
+        chars
+            .get(i)
+            .is_some_and(|&(offset, _)| content[offset..].starts_with(s))
+    };
+    let error = |i: usize, message: String| {
+        let offset = chars.get(i).map_or(content.len(), |&(offset, _)| offset);
+        let (line, column) = line_column(content, offset);
+        Some(SyntaxError {
+            line,
+            column,
+            message,
+        })
+    };
+
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut last_significant: Option<char> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i].1;
+
+        if let Some(&Frame::Template(start)) = stack.last() {
+            match c {
+                '\\' => i += 1,
+                '`' => {
+                    stack.pop();
+                    last_significant = Some('`');
+                }
+                '$' if at(i + 1) == Some('{') => {
+                    stack.push(Frame::TemplateExpr);
+                    i += 1;
+                }
+                _ => {}
+            }
+            i += 1;
+            if i >= chars.len() && matches!(stack.last(), Some(Frame::Template(_))) {
+                return error(start, "Unterminated template literal".to_string());
+            }
+            continue;
+        }
+
+        if starts_with(i, rules.line_comment) {
+            while i < chars.len() && chars[i].1 != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if rules.block_comments && starts_with(i, "/*") {
+            let start = i;
+            i += 2;
+            while i < chars.len() && !starts_with(i, "*/") {
+                i += 1;
+            }
+            if i >= chars.len() {
+                return error(start, "Unterminated block comment".to_string());
+            }
+            i += 2;
+            continue;
+        }
+        if rules.triple_quotes && (starts_with(i, "\"\"\"") || starts_with(i, "'''")) {
+            let start = i;
+            let quote = &content[chars[i].0..chars[i].0 + 3];
+            i += 3;
+            while i < chars.len() && !starts_with(i, quote) {
+                if chars[i].1 == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            if i >= chars.len() {
+                return error(start, "Unterminated triple-quoted string".to_string());
+            }
+            i += 3;
+            last_significant = Some('"');
+            continue;
+        }
+
+        match c {
+            '"' | '\'' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i].1 != c && chars[i].1 != '\n' {
+                    if chars[i].1 == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                if at(i) != Some(c) {
+                    if rules.lenient_quotes {
+                        i = start + 1;
+                        continue;
+                    }
+                    return error(start, "Unterminated string literal".to_string());
+                }
+                last_significant = Some('"');
+            }
+            '`' if rules.template_strings => stack.push(Frame::Template(i)),
+            '/' if rules.regex_literals
+                && last_significant.is_none_or(|p| "(,=:[!&|?{};+-*%<>~^".contains(p)) =>
+            {
+                // Skip a regex literal; if it does not close on this line it was division.
+                let mut j = i + 1;
+                let mut in_class = false;
+                while let Some(r) = at(j) {
+                    match r {
+                        '\\' => j += 1,
+                        '[' => in_class = true,
+                        ']' => in_class = false,
+                        '/' if !in_class => break,
+                        '\n' => break,
+                        _ => {}
+                    }
+                    j += 1;
+                }
+                if at(j) == Some('/') {
+                    i = j;
+                    last_significant = Some('/');
+                } else {
+                    last_significant = Some(c);
+                }
+            }
+            '(' | '[' | '{' => {
+                stack.push(Frame::Bracket(c, i));
+                last_significant = Some(c);
+            }
+            ')' | ']' | '}' => {
+                let expected = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                match stack.last().copied() {
+                    Some(Frame::TemplateExpr) if c == '}' => {
+                        stack.pop();
+                    }
+                    Some(Frame::Bracket(open, _)) if open == expected => {
+                        stack.pop();
+                    }
+                    Some(Frame::Bracket(open, position)) => {
+                        let (line, column) = line_column(content, chars[position].0);
+                        return error(
+                            i,
+                            format!("Unexpected `{c}`; `{open}` opened at {line}:{column} is not closed"),
+                        );
+                    }
+                    _ => return error(i, format!("Unexpected `{c}`")),
+                }
+                last_significant = Some(c);
+            }
+            c if !c.is_whitespace() => last_significant = Some(c),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    match stack.last() {
+        Some(Frame::Bracket(open, position)) => error(*position, format!("Unclosed `{open}`")),
+        Some(Frame::Template(position)) => {
+            error(*position, "Unterminated template literal".to_string())
+        }
+        Some(Frame::TemplateExpr) => {
+            error(chars.len(), "Unterminated template literal".to_string())
+        }
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_error(file_path: &str, content: &str) -> Option<(u32, u32)> {
+        let language = Language::from_path(file_path).unwrap();
+        check(language, file_path, content)
+            .first()
+            .map(|error| (error.line, error.column))
+    }
+
+    #[test]
+    fn test_javascript_delimiters() {
+        let valid = "const re = /[)}]/g;\nconst s = `a ${b({ c: '}' })} d`;\n// )\n/* ] */\nconst x = a / b / c;\n";
+        assert_eq!(first_error("a.ts", valid), None);
+        assert_eq!(
+            first_error("a.ts", "function f() {\n  return [1, 2;\n}\n"),
+            Some((3, 1))
+        );
+        assert_eq!(first_error("a.js", "const s = 'abc;\n"), Some((1, 11)));
+        assert_eq!(first_error("a.js", "if (x) {\n  y();\n"), Some((1, 8)));
+        assert_eq!(first_error("a.tsx", "const a = <p>Don't {x}</p>;\n"), None);
+    }
+
+    #[test]
+    fn test_python_delimiters() {
+        assert_eq!(
+            first_error("a.py", "s = \"\"\"\n(\n\"\"\"\nx = {'a': [1]}  # ]\n"),
+            None
+        );
+        assert_eq!(first_error("a.py", "x = foo(1,\n"), Some((1, 8)));
+    }
+
+    #[test]
+    fn test_parsed_languages() {
+        assert_eq!(first_error("a.rs", "fn main() {}\n"), None);
+        assert_eq!(
+            first_error("a.rs", "fn main() {\n    let x = ;\n}\n"),
+            Some((2, 13))
+        );
+        assert_eq!(first_error("a.json", "{\"a\": 1,}"), Some((1, 9)));
+        assert_eq!(
+            first_error(
+                "tsconfig.json",
+                "{\n  // paths\n  \"a\": [1, 2,], /* \"b\": 2 */\n  \"c\": \"//,}\",\n}\n"
+            ),
+            None
+        );
+        assert_eq!(first_error(".vscode/settings.json", "{\"a\": 1,}"), None);
+        assert_eq!(
+            first_error("a.jsonc", "{\n  // a\n  \"a\": 1 \"b\": 2\n}"),
+            Some((3, 10))
+        );
+        assert_eq!(
+            first_error("data/settings.json", "{\"a\": 1,}"),
+            Some((1, 9))
+        );
+        assert_eq!(first_error("a.toml", "[a]\nb = \n"), Some((2, 5)));
+        assert_eq!(first_error("a.yaml", "a: 1\n---\nb: [1, 2\n"), Some((4, 1)));
+        assert_eq!(first_error("a.yml", "a: 1\n---\nb: 2\n"), None);
+    }
+}
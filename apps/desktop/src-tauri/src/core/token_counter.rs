@@ -172,6 +172,16 @@ pub fn count_file_tokens(path: &Path) -> FileTokenInfo {
     }
 }
 
+/// The token count from an earlier `count_file_tokens`, if the file has not changed since.
+pub fn cached_tokens(path: &Path, metadata: &fs::Metadata) -> Option<u32> {
+    let key = CacheKey {
+        path: path.to_string_lossy().to_string(),
+        mtime_ms: metadata.modified().ok().and_then(to_millis)?,
+        size: metadata.len(),
+    };
+    TOKEN_CACHE.lock().unwrap().get(&key).copied()
+}
+
 pub async fn count_tokens_for_paths(paths: Vec<String>) -> anyhow::Result<Vec<FileTokenInfo>> {
     if paths.is_empty() {
         return Ok(Vec::new());
@@ -1,3 +1,6 @@
+use crate::core::protected_paths::ProtectedPathError;
+use crate::services::watcher_service::WatchLimitError;
+use crate::types::{ProtectedPathViolation, WatchLimitExceeded};
 use serde::{ser::Serializer, Serialize};
 use specta::Type;
 use thiserror::Error;
@@ -10,8 +13,57 @@ pub enum AppError {
     SerdeJson(#[from] serde_json::Error),
     #[error("UTF8 conversion error: {0}")]
     Utf8(#[from] std::string::FromUtf8Error),
+    #[error("{}", ProtectedPathError(.0.clone()))]
+    ProtectedPath(ProtectedPathViolation),
+    #[error("{}", WatchLimitError(.0.clone()))]
+    WatchLimit(WatchLimitExceeded),
     #[error("An internal error occurred: {0}")]
-    Anyhow(#[from] anyhow::Error),
+    Anyhow(anyhow::Error),
+}
+
+impl From<anyhow::Error> for AppError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<ProtectedPathError>() {
+            Ok(ProtectedPathError(violation)) => return AppError::ProtectedPath(violation),
+            Err(error) => error,
+        };
+        match error.downcast::<WatchLimitError>() {
+            Ok(WatchLimitError(exceeded)) => AppError::WatchLimit(exceeded),
+            Err(error) => AppError::Anyhow(error),
+        }
+    }
+}
+
+/// Errors reach the UI as a message, or with details for errors it can explain.
+#[derive(Serialize, Type)]
+#[serde(untagged)]
+enum ErrorPayload {
+    Message(String),
+    ProtectedPath {
+        message: String,
+        violation: ProtectedPathViolation,
+    },
+    #[serde(rename_all = "camelCase")]
+    WatchLimit {
+        message: String,
+        watch_limit: WatchLimitExceeded,
+    },
+}
+
+impl ErrorPayload {
+    fn from_error(error: &AppError) -> Self {
+        match error {
+            AppError::ProtectedPath(violation) => ErrorPayload::ProtectedPath {
+                message: error.to_string(),
+                violation: violation.clone(),
+            },
+            AppError::WatchLimit(exceeded) => ErrorPayload::WatchLimit {
+                message: error.to_string(),
+                watch_limit: exceeded.clone(),
+            },
+            _ => ErrorPayload::Message(error.to_string()),
+        }
+    }
 }
 
 impl Serialize for AppError {
@@ -19,14 +71,30 @@ impl Serialize for AppError {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        ErrorPayload::from_error(self).serialize(serializer)
     }
 }
 
 impl Type for AppError {
     fn inline(type_map: &mut specta::TypeMap, generics: specta::Generics) -> specta::DataType {
-        String::inline(type_map, generics)
+        ErrorPayload::inline(type_map, generics)
     }
 }
 
 pub type Result<T, E = AppError> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_limit_errors_keep_their_details() {
+        let error = AppError::WatchLimit(WatchLimitExceeded {
+            root_path: "/project".to_string(),
+            max_user_watches: Some(8192),
+        });
+        let payload = serde_json::to_value(&error).unwrap();
+        assert_eq!(payload["watchLimit"]["rootPath"], "/project");
+        assert_eq!(payload["watchLimit"]["maxUserWatches"], 8192);
+    }
+}
@@ -26,6 +26,7 @@ pub fn run() {
                 commands::register_window_project,
                 commands::close_window,
                 commands::list_directory_recursive,
+                commands::list_directory,
                 commands::get_relative_path,
                 commands::read_file_content,
                 commands::read_file_as_base64,
@@ -35,13 +36,44 @@ pub fn run() {
                 commands::is_binary_file,
                 commands::file_exists,
                 commands::write_file_content,
+                commands::set_symlink_policy,
+                commands::set_protected_paths,
+                commands::get_protected_paths,
                 commands::delete_file,
                 commands::move_file,
                 commands::backup_files,
                 commands::revert_file_from_backup,
                 commands::read_file_from_backup,
                 commands::delete_backup,
+                commands::list_backups,
+                commands::get_backup,
+                commands::revert_backup,
+                commands::record_history_entry,
+                commands::get_history,
+                commands::undo_change_set,
+                commands::redo_change_set,
+                commands::create_review_session,
+                commands::save_review_session,
+                commands::list_review_sessions,
+                commands::resume_review_session,
+                commands::discard_review_session,
                 commands::parse_changes_from_markdown,
+                commands::track_review,
+                commands::untrack_review,
+                commands::rerun_review,
+                commands::apply_selected_hunks,
+                commands::format_proposed_changes,
+                commands::format_files,
+                commands::run_verification,
+                commands::create_sandbox,
+                commands::apply_to_sandbox,
+                commands::run_in_sandbox,
+                commands::diff_sandbox,
+                commands::promote_sandbox,
+                commands::discard_sandbox,
+                commands::build_continuation_prompt,
+                commands::merge_continuation,
+                commands::build_repair_prompt,
                 commands::is_git_repository,
                 commands::get_git_status,
                 commands::get_recent_commits,
@@ -53,11 +85,20 @@ pub fn run() {
                 commands::kill_pty,
                 commands::get_cli_status,
                 commands::install_cli_shim,
+                commands::load_project_config,
+                commands::resolve_project_settings,
+                commands::approve_project_formatters,
                 commands::start_watching,
                 commands::stop_watching,
+                commands::restart_watcher,
+                commands::list_watchers,
                 commands::search_files
             ])
-            .events(tauri_specta::collect_events![]);
+            .events(tauri_specta::collect_events![
+                types::FsChangeEvent,
+                types::ProjectConfigChangedEvent,
+                types::ReviewTargetChangedEvent
+            ]);
 
         #[cfg(debug_assertions)]
         specta_builder
@@ -158,6 +199,11 @@ pub fn run() {
         .setup(move |app| {
             specta_builder.mount_events(app);
 
+            match app.path().app_data_dir() {
+                Ok(dir) => crate::core::backup_store::init(&dir),
+                Err(e) => error!("Failed to resolve app data dir for backups: {e}"),
+            }
+
             let handle = app.handle().clone();
             match app.cli().matches() {
                 Ok(matches) => {
@@ -202,6 +248,12 @@ pub fn run() {
             }
             Ok(())
         })
+        .on_window_event(|window, event| {
+            // Windows closed without `close_window` still release their watchers.
+            if let tauri::WindowEvent::Destroyed = event {
+                services::watcher_service::stop_watchers_for_window(window.label());
+            }
+        })
         .invoke_handler(invoke_handler)
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,3 +1,4 @@
+use crate::core::project_config;
 use crate::types::{IgnoreSettings, SearchResult};
 use anyhow::Result;
 use crossbeam_channel::unbounded;
@@ -8,7 +9,7 @@ fn normalize_path_str(p: &Path) -> String {
     p.to_string_lossy().replace('\\', "/")
 }
 
-fn calculate_fuzzy_score(query: &str, text: &str) -> i32 {
+pub(crate) fn calculate_fuzzy_score(query: &str, text: &str) -> i32 {
     if query.is_empty() {
         return 0;
     }
@@ -114,6 +115,7 @@ pub async fn search_files(
         return Ok(vec![]);
     }
 
+    let settings = project_config::ignore_settings(root_path, settings);
     let root_path_owned = root_path.to_owned();
     let query_owned = query.to_string();
     let limit = limit.unwrap_or(100) as usize;
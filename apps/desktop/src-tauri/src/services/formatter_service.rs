@@ -0,0 +1,130 @@
+use crate::core::{fs_utils, path_utils, project_config, shell};
+use crate::types::{FormatterConfig, FormatterResult};
+use anyhow::{anyhow, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+use std::time::Duration;
+
+const FORMATTER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Formatters compiled against a project root; the first matching glob wins.
+pub struct FormatterSet {
+    formatters: Vec<(Gitignore, String)>,
+}
+
+impl FormatterSet {
+    pub fn new(root_path: &Path, formatters: &[FormatterConfig]) -> Result<Self> {
+        let formatters = formatters
+            .iter()
+            .map(|formatter| {
+                let mut builder = GitignoreBuilder::new(root_path);
+                builder.add_line(None, &formatter.glob)?;
+                Ok((builder.build()?, formatter.command.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { formatters })
+    }
+
+    /// The command for `relative_path`; paths outside the project never have one.
+    pub fn command_for(&self, relative_path: &str) -> Option<&str> {
+        let relative_path = path_utils::normalize_relative(relative_path)?;
+        self.formatters
+            .iter()
+            .find(|(matcher, _)| {
+                matcher
+                    .matched_path_or_any_parents(&relative_path, false)
+                    .is_ignore()
+            })
+            .map(|(_, command)| command.as_str())
+    }
+}
+
+/// Formats the files at `relative_paths` in place, reporting on each file that has a formatter.
+pub async fn format_files(
+    root_path: &Path,
+    relative_paths: Vec<String>,
+    formatters: Vec<FormatterConfig>,
+) -> Result<Vec<FormatterResult>> {
+    let formatters = project_config::formatters(root_path, formatters);
+    let formatter_set = FormatterSet::new(root_path, &formatters)?;
+    let mut results = Vec::new();
+    for relative_path in relative_paths {
+        // Formatting rewrites the file, so it is held to the same rules as any other write.
+        let path = match fs_utils::resolve_relative(root_path, &relative_path)
+            .and_then(|path| fs_utils::ensure_not_protected(&path, root_path).map(|_| path))
+        {
+            Ok(path) => path,
+            Err(e) => {
+                results.push(formatter_result(relative_path, "", Some(e)));
+                continue;
+            }
+        };
+        let Some(command) = formatter_set.command_for(&relative_path) else {
+            continue;
+        };
+        let outcome = run_formatter(command, &path, root_path).await;
+        results.push(formatter_result(relative_path, command, outcome.err()));
+    }
+    Ok(results)
+}
+
+/// Formats proposed `content` in a temporary copy named like `relative_path`, without touching
+/// the project. The formatter still runs from the project root so it picks up project config.
+pub async fn format_content(
+    root_path: &Path,
+    relative_path: &str,
+    content: &str,
+    command: &str,
+) -> Result<String> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_path = fs_utils::resolve_relative(temp_dir.path(), relative_path)?;
+    if let Some(parent) = temp_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&temp_path, content).await?;
+    run_formatter(command, &temp_path, root_path).await?;
+    fs_utils::read_file_content(&temp_path).await
+}
+
+pub fn formatter_result(
+    file_path: String,
+    command: &str,
+    error: Option<anyhow::Error>,
+) -> FormatterResult {
+    FormatterResult {
+        file_path,
+        command: command.to_string(),
+        success: error.is_none(),
+        error: error.map(|e| e.to_string()),
+    }
+}
+
+async fn run_formatter(command: &str, file_path: &Path, cwd: &Path) -> Result<()> {
+    let quoted_path = shell::quote_arg(&file_path.to_string_lossy());
+    let command_line = if command.contains("{file}") {
+        command.replace("{file}", &quoted_path)
+    } else {
+        format!("{} {}", command, quoted_path)
+    };
+
+    let output = shell::run(&command_line, cwd, FORMATTER_TIMEOUT).await?;
+    if output.timed_out {
+        return Err(anyhow!(
+            "Formatter timed out after {}s",
+            FORMATTER_TIMEOUT.as_secs()
+        ));
+    }
+    if !output.success() {
+        let message = if output.stderr.trim().is_empty() {
+            output.stdout.trim()
+        } else {
+            output.stderr.trim()
+        };
+        return Err(anyhow!(
+            "Formatter exited with code {}: {}",
+            output.exit_code.unwrap_or(-1),
+            message
+        ));
+    }
+    Ok(())
+}
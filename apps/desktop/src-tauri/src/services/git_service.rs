@@ -1,6 +1,7 @@
 use crate::types::{Commit, DiffOption, GitStatus};
 use anyhow::{anyhow, Result};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 fn run_git_command(cwd: &Path, args: &[&str]) -> Result<String> {
@@ -119,10 +120,86 @@ pub fn get_git_diff(repo_path: &Path, option: DiffOption) -> Result<String> {
     }
 }
 
+pub fn get_repo_root(path: &Path) -> Result<PathBuf> {
+    let output = run_git_command(path, &["rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(output.trim()))
+}
+
+/// Paths relative to the repository root whose working tree state differs from HEAD,
+/// including untracked files and both sides of renames.
+pub fn get_dirty_paths(repo_path: &Path) -> Result<Vec<String>> {
+    let output = run_git_command(
+        repo_path,
+        &["status", "--porcelain", "-z", "--untracked-files=all"],
+    )?;
+    let mut paths = Vec::new();
+    let mut entries = output.split('\0');
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (status, path) = entry.split_at(3);
+        paths.push(path.to_string());
+        if status.starts_with('R') || status.starts_with('C') {
+            if let Some(original_path) = entries.next() {
+                paths.push(original_path.to_string());
+            }
+        }
+    }
+    Ok(paths)
+}
+
+/// The porcelain status code of every changed file under `path`, keyed by its path relative
+/// to `path`.
+pub fn get_status_codes(path: &Path) -> Result<HashMap<String, String>> {
+    let repo_root = get_repo_root(path)?;
+    let prefix = std::fs::canonicalize(path)?
+        .strip_prefix(&repo_root)
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let output = run_git_command(
+        &repo_root,
+        &["status", "--porcelain", "-z", "--untracked-files=all"],
+    )?;
+    let mut codes = HashMap::new();
+    let mut entries = output.split('\0');
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (status, file_path) = entry.split_at(3);
+        let code = status[..2].to_string();
+        if code.starts_with('R') || code.starts_with('C') {
+            entries.next();
+        }
+        if let Ok(relative) = Path::new(file_path).strip_prefix(&prefix) {
+            codes.insert(relative.to_string_lossy().replace('\\', "/"), code);
+        }
+    }
+    Ok(codes)
+}
+
+pub fn add_worktree(repo_path: &Path, worktree_path: &Path) -> Result<()> {
+    let worktree_path = worktree_path.to_string_lossy();
+    run_git_command(
+        repo_path,
+        &["worktree", "add", "--detach", &worktree_path, "HEAD"],
+    )?;
+    Ok(())
+}
+
+pub fn remove_worktree(repo_path: &Path, worktree_path: &Path) -> Result<()> {
+    let worktree_path = worktree_path.to_string_lossy();
+    run_git_command(
+        repo_path,
+        &["worktree", "remove", "--force", &worktree_path],
+    )?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     #[test]
     fn test_workspace_diff_includes_untracked() {
@@ -0,0 +1,181 @@
+use crate::core::{backup_store, fs_utils, path_utils};
+use crate::types::{
+    BackupFile, ChangeSummary, ConflictKind, HistoryConflict, HistoryEntry, HistoryStepResult,
+    ProjectHistory,
+};
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+const HISTORY_DIR: &str = "history";
+const MAX_ENTRIES: usize = 50;
+
+/// Undo, redo and recording each read, change and write a project's history file.
+static HISTORY_LOCK: Lazy<Mutex<()>> = Lazy::new(Default::default);
+
+/// Records a change set that was just applied. `backup_id` is the backup taken before applying
+/// it; the history keeps its own copy, plus a snapshot of the same paths as they are now.
+/// Anything that could have been redone is discarded.
+pub async fn record_applied(
+    data_dir: &Path,
+    root_path: &Path,
+    backup_id: &str,
+    summary: Option<ChangeSummary>,
+) -> Result<ProjectHistory> {
+    let _guard = HISTORY_LOCK.lock().await;
+    let before = backup_store::get_backup(backup_id).await?;
+    let files: Vec<String> = before.files.into_iter().map(|file| file.path).collect();
+    let before_backup_id = backup_store::clone_backup(backup_id).await?;
+    let after_backup_id = backup_store::create_backup(
+        root_path,
+        files.iter().map(PathBuf::from).collect(),
+        summary.clone(),
+    )
+    .await?;
+
+    let mut history = load_history(data_dir, root_path).await?;
+    let discarded = history.entries.split_off(history.position as usize);
+    history.entries.push(HistoryEntry {
+        id: Uuid::new_v4().to_string(),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as f64)
+            .unwrap_or_default(),
+        summary,
+        files,
+        before_backup_id,
+        after_backup_id,
+    });
+    let overflow = history.entries.len().saturating_sub(MAX_ENTRIES);
+    let dropped: Vec<_> = history.entries.drain(..overflow).collect();
+    history.position = history.entries.len() as u32;
+
+    save_history(data_dir, root_path, &history).await?;
+    for entry in discarded.iter().chain(&dropped) {
+        delete_entry_backups(entry).await?;
+    }
+    Ok(history)
+}
+
+pub async fn get_history(data_dir: &Path, root_path: &Path) -> Result<ProjectHistory> {
+    load_history(data_dir, root_path).await
+}
+
+/// Reverts the most recently applied change set, unless one of its files was changed since.
+pub async fn undo(data_dir: &Path, root_path: &Path) -> Result<HistoryStepResult> {
+    let _guard = HISTORY_LOCK.lock().await;
+    let history = load_history(data_dir, root_path).await?;
+    let Some(entry) = (history.position as usize)
+        .checked_sub(1)
+        .and_then(|index| history.entries.get(index))
+        .cloned()
+    else {
+        return Err(anyhow!("Nothing to undo"));
+    };
+    step(
+        data_dir,
+        root_path,
+        history,
+        &entry.after_backup_id,
+        &entry.before_backup_id,
+        |history| history.position -= 1,
+    )
+    .await
+}
+
+/// Re-applies the change set after the current position, unless one of its files was changed
+/// since it was undone.
+pub async fn redo(data_dir: &Path, root_path: &Path) -> Result<HistoryStepResult> {
+    let _guard = HISTORY_LOCK.lock().await;
+    let history = load_history(data_dir, root_path).await?;
+    let Some(entry) = history.entries.get(history.position as usize).cloned() else {
+        return Err(anyhow!("Nothing to redo"));
+    };
+    step(
+        data_dir,
+        root_path,
+        history,
+        &entry.before_backup_id,
+        &entry.after_backup_id,
+        |history| history.position += 1,
+    )
+    .await
+}
+
+/// Moves the project from the state in `expected_backup_id` to the one in `target_backup_id`,
+/// refusing if the files on disk no longer match the expected state.
+async fn step(
+    data_dir: &Path,
+    root_path: &Path,
+    mut history: ProjectHistory,
+    expected_backup_id: &str,
+    target_backup_id: &str,
+    advance: impl FnOnce(&mut ProjectHistory),
+) -> Result<HistoryStepResult> {
+    let expected = backup_store::get_backup(expected_backup_id).await?;
+    let conflicts = find_conflicts(root_path, &expected.files).await?;
+    if !conflicts.is_empty() {
+        return Ok(HistoryStepResult {
+            applied: false,
+            conflicts,
+            history,
+        });
+    }
+
+    backup_store::revert_backup(target_backup_id).await?;
+    advance(&mut history);
+    save_history(data_dir, root_path, &history).await?;
+    Ok(HistoryStepResult {
+        applied: true,
+        conflicts: Vec::new(),
+        history,
+    })
+}
+
+async fn find_conflicts(root_path: &Path, expected: &[BackupFile]) -> Result<Vec<HistoryConflict>> {
+    let mut conflicts = Vec::new();
+    for file in expected {
+        let actual = backup_store::current_hash(root_path, &file.path).await?;
+        let kind = match (&file.hash, &actual) {
+            (Some(a), Some(b)) if a != b => ConflictKind::Modified,
+            (None, Some(_)) => ConflictKind::Created,
+            (Some(_), None) => ConflictKind::Deleted,
+            _ => continue,
+        };
+        conflicts.push(HistoryConflict {
+            file_path: file.path.clone(),
+            kind,
+        });
+    }
+    Ok(conflicts)
+}
+
+async fn delete_entry_backups(entry: &HistoryEntry) -> Result<()> {
+    backup_store::delete_backup(&entry.before_backup_id).await?;
+    backup_store::delete_backup(&entry.after_backup_id).await
+}
+
+fn history_path(data_dir: &Path, root_path: &Path) -> PathBuf {
+    let key = path_utils::project_key(&root_path.to_string_lossy());
+    data_dir.join(HISTORY_DIR).join(format!("{key}.json"))
+}
+
+async fn load_history(data_dir: &Path, root_path: &Path) -> Result<ProjectHistory> {
+    let path = history_path(data_dir, root_path);
+    if !path.exists() {
+        return Ok(ProjectHistory::default());
+    }
+    Ok(serde_json::from_slice(&fs::read(&path).await?)?)
+}
+
+async fn save_history(data_dir: &Path, root_path: &Path, history: &ProjectHistory) -> Result<()> {
+    let path = history_path(data_dir, root_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    fs_utils::write_atomic(&path, serde_json::to_vec_pretty(history)?).await
+}
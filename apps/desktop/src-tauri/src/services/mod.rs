@@ -1,7 +1,12 @@
 pub mod cli_service;
 pub mod file_search_service;
+pub mod formatter_service;
 pub mod git_service;
+pub mod history_service;
 pub mod project_service;
 pub mod pty_service;
 pub mod review_service;
+pub mod sandbox_service;
+pub mod session_service;
+pub mod verification_service;
 pub mod watcher_service;
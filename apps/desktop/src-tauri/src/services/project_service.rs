@@ -1,13 +1,43 @@
-use crate::core::fs_utils;
-use crate::types::{FileNode, IgnoreSettings};
+use crate::core::{file_metadata, fs_utils, project_config, protected_paths};
+use crate::services::git_service;
+use crate::types::{
+    FileNode, IgnoreSettings, ProjectConfigStatus, ProjectSettings, ProtectedPathRule,
+    ResolvedProjectSettings, SymlinkPolicy,
+};
 use anyhow::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+/// The whole tree below `root_path`. With `with_metadata`, every node also carries its
+/// [`FileMetadata`](crate::types::FileMetadata), which costs a read of the start of each file.
 pub async fn list_directory_recursive(
     root_path: &Path,
     settings: IgnoreSettings,
+    with_metadata: bool,
 ) -> Result<FileNode> {
-    fs_utils::list_directory_recursive(root_path, settings).await
+    let settings = project_config::ignore_settings(root_path, settings);
+    let mut tree = fs_utils::list_directory_recursive(root_path, settings).await?;
+    if with_metadata {
+        let root_path = root_path.to_path_buf();
+        tree = tokio::task::spawn_blocking(move || {
+            let git_statuses = git_service::get_status_codes(&root_path).unwrap_or_default();
+            let tree_root = PathBuf::from(&tree.path);
+            file_metadata::annotate(&mut tree, &tree_root, &git_statuses);
+            tree
+        })
+        .await?;
+    }
+    Ok(tree)
+}
+
+/// One level of the tree (or `depth` levels) below `dir_path`, for expanding it on demand.
+pub async fn list_directory(
+    root_path: &Path,
+    dir_path: &Path,
+    settings: IgnoreSettings,
+    depth: Option<u32>,
+) -> Result<FileNode> {
+    let settings = project_config::ignore_settings(root_path, settings);
+    fs_utils::list_directory(root_path, dir_path, settings, depth.unwrap_or(1)).await
 }
 
 pub async fn read_file_content(path: &Path) -> Result<String> {
@@ -33,3 +63,41 @@ pub async fn move_file(from: &Path, to: &Path, root_path: &Path) -> Result<()> {
 pub async fn is_binary(path: &Path) -> Result<bool> {
     fs_utils::is_binary(path).await
 }
+
+pub fn set_symlink_policy(root_path: &Path, policy: SymlinkPolicy) {
+    fs_utils::set_symlink_policy(root_path, policy)
+}
+
+pub fn set_protected_paths(root_path: &Path, rules: Vec<ProtectedPathRule>) -> Result<()> {
+    protected_paths::set_rules(root_path, rules)
+}
+
+pub fn get_protected_paths(root_path: &Path) -> Vec<ProtectedPathRule> {
+    protected_paths::rules_for(root_path)
+}
+
+/// Re-reads the project's `.repowizard.toml`.
+pub fn load_project_config(root_path: &Path) -> ProjectConfigStatus {
+    project_config::load(root_path)
+}
+
+/// The user's settings with the project's config file applied on top.
+pub fn resolve_project_settings(
+    root_path: &Path,
+    settings: ProjectSettings,
+) -> ResolvedProjectSettings {
+    ResolvedProjectSettings {
+        settings: project_config::merge(
+            settings,
+            project_config::approved_config(root_path).as_ref(),
+        ),
+        config: project_config::status(root_path),
+        formatter_consent: project_config::formatter_consent(root_path),
+    }
+}
+
+/// Lets the formatter commands from the project's config file run. `fingerprint` must be the
+/// one from the [`FormatterConsent`](crate::types::FormatterConsent) the user approved.
+pub fn approve_project_formatters(root_path: &Path, fingerprint: &str) -> Result<()> {
+    project_config::approve_formatters(root_path, fingerprint)
+}
@@ -1,9 +1,29 @@
-use crate::core::{fs_utils, parser};
-use crate::types::ChangeOperation;
+use crate::core::{
+    backup_store, binary_utils, followup_prompt, fs_utils, hunks, parser, project_config,
+    protected_paths, review_targets, syntax_check, token_counter,
+};
+use crate::services::{file_search_service, formatter_service, watcher_service};
+use crate::types::{
+    BackupManifest, ChangeOperation, ChangeSummary, EditFormat, FormattedChanges, FormatterConfig,
+    Hunk, IgnoreSettings, OperationSource, PatchBlock, PathSuggestion, PathSuggestionReason,
+    ProtectionLevel, RepairPrompt, ReviewResult, ReviewTargetChange, Truncation,
+};
 use anyhow::{anyhow, Result};
+use ignore::WalkBuilder;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+const MAX_PATH_SUGGESTIONS: usize = 3;
+const MAX_SUGGESTION_FILE_SIZE: u64 = 1024 * 1024;
+/// Files read in search of a missing target's SEARCH content; the rest are matched by path only.
+const MAX_SUGGESTION_FILES_READ: usize = 500;
+
+/// A PATCH/OVERWRITE target that does not exist and was not declared as a new file.
+struct MissingTarget {
+    file_path: String,
+    search_replace_blocks: Vec<parser::SearchReplaceBlock>,
+}
+
 pub async fn process_markdown_changes(
     markdown: &str,
     root_path: &str,
@@ -25,55 +45,86 @@ pub async fn process_markdown_changes(
     }
 
     let mut processed_ops: Vec<ChangeOperation> = Vec::new();
+    let mut missing_targets: Vec<MissingTarget> = Vec::new();
 
     for (file_path, ops) in file_ops {
         let path_buf = root_path_buf.join(&file_path);
-        let mut current_content = if path_buf.exists() {
+        let target_exists = path_buf.exists();
+        let mut current_content = if target_exists {
             fs_utils::read_file_bytes(&path_buf).await?
         } else {
             Vec::new()
         };
+        let original_hash = target_exists.then(|| fs_utils::content_hash(&current_content));
+        let original_text = String::from_utf8_lossy(&current_content).to_string();
 
-        let mut is_new_file_flag = !path_buf.exists();
+        let mut is_new_file_flag = !target_exists;
+        let mut declared_new_file = false;
         let mut last_op_type_is_patch = false;
         let mut acc_total_blocks: u32 = 0;
         let mut acc_applied_blocks: u32 = 0;
+        let mut acc_blocks: Vec<PatchBlock> = Vec::new();
+        let mut missing_target_blocks: Vec<parser::SearchReplaceBlock> = Vec::new();
+        let mut op_source = OperationSource::default();
+        let mut op_truncation: Option<Truncation> = None;
 
         for op in ops {
             match op {
                 parser::IntermediateOperation::Patch {
                     search_replace_blocks,
                     is_new_file,
+                    source,
+                    truncation,
                     ..
                 } => {
                     let mut content_str = String::from_utf8_lossy(&current_content).to_string();
                     acc_total_blocks += search_replace_blocks.len() as u32;
+                    if !target_exists {
+                        missing_target_blocks.extend(search_replace_blocks.iter().cloned());
+                    }
 
-                    for (search_block, replace_block) in search_replace_blocks {
-                        match apply_patch(&content_str, &search_block, &replace_block) {
+                    for block in search_replace_blocks {
+                        let applied = match apply_patch(&content_str, &block.search, &block.replace)
+                        {
                             Ok(new_content) => {
                                 content_str = new_content;
                                 acc_applied_blocks += 1;
+                                true
                             }
                             Err(_) => {
                                 // Skip if not found
+                                false
                             }
-                        }
+                        };
+                        acc_blocks.push(PatchBlock {
+                            search: block.search,
+                            replace: block.replace,
+                            applied,
+                            span: block.span,
+                        });
                     }
                     current_content = content_str.into_bytes();
+                    op_source = source;
+                    op_truncation = truncation;
                     if is_new_file {
                         is_new_file_flag = true;
+                        declared_new_file = true;
                     }
                     last_op_type_is_patch = true;
                 }
                 parser::IntermediateOperation::Overwrite {
                     content,
                     is_new_file,
+                    source,
+                    truncation,
                     ..
                 } => {
                     current_content = content.into_bytes();
+                    op_source = source;
+                    op_truncation = truncation;
                     if is_new_file {
                         is_new_file_flag = true;
+                        declared_new_file = true;
                     }
                     last_op_type_is_patch = false;
                     // Reset accumulators if overwritten?
@@ -82,6 +133,7 @@ pub async fn process_markdown_changes(
                     // Usually overwrite is a fresh start.
                     acc_total_blocks = 0;
                     acc_applied_blocks = 0;
+                    acc_blocks.clear();
                 }
                 _ => {}
             }
@@ -89,6 +141,27 @@ pub async fn process_markdown_changes(
 
         let final_content = String::from_utf8(current_content)?;
 
+        if !target_exists && !declared_new_file {
+            missing_targets.push(MissingTarget {
+                file_path: file_path.clone(),
+                search_replace_blocks: missing_target_blocks,
+            });
+        }
+
+        let syntax = syntax_check::check_proposed(
+            &file_path,
+            &final_content,
+            target_exists.then_some(original_text.as_str()),
+        );
+        let base = hunk_base(original_text, last_op_type_is_patch, acc_applied_blocks);
+        let op_hunks = if last_op_type_is_patch {
+            hunks::block_hunks(&file_path, &base, &final_content, &acc_blocks)
+                .unwrap_or_else(|| hunks::diff_hunks(&file_path, &base, &final_content))
+        } else {
+            hunks::diff_hunks(&file_path, &base, &final_content)
+        };
+        let (diff, stats) = hunks::line_diff(&base, &final_content);
+
         if last_op_type_is_patch {
             processed_ops.push(ChangeOperation::Patch {
                 file_path,
@@ -96,23 +169,70 @@ pub async fn process_markdown_changes(
                 is_new_file: is_new_file_flag,
                 total_blocks: acc_total_blocks,
                 applied_blocks: acc_applied_blocks,
+                blocks: acc_blocks,
+                hunks: op_hunks,
+                original_hash,
+                diff,
+                stats,
+                path_suggestions: Vec::new(),
+                source: op_source,
+                truncation: op_truncation,
+                syntax,
             });
         } else {
             processed_ops.push(ChangeOperation::Overwrite {
                 file_path,
                 content: final_content,
                 is_new_file: is_new_file_flag,
+                hunks: op_hunks,
+                original_hash,
+                diff,
+                stats,
+                path_suggestions: Vec::new(),
+                source: op_source,
+                truncation: op_truncation,
+                syntax,
             });
         }
     }
 
+    if !missing_targets.is_empty() {
+        let mut suggestions =
+            suggest_paths_for_missing_targets(&root_path_buf, missing_targets).await?;
+        for op in processed_ops.iter_mut() {
+            if let ChangeOperation::Patch {
+                file_path,
+                path_suggestions,
+                ..
+            }
+            | ChangeOperation::Overwrite {
+                file_path,
+                path_suggestions,
+                ..
+            } = op
+            {
+                if let Some(found) = suggestions.remove(file_path.as_str()) {
+                    *path_suggestions = found;
+                }
+            }
+        }
+    }
+
     for op in other_ops {
         match op {
-            parser::IntermediateOperation::Delete { file_path } => {
-                processed_ops.push(ChangeOperation::Delete { file_path });
+            parser::IntermediateOperation::Delete { file_path, source } => {
+                processed_ops.push(ChangeOperation::Delete { file_path, source });
             }
-            parser::IntermediateOperation::Move { from_path, to_path } => {
-                processed_ops.push(ChangeOperation::Move { from_path, to_path });
+            parser::IntermediateOperation::Move {
+                from_path,
+                to_path,
+                source,
+            } => {
+                processed_ops.push(ChangeOperation::Move {
+                    from_path,
+                    to_path,
+                    source,
+                });
             }
             _ => {}
         }
@@ -121,8 +241,366 @@ pub async fn process_markdown_changes(
     Ok(processed_ops)
 }
 
-pub async fn backup_files(root_path: &Path, paths: Vec<PathBuf>) -> Result<String> {
-    fs_utils::backup_files(root_path, paths).await
+/// Parses a response into a review: operations on denied paths are dropped, and every
+/// protected path an operation touches is reported.
+pub async fn review_changes(markdown: &str, root_path: &str) -> Result<ReviewResult> {
+    let operations = process_markdown_changes(markdown, root_path).await?;
+    let protected = protected_paths::for_project(Path::new(root_path))?;
+
+    let mut kept = Vec::new();
+    let mut violations = Vec::new();
+    for op in operations {
+        let paths = match &op {
+            ChangeOperation::Patch { file_path, .. }
+            | ChangeOperation::Overwrite { file_path, .. }
+            | ChangeOperation::Delete { file_path, .. } => vec![file_path],
+            ChangeOperation::Move {
+                from_path, to_path, ..
+            } => vec![from_path, to_path],
+        };
+        let op_violations: Vec<_> = paths
+            .into_iter()
+            .filter_map(|path| protected.check(path))
+            .collect();
+        let denied = op_violations
+            .iter()
+            .any(|violation| violation.level == ProtectionLevel::Deny);
+        violations.extend(op_violations);
+        if !denied {
+            kept.push(op);
+        }
+    }
+
+    Ok(ReviewResult {
+        summary: summarize_changes(&kept),
+        operations: kept,
+        violations,
+    })
+}
+
+/// Tracks the files a review touches, so that edits made to them elsewhere are reported.
+pub fn track_review(root_path: &Path, markdown: &str, operations: &[ChangeOperation]) {
+    review_targets::track(root_path, markdown, operations);
+}
+
+pub fn untrack_review(root_path: &Path) {
+    review_targets::untrack(root_path);
+}
+
+/// Files of the open review among `paths` that were changed outside the review.
+pub fn changed_review_targets<'a>(
+    root_path: &Path,
+    paths: impl IntoIterator<Item = &'a Path>,
+) -> Vec<ReviewTargetChange> {
+    review_targets::changed(root_path, paths)
+}
+
+/// Parses the open review's response again against the current content of `file_paths`, and
+/// returns the new operations for those files.
+pub async fn rerun_review(root_path: &str, file_paths: &[String]) -> Result<Vec<ChangeOperation>> {
+    let root_path_buf = PathBuf::from(root_path);
+    let markdown = review_targets::markdown(&root_path_buf)
+        .ok_or_else(|| anyhow!("No review is open for {root_path}"))?;
+    let operations: Vec<ChangeOperation> = process_markdown_changes(&markdown, root_path)
+        .await?
+        .into_iter()
+        .filter(|op| match op {
+            ChangeOperation::Patch { file_path, .. }
+            | ChangeOperation::Overwrite { file_path, .. }
+            | ChangeOperation::Delete { file_path, .. } => file_paths.contains(file_path),
+            ChangeOperation::Move { .. } => false,
+        })
+        .collect();
+    review_targets::retrack(&root_path_buf, &operations);
+    Ok(operations)
+}
+
+pub fn summarize_changes(operations: &[ChangeOperation]) -> ChangeSummary {
+    let mut summary = ChangeSummary::default();
+    for op in operations {
+        match op {
+            ChangeOperation::Patch {
+                is_new_file,
+                stats,
+                total_blocks,
+                applied_blocks,
+                ..
+            } => {
+                if *is_new_file {
+                    summary.files_created += 1;
+                } else {
+                    summary.files_modified += 1;
+                }
+                summary.lines_added += stats.added;
+                summary.lines_removed += stats.removed;
+                summary.total_blocks += total_blocks;
+                summary.applied_blocks += applied_blocks;
+            }
+            ChangeOperation::Overwrite {
+                is_new_file, stats, ..
+            } => {
+                if *is_new_file {
+                    summary.files_created += 1;
+                } else {
+                    summary.files_modified += 1;
+                }
+                summary.lines_added += stats.added;
+                summary.lines_removed += stats.removed;
+            }
+            ChangeOperation::Delete { .. } => summary.files_deleted += 1,
+            ChangeOperation::Move { .. } => summary.files_moved += 1,
+        }
+    }
+    summary
+}
+
+/// Refuses an operation the response was cut off in; its content is incomplete until a
+/// continuation has been merged and the response reviewed again.
+pub fn ensure_complete(operation: &ChangeOperation) -> Result<()> {
+    match operation {
+        ChangeOperation::Patch {
+            file_path,
+            truncation: Some(_),
+            ..
+        }
+        | ChangeOperation::Overwrite {
+            file_path,
+            truncation: Some(_),
+            ..
+        } => Err(anyhow!(
+            "The change to {} was cut off. Merge a continuation of the response before applying it.",
+            file_path
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Rebuilds the content of a patched or overwritten file from its original with only the
+/// hunks in `hunk_ids` applied.
+pub async fn apply_selected_hunks(
+    root_path: &Path,
+    operation: ChangeOperation,
+    hunk_ids: Vec<String>,
+) -> Result<String> {
+    ensure_complete(&operation)?;
+    let (file_path, op_hunks, is_patch, applied_blocks) = match &operation {
+        ChangeOperation::Patch {
+            file_path,
+            hunks,
+            applied_blocks,
+            ..
+        } => (file_path, hunks, true, *applied_blocks),
+        ChangeOperation::Overwrite {
+            file_path, hunks, ..
+        } => (file_path, hunks, false, 0),
+        _ => return Err(anyhow!("Only patch and overwrite operations have hunks")),
+    };
+
+    let selected = hunk_ids
+        .iter()
+        .map(|id| {
+            op_hunks
+                .iter()
+                .find(|hunk| &hunk.id == id)
+                .cloned()
+                .ok_or_else(|| anyhow!("Unknown hunk: {}", id))
+        })
+        .collect::<Result<Vec<Hunk>>>()?;
+
+    let original = read_original_text(root_path, file_path).await?;
+    hunks::apply_hunks(&hunk_base(original, is_patch, applied_blocks), &selected)
+}
+
+/// Runs the matching formatter over each proposed file so that the reviewed diff shows
+/// formatted content. Files whose formatter fails keep their content unchanged.
+pub async fn format_proposed_changes(
+    root_path: &Path,
+    mut operations: Vec<ChangeOperation>,
+    formatters: Vec<FormatterConfig>,
+) -> Result<FormattedChanges> {
+    let formatters = project_config::formatters(root_path, formatters);
+    let formatter_set = formatter_service::FormatterSet::new(root_path, &formatters)?;
+    let mut results = Vec::new();
+
+    for op in operations.iter_mut() {
+        let (file_path, proposed, is_patch, applied) = match &*op {
+            ChangeOperation::Patch {
+                file_path,
+                content,
+                applied_blocks,
+                ..
+            } => (file_path.clone(), content.clone(), true, *applied_blocks),
+            ChangeOperation::Overwrite {
+                file_path, content, ..
+            } => (file_path.clone(), content.clone(), false, 0),
+            _ => continue,
+        };
+        if let Err(e) = fs_utils::resolve_relative(root_path, &file_path) {
+            results.push(formatter_service::formatter_result(file_path, "", Some(e)));
+            continue;
+        }
+        let Some(command) = formatter_set.command_for(&file_path) else {
+            continue;
+        };
+
+        let formatted = match formatter_service::format_content(
+            root_path, &file_path, &proposed, command,
+        )
+        .await
+        {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                results.push(formatter_service::formatter_result(
+                    file_path,
+                    command,
+                    Some(e),
+                ));
+                continue;
+            }
+        };
+
+        if formatted != proposed {
+            let original = read_original_text(root_path, &file_path).await?;
+            let base = hunk_base(original, is_patch, applied);
+            let new_hunks = hunks::diff_hunks(&file_path, &base, &formatted);
+            let (new_diff, new_stats) = hunks::line_diff(&base, &formatted);
+            if let ChangeOperation::Patch {
+                content,
+                hunks,
+                diff,
+                stats,
+                syntax,
+                ..
+            }
+            | ChangeOperation::Overwrite {
+                content,
+                hunks,
+                diff,
+                stats,
+                syntax,
+                ..
+            } = op
+            {
+                if let Some(syntax) = syntax {
+                    let language = syntax_check::Language::from_path(&file_path);
+                    if let Some(language) = language {
+                        syntax.errors = syntax_check::check(language, &file_path, &formatted);
+                    }
+                }
+                *content = formatted;
+                *hunks = new_hunks;
+                *diff = new_diff;
+                *stats = new_stats;
+            }
+        }
+        results.push(formatter_service::formatter_result(
+            file_path, command, None,
+        ));
+    }
+
+    Ok(FormattedChanges {
+        operations,
+        results,
+    })
+}
+
+async fn read_original_text(root_path: &Path, file_path: &str) -> Result<String> {
+    let path = root_path.join(file_path);
+    if path.exists() {
+        Ok(String::from_utf8_lossy(&fs_utils::read_file_bytes(&path).await?).to_string())
+    } else {
+        Ok(String::new())
+    }
+}
+
+/// Patch hunks are relative to the original with line endings normalized, as `apply_patch` does.
+fn hunk_base(original: String, is_patch: bool, applied_blocks: u32) -> String {
+    if is_patch && applied_blocks > 0 {
+        original.replace("\r\n", "\n")
+    } else {
+        original
+    }
+}
+
+pub fn build_continuation_prompt(markdown: &str) -> Result<String> {
+    let op = truncated_operation(markdown)?;
+    Ok(followup_prompt::build_continuation_prompt(&op))
+}
+
+/// Joins a continuation onto a truncated response; the result is parsed like any other response.
+pub fn merge_continuation(markdown: &str, continuation: &str) -> Result<String> {
+    let op = truncated_operation(markdown)?;
+    Ok(followup_prompt::merge_continuation(
+        markdown,
+        &op,
+        continuation,
+    ))
+}
+
+/// Builds a prompt asking the model to redo the blocks of `operations` that failed to apply,
+/// showing each file as it currently is on disk.
+pub async fn build_repair_prompt(
+    root_path: &Path,
+    operations: Vec<ChangeOperation>,
+    edit_format: EditFormat,
+) -> Result<RepairPrompt> {
+    let mut failures = Vec::new();
+    for op in operations {
+        let ChangeOperation::Patch {
+            file_path, blocks, ..
+        } = op
+        else {
+            continue;
+        };
+        let failed_blocks: Vec<PatchBlock> =
+            blocks.into_iter().filter(|block| !block.applied).collect();
+        if failed_blocks.is_empty() {
+            continue;
+        }
+        // The path comes from the response, so it must not reach outside the project.
+        let path = match fs_utils::resolve_relative(root_path, &file_path) {
+            Ok(path) => path,
+            Err(e) => {
+                log::warn!("Leaving {file_path} out of the repair prompt: {e}");
+                continue;
+            }
+        };
+        let current_content = if path.exists() {
+            Some(String::from_utf8_lossy(&fs_utils::read_file_bytes(&path).await?).to_string())
+        } else {
+            None
+        };
+        failures.push(followup_prompt::FailedPatch {
+            file_path,
+            current_content,
+            failed_blocks,
+        });
+    }
+
+    if failures.is_empty() {
+        return Err(anyhow!("No failed patch blocks to repair"));
+    }
+
+    let prompt = followup_prompt::build_repair_prompt(&failures, &edit_format);
+    Ok(RepairPrompt {
+        failed_blocks: failures.iter().map(|f| f.failed_blocks.len() as u32).sum(),
+        total_tokens: token_counter::count_tokens(&prompt) as u32,
+        prompt,
+    })
+}
+
+fn truncated_operation(markdown: &str) -> Result<followup_prompt::TruncatedOperation> {
+    let operations = parser::parse(markdown)?;
+    followup_prompt::find_truncated_operation(&operations)
+        .ok_or_else(|| anyhow!("Response does not end in a truncated operation"))
+}
+
+pub async fn backup_files(
+    root_path: &Path,
+    paths: Vec<PathBuf>,
+    summary: Option<ChangeSummary>,
+) -> Result<String> {
+    backup_store::create_backup(root_path, paths, summary).await
 }
 
 pub async fn revert_file_from_backup(
@@ -130,15 +608,153 @@ pub async fn revert_file_from_backup(
     backup_id: &str,
     relative_path: &Path,
 ) -> Result<()> {
-    fs_utils::revert_file_from_backup(root_path, backup_id, relative_path).await
+    backup_store::revert_file_from_backup(root_path, backup_id, relative_path).await
 }
 
 pub async fn read_file_from_backup(backup_id: &str, relative_path: &Path) -> Result<String> {
-    fs_utils::read_file_from_backup(backup_id, relative_path).await
+    backup_store::read_file_from_backup(backup_id, relative_path).await
 }
 
 pub async fn delete_backup(backup_id: &str) -> Result<()> {
-    fs_utils::delete_backup(backup_id).await
+    backup_store::delete_backup(backup_id).await
+}
+
+pub async fn list_backups(root_path: &Path) -> Result<Vec<BackupManifest>> {
+    backup_store::list_backups(root_path).await
+}
+
+pub async fn get_backup(backup_id: &str) -> Result<BackupManifest> {
+    backup_store::get_backup(backup_id).await
+}
+
+pub async fn revert_backup(backup_id: &str) -> Result<Vec<String>> {
+    backup_store::revert_backup(backup_id).await
+}
+
+/// Looks for the files a PATCH/OVERWRITE most likely meant when its target does not exist,
+/// using both the location of the SEARCH content and fuzzy similarity of the path. Only files
+/// the project's ignore settings let through are considered, and only those sharing a name or
+/// extension with a target are read.
+async fn suggest_paths_for_missing_targets(
+    root_path: &Path,
+    missing_targets: Vec<MissingTarget>,
+) -> Result<HashMap<String, Vec<PathSuggestion>>> {
+    let root_path_owned = root_path.to_owned();
+    // The UI's settings reach the backend with the project's watcher.
+    let settings = watcher_service::watched_ignore_settings(root_path).unwrap_or(IgnoreSettings {
+        respect_gitignore: true,
+        custom_ignore_patterns: String::new(),
+    });
+    let settings = project_config::ignore_settings(root_path, settings);
+
+    tokio::task::spawn_blocking(move || {
+        let root_path = &root_path_owned;
+        let mut builder = WalkBuilder::new(root_path);
+        builder
+            .hidden(false)
+            .git_ignore(settings.respect_gitignore)
+            .filter_entry(|entry| entry.file_name() != ".git");
+        if !settings.custom_ignore_patterns.is_empty() {
+            if let Err(e) = builder.add_custom_ignore_patterns(&settings.custom_ignore_patterns) {
+                log::error!("Error adding custom ignore patterns: {e}");
+            }
+        }
+
+        // Only targets with SEARCH content can be found by reading files.
+        let readable_targets: Vec<&Path> = missing_targets
+            .iter()
+            .filter(|target| !target.search_replace_blocks.is_empty())
+            .map(|target| Path::new(&target.file_path))
+            .collect();
+        let mut files_read = 0;
+        let mut candidates: HashMap<String, Vec<PathSuggestion>> = HashMap::new();
+
+        for entry in builder.build().flatten() {
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                continue;
+            }
+            let path = entry.path();
+            let Ok(relative_path) = path.strip_prefix(root_path) else {
+                continue;
+            };
+            let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+
+            let worth_reading = files_read < MAX_SUGGESTION_FILES_READ
+                && readable_targets.iter().any(|target| {
+                    target.file_name() == path.file_name()
+                        || target
+                            .extension()
+                            .is_some_and(|ext| Some(ext) == path.extension())
+                });
+            let candidate_content = worth_reading
+                .then(|| entry.metadata().ok())
+                .flatten()
+                .filter(|meta| meta.len() <= MAX_SUGGESTION_FILE_SIZE)
+                .and_then(|_| {
+                    files_read += 1;
+                    std::fs::read(path).ok()
+                })
+                .filter(|bytes| !binary_utils::is_binary_bytes(bytes))
+                .map(|bytes| String::from_utf8_lossy(&bytes).to_string());
+
+            for target in &missing_targets {
+                let mut score =
+                    file_search_service::calculate_fuzzy_score(&target.file_path, &relative_path);
+                if Path::new(&target.file_path).file_name() == path.file_name() {
+                    score += 1000;
+                }
+
+                let mut matched_blocks = 0;
+                let mut patched_content = None;
+                if let Some(content) = candidate_content.as_ref() {
+                    let mut content_str = content.clone();
+                    for block in &target.search_replace_blocks {
+                        if let Ok(new_content) =
+                            apply_patch(&content_str, &block.search, &block.replace)
+                        {
+                            content_str = new_content;
+                            matched_blocks += 1;
+                        }
+                    }
+                    if matched_blocks > 0 {
+                        patched_content = Some(content_str);
+                    }
+                }
+
+                if matched_blocks == 0 && score <= 0 {
+                    continue;
+                }
+
+                candidates
+                    .entry(target.file_path.clone())
+                    .or_default()
+                    .push(PathSuggestion {
+                        file_path: relative_path.clone(),
+                        reason: if matched_blocks > 0 {
+                            PathSuggestionReason::ContentMatch
+                        } else {
+                            PathSuggestionReason::SimilarPath
+                        },
+                        score,
+                        matched_blocks,
+                        content: patched_content,
+                    });
+            }
+        }
+
+        for suggestions in candidates.values_mut() {
+            suggestions.sort_by(|a, b| {
+                b.matched_blocks
+                    .cmp(&a.matched_blocks)
+                    .then(b.score.cmp(&a.score))
+            });
+            suggestions.truncate(MAX_PATH_SUGGESTIONS);
+        }
+
+        candidates
+    })
+    .await
+    .map_err(anyhow::Error::from)
 }
 
 fn apply_patch(content: &str, search: &str, replace: &str) -> Result<String> {
@@ -160,6 +776,10 @@ fn apply_patch(content: &str, search: &str, replace: &str) -> Result<String> {
     let content_lines: Vec<&str> = normalized_content.lines().collect();
     let content_lines_trimmed: Vec<&str> = content_lines.iter().map(|l| l.trim()).collect();
 
+    if search_lines.len() > content_lines.len() {
+        return Err(anyhow!("Search block not found"));
+    }
+
     for i in 0..=content_lines.len().saturating_sub(search_lines.len()) {
         if content_lines_trimmed[i..i + search_lines.len()] == search_lines[..] {
             // Found match at line i
@@ -0,0 +1,382 @@
+use crate::core::{backup_store, fs_utils, hunks};
+use crate::services::{git_service, review_service, verification_service};
+use crate::types::{
+    ChangeOperation, FileChangeStatus, SandboxFileDiff, SandboxInfo, SandboxKind, SandboxPromotion,
+    VerificationResult,
+};
+use anyhow::{anyhow, Result};
+use ignore::WalkBuilder;
+use once_cell::sync::Lazy;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+/// A scratch copy of a project that change sets are applied to before they reach the real tree.
+struct Sandbox {
+    root_path: PathBuf,
+    sandbox_path: PathBuf,
+    kind: SandboxKind,
+    /// Repository root and worktree location, for worktree sandboxes.
+    worktree: Option<(PathBuf, PathBuf)>,
+    /// Paths relative to the project root that applied operations have touched.
+    touched: BTreeSet<String>,
+    /// Content hash of each touched path as the sandbox had it before the first operation
+    /// touched it, which is the project's state when the sandbox was created.
+    base_hashes: HashMap<String, Option<String>>,
+    _temp_dir: TempDir,
+}
+
+impl Sandbox {
+    fn info(&self, id: &str) -> SandboxInfo {
+        SandboxInfo {
+            id: id.to_string(),
+            root_path: self.root_path.to_string_lossy().to_string(),
+            sandbox_path: self.sandbox_path.to_string_lossy().to_string(),
+            kind: self.kind.clone(),
+        }
+    }
+}
+
+static SANDBOXES: Lazy<Mutex<HashMap<String, Sandbox>>> = Lazy::new(Default::default);
+
+/// Creates a sandbox for the project at `root_path`. Gitignored directories such as
+/// `node_modules` or `target` are linked into the sandbox rather than copied, so commands run
+/// there reuse them and anything they build lands in the real directories.
+pub async fn create_sandbox(root_path: &Path, kind: SandboxKind) -> Result<SandboxInfo> {
+    let root_path = root_path.to_owned();
+    let sandbox = tokio::task::spawn_blocking(move || -> Result<Sandbox> {
+        let temp_dir = tempfile::Builder::new()
+            .prefix("repo-wizard-sandbox-")
+            .tempdir()?;
+        let tree_path = temp_dir.path().join("tree");
+
+        let (sandbox_path, worktree) = match kind {
+            SandboxKind::Worktree => {
+                let repo_root = git_service::get_repo_root(&root_path)?;
+                let prefix = std::fs::canonicalize(&root_path)?
+                    .strip_prefix(std::fs::canonicalize(&repo_root)?)?
+                    .to_path_buf();
+                git_service::add_worktree(&repo_root, &tree_path)?;
+                // The worktree starts at HEAD; bring over uncommitted and untracked state.
+                for relative_path in git_service::get_dirty_paths(&repo_root)? {
+                    sync_path(&repo_root, &tree_path, &relative_path)?;
+                }
+                (tree_path.join(prefix), Some((repo_root, tree_path)))
+            }
+            SandboxKind::Copy => {
+                copy_project(&root_path, &tree_path)?;
+                (tree_path, None)
+            }
+        };
+        link_ignored_dirs(&root_path, &sandbox_path)?;
+
+        Ok(Sandbox {
+            root_path,
+            sandbox_path,
+            kind,
+            worktree,
+            touched: BTreeSet::new(),
+            base_hashes: HashMap::new(),
+            _temp_dir: temp_dir,
+        })
+    })
+    .await??;
+
+    let id = Uuid::new_v4().to_string();
+    let info = sandbox.info(&id);
+    SANDBOXES.lock().unwrap().insert(id, sandbox);
+    Ok(info)
+}
+
+pub async fn apply_to_sandbox(id: &str, operations: Vec<ChangeOperation>) -> Result<()> {
+    for op in &operations {
+        review_service::ensure_complete(op)?;
+    }
+    let (sandbox_path, mut base_hashes) = with_sandbox(id, |sandbox| {
+        (sandbox.sandbox_path.clone(), sandbox.base_hashes.clone())
+    })?;
+
+    let mut touched = Vec::new();
+    for op in operations {
+        let paths = match &op {
+            ChangeOperation::Move {
+                from_path, to_path, ..
+            } => vec![from_path.clone(), to_path.clone()],
+            ChangeOperation::Patch { file_path, .. }
+            | ChangeOperation::Overwrite { file_path, .. }
+            | ChangeOperation::Delete { file_path, .. } => vec![file_path.clone()],
+        };
+        for path in paths {
+            if let Entry::Vacant(entry) = base_hashes.entry(path) {
+                let hash = backup_store::current_hash(&sandbox_path, entry.key()).await?;
+                entry.insert(hash);
+            }
+        }
+        match op {
+            ChangeOperation::Patch {
+                file_path, content, ..
+            }
+            | ChangeOperation::Overwrite {
+                file_path, content, ..
+            } => {
+                fs_utils::write_file_content(
+                    &sandbox_path.join(&file_path),
+                    &content,
+                    &sandbox_path,
+                )
+                .await?;
+                touched.push(file_path);
+            }
+            ChangeOperation::Delete { file_path, .. } => {
+                let path = sandbox_path.join(&file_path);
+                if path.exists() {
+                    fs_utils::delete_file(&path, &sandbox_path).await?;
+                }
+                touched.push(file_path);
+            }
+            ChangeOperation::Move {
+                from_path, to_path, ..
+            } => {
+                fs_utils::move_file(
+                    &sandbox_path.join(&from_path),
+                    &sandbox_path.join(&to_path),
+                    &sandbox_path,
+                )
+                .await?;
+                touched.push(from_path);
+                touched.push(to_path);
+            }
+        }
+    }
+
+    with_sandbox(id, |sandbox| {
+        sandbox.touched.extend(touched);
+        sandbox.base_hashes = base_hashes;
+    })
+}
+
+pub async fn run_in_sandbox(
+    id: &str,
+    command: &str,
+    timeout_secs: u32,
+) -> Result<VerificationResult> {
+    let (sandbox_path, touched) = with_sandbox(id, |sandbox| {
+        (
+            sandbox.sandbox_path.clone(),
+            sandbox.touched.iter().cloned().collect(),
+        )
+    })?;
+    verification_service::run_verification(&sandbox_path, command, timeout_secs, touched).await
+}
+
+/// Differences between the real project and the sandbox for every path the sandbox touched,
+/// including edits made there by formatters or commands.
+pub async fn diff_sandbox(id: &str) -> Result<Vec<SandboxFileDiff>> {
+    let (root_path, sandbox_path, touched) = with_sandbox(id, |sandbox| {
+        (
+            sandbox.root_path.clone(),
+            sandbox.sandbox_path.clone(),
+            sandbox.touched.clone(),
+        )
+    })?;
+
+    let mut diffs = Vec::new();
+    for file_path in touched {
+        let original = read_optional(&root_path.join(&file_path)).await?;
+        let proposed = read_optional(&sandbox_path.join(&file_path)).await?;
+        let status = match (&original, &proposed) {
+            (None, Some(_)) => FileChangeStatus::Added,
+            (Some(_), None) => FileChangeStatus::Deleted,
+            (Some(a), Some(b)) if a != b => FileChangeStatus::Modified,
+            _ => continue,
+        };
+        let (diff, stats) = hunks::line_diff(
+            original.as_deref().unwrap_or_default(),
+            proposed.as_deref().unwrap_or_default(),
+        );
+        diffs.push(SandboxFileDiff {
+            file_path,
+            status,
+            diff,
+            stats,
+        });
+    }
+    Ok(diffs)
+}
+
+/// Copies the sandbox state of every touched path into the real project, byte for byte, after
+/// backing those paths up, then discards the sandbox. Nothing is promoted if any of those paths
+/// changed in the project since the sandbox was created. A failure to discard is reported as a
+/// warning, since the project has already been changed by then.
+pub async fn promote_sandbox(id: &str) -> Result<SandboxPromotion> {
+    let changes = diff_sandbox(id).await?;
+    let (root_path, sandbox_path, base_hashes) = with_sandbox(id, |sandbox| {
+        (
+            sandbox.root_path.clone(),
+            sandbox.sandbox_path.clone(),
+            sandbox.base_hashes.clone(),
+        )
+    })?;
+
+    let mut conflicts = Vec::new();
+    for change in &changes {
+        let Some(base_hash) = base_hashes.get(&change.file_path) else {
+            continue;
+        };
+        if backup_store::current_hash(&root_path, &change.file_path).await? != *base_hash {
+            conflicts.push(change.file_path.as_str());
+        }
+    }
+    if !conflicts.is_empty() {
+        return Err(anyhow!(
+            "These files changed in the project since the sandbox was created: {}. Discard the sandbox and create a new one.",
+            conflicts.join(", ")
+        ));
+    }
+
+    let backup_id = backup_store::create_backup(
+        &root_path,
+        changes
+            .iter()
+            .map(|change| PathBuf::from(&change.file_path))
+            .collect(),
+        None,
+    )
+    .await?;
+
+    let mut promoted = Vec::new();
+    for change in changes {
+        let target = root_path.join(&change.file_path);
+        match change.status {
+            FileChangeStatus::Added | FileChangeStatus::Modified => {
+                let bytes =
+                    fs_utils::read_file_bytes(&sandbox_path.join(&change.file_path)).await?;
+                fs_utils::write_file_bytes(&target, bytes, &root_path).await?;
+            }
+            FileChangeStatus::Deleted => fs_utils::delete_file(&target, &root_path).await?,
+        }
+        promoted.push(change.file_path);
+    }
+
+    let warning = discard_sandbox(id).err().map(|e| {
+        log::warn!("Promoted sandbox {id} but failed to discard it: {e}");
+        format!("The sandbox could not be removed: {e}")
+    });
+    Ok(SandboxPromotion {
+        promoted,
+        backup_id,
+        warning,
+    })
+}
+
+/// Removes a sandbox. One whose worktree cannot be removed stays registered, so discarding it
+/// can be retried instead of leaving the worktree behind.
+pub fn discard_sandbox(id: &str) -> Result<()> {
+    let worktree = with_sandbox(id, |sandbox| sandbox.worktree.clone())?;
+    if let Some((repo_root, worktree_path)) = worktree {
+        git_service::remove_worktree(&repo_root, &worktree_path)?;
+    }
+    SANDBOXES.lock().unwrap().remove(id);
+    Ok(())
+}
+
+fn with_sandbox<T>(id: &str, f: impl FnOnce(&mut Sandbox) -> T) -> Result<T> {
+    let mut sandboxes = SANDBOXES.lock().unwrap();
+    let sandbox = sandboxes
+        .get_mut(id)
+        .ok_or_else(|| anyhow!("Sandbox not found: {}", id))?;
+    Ok(f(sandbox))
+}
+
+async fn read_optional(path: &Path) -> Result<Option<String>> {
+    if path.is_file() {
+        Ok(Some(fs_utils::read_file_content(path).await?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Walks the entries of a project that are not gitignored.
+fn project_walker(root_path: &Path) -> ignore::Walk {
+    WalkBuilder::new(root_path)
+        .hidden(false)
+        .git_ignore(true)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build()
+}
+
+/// Copies the files of a project that are not gitignored.
+fn copy_project(root_path: &Path, destination: &Path) -> Result<()> {
+    std::fs::create_dir_all(destination)?;
+    for entry in project_walker(root_path) {
+        let entry = entry?;
+        let Ok(relative_path) = entry.path().strip_prefix(root_path) else {
+            continue;
+        };
+        let target = destination.join(relative_path);
+        if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+            std::fs::create_dir_all(&target)?;
+        } else if entry.file_type().is_some_and(|ft| ft.is_file()) {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Links each top-most gitignored directory of the project into `destination`, where the
+/// sandbox has nothing yet. Writes through these links are refused by the sandbox's own path
+/// checks, since they resolve outside of it.
+fn link_ignored_dirs(root_path: &Path, destination: &Path) -> Result<()> {
+    let mut walked_dirs = HashSet::new();
+    for entry in project_walker(root_path) {
+        let entry = entry?;
+        if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+            walked_dirs.insert(entry.into_path());
+        }
+    }
+
+    for dir in &walked_dirs {
+        for child in std::fs::read_dir(dir)? {
+            let child = child?;
+            let child_path = child.path();
+            if !child.file_type()?.is_dir()
+                || child.file_name() == ".git"
+                || walked_dirs.contains(&child_path)
+            {
+                continue;
+            }
+            let Ok(relative_path) = child_path.strip_prefix(root_path) else {
+                continue;
+            };
+            let target = destination.join(relative_path);
+            if target.symlink_metadata().is_ok() {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&child_path, &target)?;
+            #[cfg(windows)]
+            std::os::windows::fs::symlink_dir(&child_path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn sync_path(from_root: &Path, to_root: &Path, relative_path: &str) -> Result<()> {
+    let source = from_root.join(relative_path);
+    let target = to_root.join(relative_path);
+    if source.is_file() {
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&source, &target)?;
+    } else if !source.exists() && target.is_file() {
+        std::fs::remove_file(&target)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use crate::core::{backup_store, fs_utils, path_utils};
+use crate::types::{
+    ChangeOperation, OperationStatus, ReviewSession, ReviewSessionInfo, SessionOperation,
+};
+use anyhow::{anyhow, Result};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs;
+use uuid::Uuid;
+
+const SESSIONS_DIR: &str = "review-sessions";
+
+/// Starts a session for a freshly parsed response, with every operation pending.
+pub async fn create_session(
+    data_dir: &Path,
+    root_path: &str,
+    raw_response: String,
+    operations: Vec<ChangeOperation>,
+    backup_id: Option<String>,
+) -> Result<ReviewSession> {
+    let now = now_millis();
+    let session = ReviewSession {
+        id: Uuid::new_v4().to_string(),
+        root_path: root_path.to_string(),
+        raw_response,
+        operations: operations
+            .into_iter()
+            .map(|operation| SessionOperation {
+                operation,
+                status: OperationStatus::Pending,
+                error: None,
+                backup_id: None,
+            })
+            .collect(),
+        backup_id,
+        created_at: now,
+        updated_at: now,
+    };
+    write_session(data_dir, &session).await?;
+    Ok(session)
+}
+
+/// Persists the current state of an existing session, e.g. after an operation was applied.
+pub async fn save_session(data_dir: &Path, mut session: ReviewSession) -> Result<ReviewSession> {
+    let path = session_path(data_dir, &session.root_path, &session.id)?;
+    if !path.exists() {
+        return Err(anyhow!("Review session not found: {}", session.id));
+    }
+    session.updated_at = now_millis();
+    write_session(data_dir, &session).await?;
+    Ok(session)
+}
+
+/// Sessions of a project, most recently updated first. Unreadable session files are skipped.
+pub async fn list_sessions(data_dir: &Path, root_path: &str) -> Result<Vec<ReviewSessionInfo>> {
+    let dir = project_dir(data_dir, root_path);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut sessions = Vec::new();
+    let mut entries = fs::read_dir(&dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Ok(session) = read_session_file(&path).await else {
+            log::warn!("Skipping unreadable review session {}", path.display());
+            continue;
+        };
+        sessions.push(session_info(&session));
+    }
+    sessions.sort_by(|a, b| b.updated_at.total_cmp(&a.updated_at));
+    Ok(sessions)
+}
+
+pub async fn resume_session(
+    data_dir: &Path,
+    root_path: &str,
+    session_id: &str,
+) -> Result<ReviewSession> {
+    let path = session_path(data_dir, root_path, session_id)?;
+    if !path.exists() {
+        return Err(anyhow!("Review session not found: {}", session_id));
+    }
+    read_session_file(&path).await
+}
+
+/// Deletes a session together with its linked backups.
+pub async fn discard_session(data_dir: &Path, root_path: &str, session_id: &str) -> Result<()> {
+    let path = session_path(data_dir, root_path, session_id)?;
+    if !path.exists() {
+        return Ok(());
+    }
+    if let Ok(session) = read_session_file(&path).await {
+        let backup_ids: BTreeSet<&String> = session
+            .backup_id
+            .iter()
+            .chain(session.operations.iter().flat_map(|op| &op.backup_id))
+            .collect();
+        for backup_id in backup_ids {
+            backup_store::delete_backup(backup_id).await?;
+        }
+    }
+    fs::remove_file(&path).await?;
+    Ok(())
+}
+
+fn session_info(session: &ReviewSession) -> ReviewSessionInfo {
+    let count = |status: OperationStatus| {
+        session
+            .operations
+            .iter()
+            .filter(|op| op.status == status)
+            .count() as u32
+    };
+    ReviewSessionInfo {
+        id: session.id.clone(),
+        root_path: session.root_path.clone(),
+        backup_id: session.backup_id.clone(),
+        created_at: session.created_at,
+        updated_at: session.updated_at,
+        pending_count: count(OperationStatus::Pending),
+        applied_count: count(OperationStatus::Applied),
+    }
+}
+
+async fn write_session(data_dir: &Path, session: &ReviewSession) -> Result<()> {
+    let path = session_path(data_dir, &session.root_path, &session.id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    fs_utils::write_atomic(&path, serde_json::to_vec_pretty(session)?).await
+}
+
+async fn read_session_file(path: &Path) -> Result<ReviewSession> {
+    let bytes = fs::read(path).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Sessions are grouped per project under a directory named after a hash of the root path.
+fn project_dir(data_dir: &Path, root_path: &str) -> PathBuf {
+    data_dir
+        .join(SESSIONS_DIR)
+        .join(path_utils::project_key(root_path))
+}
+
+fn session_path(data_dir: &Path, root_path: &str, session_id: &str) -> Result<PathBuf> {
+    Uuid::parse_str(session_id).map_err(|_| anyhow!("Invalid session id: {}", session_id))?;
+    Ok(project_dir(data_dir, root_path).join(format!("{session_id}.json")))
+}
+
+fn now_millis() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as f64)
+        .unwrap_or_default()
+}
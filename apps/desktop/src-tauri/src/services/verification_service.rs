@@ -0,0 +1,45 @@
+use crate::core::{diagnostics, followup_prompt, shell};
+use crate::types::VerificationResult;
+use anyhow::Result;
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+/// Runs a check command in the project root after changes were applied, linking any parsed
+/// diagnostics to `changed_files` (paths relative to the root).
+pub async fn run_verification(
+    root_path: &Path,
+    command: &str,
+    timeout_secs: u32,
+    changed_files: Vec<String>,
+) -> Result<VerificationResult> {
+    let output = shell::run(
+        command,
+        root_path,
+        Duration::from_secs(u64::from(timeout_secs)),
+    )
+    .await?;
+
+    let changed: HashSet<String> = changed_files
+        .into_iter()
+        .map(|path| path.replace('\\', "/"))
+        .collect();
+    let mut diagnostics =
+        diagnostics::parse_diagnostics(&format!("{}\n{}", output.stdout, output.stderr), root_path);
+    for diagnostic in diagnostics.iter_mut() {
+        diagnostic.in_changed_file = changed.contains(&diagnostic.file_path);
+    }
+    diagnostics.sort_by_key(|diagnostic| !diagnostic.in_changed_file);
+
+    let context =
+        followup_prompt::build_verification_context(command, &output, timeout_secs, &diagnostics);
+    Ok(VerificationResult {
+        command: command.to_string(),
+        exit_code: output.exit_code,
+        timed_out: output.timed_out,
+        stdout: output.stdout,
+        stderr: output.stderr,
+        diagnostics,
+        context,
+    })
+}
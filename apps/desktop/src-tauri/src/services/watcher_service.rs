@@ -1,102 +1,359 @@
-use crate::types::IgnoreSettings;
-use anyhow::Result;
-use ignore::WalkBuilder;
+use crate::core::ignore_matcher::{self, IgnoreMatcher};
+use crate::core::{project_config, review_targets};
+use crate::types::{
+    FsChangeEvent, IgnoreSettings, ProjectConfigChangedEvent, RenamedPath,
+    ReviewTargetChangedEvent, WatchLimitExceeded, WatcherInfo, WatcherStatus,
+};
+use anyhow::{bail, Result};
 #[cfg(target_os = "linux")]
 use notify_debouncer_full::FileIdMap;
 #[cfg(not(target_os = "linux"))]
 use notify_debouncer_full::NoCache;
 use notify_debouncer_full::{
     new_debouncer_opt,
-    notify::{Config, RecommendedWatcher, RecursiveMode},
+    notify::{
+        self,
+        event::{ModifyKind, RenameMode},
+        Config, Event, EventKind, RecommendedWatcher, RecursiveMode,
+    },
     DebounceEventResult,
 };
 
 use once_cell::sync::Lazy;
-use std::{collections::HashMap, path::Path, sync::Mutex, time::Duration};
-use tauri::Emitter;
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tauri_specta::Event as _;
+use thiserror::Error;
+
+/// Delay before the first automatic restart of a failed watcher, doubled for each further one.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTARTS: u32 = 5;
+
+#[derive(Debug, Error)]
+#[error(
+    "Ran out of file watches while watching {}. Raise the system limit{} or ignore large \
+     folders, then reopen the project.",
+    .0.root_path,
+    .0.max_user_watches.map(|n| format!(" (currently {n})")).unwrap_or_default()
+)]
+pub struct WatchLimitError(pub WatchLimitExceeded);
 
 type WatcherType = Box<dyn std::any::Any + Send + Sync>;
-type WatcherMap = Mutex<HashMap<String, WatcherType>>;
-static WATCHERS: Lazy<WatcherMap> = Lazy::new(Default::default);
 
+/// What the event handler needs, shared with the registry so settings can change in place.
+struct WatchState {
+    settings: IgnoreSettings,
+    matcher: IgnoreMatcher,
+    status: WatcherStatus,
+    last_error: Option<String>,
+    restarts: u32,
+    started_at: f64,
+    last_event_at: Option<f64>,
+}
+
+impl WatchState {
+    fn new(settings: IgnoreSettings, matcher: IgnoreMatcher) -> Self {
+        Self {
+            matcher,
+            settings,
+            status: WatcherStatus::Running,
+            last_error: None,
+            restarts: 0,
+            started_at: now_millis(),
+            last_event_at: None,
+        }
+    }
+
+    fn record_error(&mut self, error: &anyhow::Error) {
+        self.status = if error.is::<WatchLimitError>() {
+            WatcherStatus::LimitReached
+        } else {
+            WatcherStatus::Failed
+        };
+        self.last_error = Some(error.to_string());
+    }
+}
+
+struct Watcher {
+    /// `None` while the watcher waits to be restarted.
+    debouncer: Option<WatcherType>,
+    state: Arc<Mutex<WatchState>>,
+    window_labels: BTreeSet<String>,
+}
+
+static WATCHERS: Lazy<Mutex<HashMap<String, Watcher>>> = Lazy::new(Default::default);
+
+/// Watches `root_path` on behalf of a window. If the project is already watched, the window is
+/// added to its users and `settings` replace the current ones; a watcher that has given up is
+/// started again. A watcher that fails to start is still registered, so that it can be
+/// restarted.
 pub fn start_watching(
     app_handle: tauri::AppHandle,
+    window_label: &str,
     root_path: &Path,
     settings: IgnoreSettings,
 ) -> Result<()> {
+    // Compiling walks the project, which must not hold up the other watchers.
+    let matcher = compile_matcher(root_path, &settings);
     let mut watchers = WATCHERS.lock().unwrap();
 
     let path_str = root_path.to_string_lossy().to_string();
-    if watchers.contains_key(&path_str) {
-        return Ok(());
+    if let Some(watcher) = watchers.get_mut(&path_str) {
+        watcher.window_labels.insert(window_label.to_string());
+        let mut state = watcher.state.lock().unwrap();
+        state.settings = settings.clone();
+        if !matches!(
+            state.status,
+            WatcherStatus::LimitReached | WatcherStatus::Failed
+        ) {
+            state.matcher = matcher;
+            return Ok(());
+        }
+        state.restarts = 0;
+        drop(state);
+        return reopen(&app_handle, root_path, watcher, (settings, matcher));
     }
 
-    let event_handler_app_handle = app_handle.clone();
-    let event_handler_path_str = path_str.clone();
-    let event_handler_settings = settings.clone();
+    let state = Arc::new(Mutex::new(WatchState::new(settings, matcher)));
+    let (debouncer, result) = match watch(app_handle, root_path, state.clone()) {
+        Ok(debouncer) => (Some(debouncer), Ok(())),
+        Err(e) => {
+            state.lock().unwrap().record_error(&e);
+            (None, Err(e))
+        }
+    };
+    watchers.insert(
+        path_str,
+        Watcher {
+            debouncer,
+            state,
+            window_labels: BTreeSet::from([window_label.to_string()]),
+        },
+    );
+
+    result
+}
+
+/// Restarts the watcher of `root_path` now, resetting its restart count.
+pub fn restart_watcher(app_handle: tauri::AppHandle, root_path: &Path) -> Result<()> {
+    let Some(rules) = recompile_rules(root_path) else {
+        bail!("{} is not being watched", root_path.display());
+    };
+    let mut watchers = WATCHERS.lock().unwrap();
+    let Some(watcher) = watchers.get_mut(root_path.to_string_lossy().as_ref()) else {
+        bail!("{} is not being watched", root_path.display());
+    };
+    watcher.state.lock().unwrap().restarts = 0;
+    reopen(&app_handle, root_path, watcher, rules)
+}
+
+/// The active watchers, ordered by project.
+pub fn list_watchers() -> Vec<WatcherInfo> {
+    let watchers = WATCHERS.lock().unwrap();
+    let mut infos: Vec<WatcherInfo> = watchers
+        .iter()
+        .map(|(root_path, watcher)| {
+            let state = watcher.state.lock().unwrap();
+            WatcherInfo {
+                root_path: root_path.clone(),
+                window_labels: watcher.window_labels.iter().cloned().collect(),
+                settings: state.settings.clone(),
+                status: state.status.clone(),
+                last_error: state.last_error.clone(),
+                restarts: state.restarts,
+                started_at: state.started_at,
+                last_event_at: state.last_event_at,
+            }
+        })
+        .collect();
+    infos.sort_by(|a, b| a.root_path.cmp(&b.root_path));
+    infos
+}
+
+/// The ignore settings the UI last gave the watcher of `root_path`, if it is watched.
+pub fn watched_ignore_settings(root_path: &Path) -> Option<IgnoreSettings> {
+    let watchers = WATCHERS.lock().unwrap();
+    let watcher = watchers.get(root_path.to_string_lossy().as_ref())?;
+    let settings = watcher.state.lock().unwrap().settings.clone();
+    Some(settings)
+}
+
+/// The watcher's settings with a matcher freshly compiled from them, or `None` if `root_path`
+/// is not watched. The registry is only locked to read the settings.
+fn recompile_rules(root_path: &Path) -> Option<(IgnoreSettings, IgnoreMatcher)> {
+    let settings = watched_ignore_settings(root_path)?;
+    let matcher = compile_matcher(root_path, &settings);
+    Some((settings, matcher))
+}
+
+/// Replaces the watcher's debouncer with a new one. Events in between are lost, so the matcher
+/// is replaced by `rules`, compiled beforehand in case ignore files changed meanwhile.
+fn reopen(
+    app_handle: &tauri::AppHandle,
+    root_path: &Path,
+    watcher: &mut Watcher,
+    (settings, matcher): (IgnoreSettings, IgnoreMatcher),
+) -> Result<()> {
+    // Release the old watches first; they count towards the same limit.
+    watcher.debouncer = None;
+    match watch(app_handle.clone(), root_path, watcher.state.clone()) {
+        Ok(debouncer) => {
+            watcher.debouncer = Some(debouncer);
+            let mut state = watcher.state.lock().unwrap();
+            // Settings that changed since are compiled into the matcher already.
+            if state.settings == settings {
+                state.matcher = matcher;
+            }
+            state.status = WatcherStatus::Running;
+            Ok(())
+        }
+        Err(e) => {
+            watcher.state.lock().unwrap().record_error(&e);
+            Err(e)
+        }
+    }
+}
+
+/// Restarts the watcher after a delay, unless it has been restarted too often already.
+fn schedule_restart(app_handle: tauri::AppHandle, root_path: PathBuf, state: &mut WatchState) {
+    if state.restarts >= MAX_RESTARTS {
+        log::error!(
+            "Giving up on watching {} after {MAX_RESTARTS} restarts",
+            root_path.display()
+        );
+        return;
+    }
+    let delay = RESTART_DELAY * 2u32.pow(state.restarts);
+    state.restarts += 1;
+    state.status = WatcherStatus::Restarting;
+
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        // The watcher may have been stopped or restarted by hand in the meantime.
+        let Some(rules) = recompile_rules(&root_path) else {
+            return;
+        };
+        let mut watchers = WATCHERS.lock().unwrap();
+        let Some(watcher) = watchers.get_mut(root_path.to_string_lossy().as_ref()) else {
+            return;
+        };
+        if watcher.state.lock().unwrap().status != WatcherStatus::Restarting {
+            return;
+        }
+        match reopen(&app_handle, &root_path, watcher, rules) {
+            Ok(()) => log::info!("Restarted watcher for {}", root_path.display()),
+            Err(e) => {
+                log::error!("Failed to restart watcher for {}: {e}", root_path.display());
+                let state = watcher.state.clone();
+                schedule_restart(app_handle, root_path, &mut state.lock().unwrap());
+            }
+        }
+    });
+}
+
+fn watch(
+    app_handle: tauri::AppHandle,
+    root_path: &Path,
+    state: Arc<Mutex<WatchState>>,
+) -> Result<WatcherType> {
+    let event_handler_path_str = root_path.to_string_lossy().to_string();
     let event_handler_root_path = root_path.to_path_buf();
+    let config_path = project_config::config_path(root_path);
 
     let event_handler = move |result: DebounceEventResult| match result {
         Ok(events) => {
-            let mut has_unignored_change = false;
-
-            'events: for event in &events {
-                for p in &event.paths {
-                    let candidate = if p.exists() {
-                        p.to_path_buf()
-                    } else {
-                        p.parent()
-                            .map(|parent| parent.to_path_buf())
-                            .unwrap_or_else(|| event_handler_root_path.clone())
-                    };
-
-                    let mut builder = WalkBuilder::new(&event_handler_root_path);
-                    builder.add(&candidate);
-                    builder
-                        .hidden(false)
-                        .git_ignore(event_handler_settings.respect_gitignore)
-                        .max_depth(Some(0));
-
-                    if !event_handler_settings.custom_ignore_patterns.is_empty() {
-                        if let Err(e) = builder.add_custom_ignore_patterns(
-                            &event_handler_settings.custom_ignore_patterns,
-                        ) {
-                            log::error!("Failed to apply custom ignore patterns: {e}");
-                        }
-                    }
+            state.lock().unwrap().last_event_at = Some(now_millis());
 
-                    let mut path_is_unignored = false;
-                    for entry in builder.build().flatten() {
-                        if entry.path() == candidate {
-                            path_is_unignored = true;
-                            break;
-                        }
-                    }
+            // The project's config file is picked up even when it is ignored.
+            let config_changed = events
+                .iter()
+                .any(|event| event.paths.iter().any(|p| p == &config_path));
+            if config_changed {
+                project_config::load(&event_handler_root_path);
+                let event = ProjectConfigChangedEvent {
+                    root_path: event_handler_path_str.clone(),
+                };
+                if let Err(e) = event.emit(&app_handle) {
+                    log::error!("Failed to emit project-config-changed-event: {e}");
+                }
+            }
+            // The matcher is only rebuilt when the rules it was compiled from may have changed,
+            // and without holding the state, which other threads wait on.
+            let settings = {
+                let state = state.lock().unwrap();
+                let rules_changed = config_changed
+                    || events
+                        .iter()
+                        .any(|event| event.paths.iter().any(|p| changes_rules(&state.matcher, p)));
+                rules_changed.then(|| state.settings.clone())
+            };
+            if let Some(settings) = settings {
+                let matcher = compile_matcher(&event_handler_root_path, &settings);
+                let mut state = state.lock().unwrap();
+                // Settings that changed since are compiled into the matcher already.
+                if state.settings == settings {
+                    state.matcher = matcher;
+                }
+            }
 
-                    if path_is_unignored {
-                        has_unignored_change = true;
-                        break 'events;
-                    }
+            let state = state.lock().unwrap();
+            let change = collect_changes(
+                &event_handler_path_str,
+                events.iter().map(|event| &event.event),
+                |path| is_unignored(&state.matcher, path),
+            );
+            drop(state);
+            let is_empty = change.created.is_empty()
+                && change.modified.is_empty()
+                && change.removed.is_empty()
+                && change.renamed.is_empty();
+            if !is_empty {
+                if let Err(e) = change.emit(&app_handle) {
+                    log::error!("Failed to emit fs-change-event: {e}");
                 }
             }
 
-            if has_unignored_change {
-                if let Err(e) = event_handler_app_handle
-                    .emit("file-change-event", event_handler_path_str.clone())
-                {
-                    log::error!("Failed to emit file-change-event: {e}");
+            // Files under review are checked whether or not they are ignored.
+            let changes = review_targets::changed(
+                &event_handler_root_path,
+                events
+                    .iter()
+                    .flat_map(|event| event.paths.iter().map(PathBuf::as_path)),
+            );
+            if !changes.is_empty() {
+                let event = ReviewTargetChangedEvent {
+                    root_path: event_handler_path_str.clone(),
+                    changes,
+                };
+                if let Err(e) = event.emit(&app_handle) {
+                    log::error!("Failed to emit review-target-changed-event: {e}");
                 }
             }
         }
         Err(errors) => {
-            for error in errors {
+            for error in &errors {
                 log::error!("File watch error: {error:?}");
             }
+            let Some(error) = errors.into_iter().last() else {
+                return;
+            };
+            let mut state = state.lock().unwrap();
+            if state.status == WatcherStatus::Restarting {
+                return;
+            }
+            state.record_error(&watch_error(&event_handler_root_path, error));
+            schedule_restart(
+                app_handle.clone(),
+                event_handler_root_path.clone(),
+                &mut state,
+            );
         }
     };
 
-    let path_to_watch = root_path.to_path_buf();
-
     #[cfg(target_os = "linux")]
     let cache = FileIdMap::new();
     #[cfg(not(target_os = "linux"))]
@@ -110,14 +367,289 @@ pub fn start_watching(
         Config::default(),
     )?;
 
-    debouncer.watch(&path_to_watch, RecursiveMode::Recursive)?;
+    debouncer
+        .watch(root_path, RecursiveMode::Recursive)
+        .map_err(|e| watch_error(root_path, e))?;
+
+    Ok(Box::new(debouncer))
+}
 
-    watchers.insert(path_str, Box::new(debouncer));
+fn compile_matcher(root_path: &Path, settings: &IgnoreSettings) -> IgnoreMatcher {
+    IgnoreMatcher::new(
+        root_path,
+        &project_config::ignore_settings(root_path, settings.clone()),
+    )
+}
 
-    Ok(())
+/// Turns running out of watches into a [`WatchLimitError`].
+fn watch_error(root_path: &Path, error: notify::Error) -> anyhow::Error {
+    if matches!(error.kind, notify::ErrorKind::MaxFilesWatch) {
+        let max_user_watches = std::fs::read_to_string("/proc/sys/fs/inotify/max_user_watches")
+            .ok()
+            .and_then(|limit| limit.trim().parse().ok());
+        WatchLimitError(WatchLimitExceeded {
+            root_path: root_path.to_string_lossy().to_string(),
+            max_user_watches,
+        })
+        .into()
+    } else {
+        error.into()
+    }
 }
 
-pub fn stop_watching(root_path: &Path) {
+fn now_millis() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as f64)
+        .unwrap_or_default()
+}
+
+/// Whether a change at `path` may change what `matcher` ignores. Ignore files in ignored
+/// directories never take effect, so changes to them, say in `node_modules`, don't count.
+fn changes_rules(matcher: &IgnoreMatcher, path: &Path) -> bool {
+    ignore_matcher::is_ignore_file(path)
+        && (path.ends_with(".git/info/exclude")
+            || path
+                .parent()
+                .is_none_or(|dir| !matcher.is_ignored(dir, true)))
+}
+
+/// Whether a change at `path` should be reported. A path that no longer exists may have been
+/// a file or a directory, so it is left out if either would be ignored.
+fn is_unignored(matcher: &IgnoreMatcher, path: &Path) -> bool {
+    if path.exists() {
+        !matcher.is_ignored(path, path.is_dir())
+    } else {
+        !matcher.is_ignored(path, false) && !matcher.is_ignored(path, true)
+    }
+}
+
+/// Sorts a batch of debounced events into created, modified, removed and renamed paths,
+/// keeping only those `include` accepts. A path created and removed within the batch is left
+/// out; one removed and created again is reported as modified.
+fn collect_changes<'a>(
+    root_path: &str,
+    events: impl IntoIterator<Item = &'a Event>,
+    include: impl Fn(&Path) -> bool,
+) -> FsChangeEvent {
+    let mut created = BTreeSet::new();
+    let mut modified = BTreeSet::new();
+    let mut removed = BTreeSet::new();
+    let mut renamed = Vec::new();
+
+    for event in events {
+        match (&event.kind, event.paths.as_slice()) {
+            (EventKind::Modify(ModifyKind::Name(RenameMode::Both)), [from, to]) => {
+                match (include(from), include(to)) {
+                    (true, true) => renamed.push(RenamedPath {
+                        from: path_string(from),
+                        to: path_string(to),
+                    }),
+                    (true, false) => {
+                        removed.insert(from.clone());
+                    }
+                    (false, true) => {
+                        created.insert(to.clone());
+                    }
+                    (false, false) => {}
+                }
+            }
+            (EventKind::Modify(ModifyKind::Name(RenameMode::From)), paths)
+            | (EventKind::Remove(_), paths) => {
+                removed.extend(paths.iter().filter(|p| include(p)).cloned());
+            }
+            (EventKind::Modify(ModifyKind::Name(RenameMode::To)), paths)
+            | (EventKind::Create(_), paths) => {
+                created.extend(paths.iter().filter(|p| include(p)).cloned());
+            }
+            (EventKind::Modify(_), paths) | (EventKind::Any, paths) => {
+                modified.extend(paths.iter().filter(|p| include(p)).cloned());
+            }
+            (EventKind::Access(_), _) | (EventKind::Other, _) => {}
+        }
+    }
+
+    let recreated: Vec<PathBuf> = created.intersection(&removed).cloned().collect();
+    for path in recreated {
+        created.remove(&path);
+        removed.remove(&path);
+        if path.exists() {
+            modified.insert(path);
+        }
+    }
+    modified.retain(|path| !created.contains(path) && !removed.contains(path));
+
+    let to_strings = |paths: BTreeSet<PathBuf>| paths.iter().map(|p| path_string(p)).collect();
+    FsChangeEvent {
+        root_path: root_path.to_string(),
+        created: to_strings(created),
+        modified: to_strings(modified),
+        removed: to_strings(removed),
+        renamed,
+    }
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Stops watching `root_path` for a window. The watcher itself stops once no window uses it.
+pub fn stop_watching(window_label: &str, root_path: &Path) {
+    let mut watchers = WATCHERS.lock().unwrap();
+    release(
+        &mut watchers,
+        window_label,
+        Some(root_path.to_string_lossy().as_ref()),
+    );
+}
+
+/// Stops watching every project for a window that has closed.
+pub fn stop_watchers_for_window(window_label: &str) {
     let mut watchers = WATCHERS.lock().unwrap();
-    if let Some(_debouncer) = watchers.remove(root_path.to_string_lossy().as_ref()) {}
+    release(&mut watchers, window_label, None);
+}
+
+fn release(watchers: &mut HashMap<String, Watcher>, window_label: &str, root_path: Option<&str>) {
+    watchers.retain(|path, watcher| {
+        if root_path.is_none_or(|root_path| root_path == path) {
+            watcher.window_labels.remove(window_label);
+        }
+        !watcher.window_labels.is_empty()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify_debouncer_full::notify::event::{CreateKind, DataChange, RemoveKind};
+
+    fn event(kind: EventKind, paths: &[&Path]) -> Event {
+        paths.iter().fold(Event::new(kind), |event, path| {
+            event.add_path(path.to_path_buf())
+        })
+    }
+
+    #[test]
+    fn test_watchers_stop_when_their_last_window_closes() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = IgnoreSettings {
+            respect_gitignore: true,
+            custom_ignore_patterns: String::new(),
+        };
+        let watcher = |labels: &[&str]| Watcher {
+            debouncer: None,
+            state: Arc::new(Mutex::new(WatchState::new(
+                settings.clone(),
+                compile_matcher(dir.path(), &settings),
+            ))),
+            window_labels: labels.iter().map(|label| label.to_string()).collect(),
+        };
+        let mut watchers = HashMap::from([
+            ("/a".to_string(), watcher(&["main"])),
+            ("/b".to_string(), watcher(&["main", "other"])),
+            ("/c".to_string(), watcher(&["other"])),
+        ]);
+
+        release(&mut watchers, "other", Some("/c"));
+        assert!(!watchers.contains_key("/c"));
+
+        release(&mut watchers, "main", None);
+        assert_eq!(watchers.keys().collect::<Vec<_>>(), vec!["/b"]);
+        assert_eq!(
+            watchers["/b"].window_labels,
+            BTreeSet::from(["other".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_running_out_of_watches_is_a_typed_error() {
+        let root = Path::new("/project");
+        let error = watch_error(root, notify::Error::new(notify::ErrorKind::MaxFilesWatch));
+        let WatchLimitError(exceeded) = error.downcast_ref::<WatchLimitError>().unwrap();
+        assert_eq!(exceeded.root_path, "/project");
+
+        let error = watch_error(root, notify::Error::generic("boom"));
+        assert!(!error.is::<WatchLimitError>());
+    }
+
+    #[test]
+    fn test_only_ignore_files_that_take_effect_change_the_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join(".git/info")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join(".gitignore"), "node_modules/\n").unwrap();
+        let settings = IgnoreSettings {
+            respect_gitignore: true,
+            custom_ignore_patterns: String::new(),
+        };
+        let matcher = compile_matcher(root, &settings);
+
+        assert!(changes_rules(&matcher, &root.join(".gitignore")));
+        assert!(changes_rules(&matcher, &root.join("src/.ignore")));
+        assert!(changes_rules(&matcher, &root.join(".git/info/exclude")));
+        assert!(!changes_rules(
+            &matcher,
+            &root.join("node_modules/pkg/.gitignore")
+        ));
+        assert!(!changes_rules(&matcher, &root.join("src/main.rs")));
+    }
+
+    #[test]
+    fn test_changes_are_sorted_by_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let path = |name: &str| root.join(name);
+        std::fs::write(path("recreated.txt"), "").unwrap();
+
+        let events = [
+            event(EventKind::Create(CreateKind::File), &[&path("new.txt")]),
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                &[&path("new.txt")],
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                &[&path("edited.txt")],
+            ),
+            event(EventKind::Remove(RemoveKind::File), &[&path("gone.txt")]),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &[&path("a.txt"), &path("b.txt")],
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &[&path("c.txt"), &path("target/c.txt")],
+            ),
+            event(EventKind::Create(CreateKind::File), &[&path("temp.txt")]),
+            event(EventKind::Remove(RemoveKind::File), &[&path("temp.txt")]),
+            event(
+                EventKind::Remove(RemoveKind::File),
+                &[&path("recreated.txt")],
+            ),
+            event(
+                EventKind::Create(CreateKind::File),
+                &[&path("recreated.txt")],
+            ),
+            event(
+                EventKind::Create(CreateKind::File),
+                &[&path("target/out.o")],
+            ),
+        ];
+        let change = collect_changes("/root", &events, |p| !p.starts_with(root.join("target")));
+
+        let s = |name: &str| path_string(&path(name));
+        assert_eq!(change.root_path, "/root");
+        assert_eq!(change.created, vec![s("new.txt")]);
+        assert_eq!(change.modified, vec![s("edited.txt"), s("recreated.txt")]);
+        assert_eq!(change.removed, vec![s("c.txt"), s("gone.txt")]);
+        assert_eq!(
+            change.renamed,
+            vec![RenamedPath {
+                from: s("a.txt"),
+                to: s("b.txt"),
+            }]
+        );
+    }
 }
@@ -9,6 +9,27 @@ pub struct FileNode {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub children: Option<Vec<FileNode>>,
     pub is_directory: bool,
+    /// Set on directories from a depth-limited listing, including those whose children were
+    /// not loaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub child_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<FileMetadata>,
+}
+
+/// For directories, `size`, `tokens` and `modifiedAt` are rolled up from the files below.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMetadata {
+    pub size: f64,
+    pub modified_at: Option<f64>,
+    pub is_binary: Option<bool>,
+    /// Two-letter `git status --porcelain` code, e.g. ` M` or `??`.
+    pub git_status: Option<String>,
+    /// Only counts that are already cached; files that were never counted are left out.
+    pub tokens: Option<u32>,
+    pub file_count: Option<u32>,
+    pub dirty_count: Option<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
@@ -21,17 +42,317 @@ pub enum ChangeOperation {
         is_new_file: bool,
         total_blocks: u32,
         applied_blocks: u32,
+        blocks: Vec<PatchBlock>,
+        hunks: Vec<Hunk>,
+        original_hash: Option<String>,
+        diff: Vec<DiffHunk>,
+        stats: LineStats,
+        path_suggestions: Vec<PathSuggestion>,
+        source: OperationSource,
+        truncation: Option<Truncation>,
+        syntax: Option<SyntaxCheck>,
     },
     #[serde(rename_all = "camelCase")]
     Overwrite {
         file_path: String,
         content: String,
         is_new_file: bool,
+        hunks: Vec<Hunk>,
+        original_hash: Option<String>,
+        diff: Vec<DiffHunk>,
+        stats: LineStats,
+        path_suggestions: Vec<PathSuggestion>,
+        source: OperationSource,
+        truncation: Option<Truncation>,
+        syntax: Option<SyntaxCheck>,
     },
     #[serde(rename_all = "camelCase")]
-    Delete { file_path: String },
+    Delete {
+        file_path: String,
+        source: OperationSource,
+    },
     #[serde(rename_all = "camelCase")]
-    Move { from_path: String, to_path: String },
+    Move {
+        from_path: String,
+        to_path: String,
+        source: OperationSource,
+    },
+}
+
+/// Location in the original response: a half-open byte range and 1-based, inclusive lines.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceSpan {
+    pub start_byte: u32,
+    pub end_byte: u32,
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationSource {
+    pub span: SourceSpan,
+    pub explanation: String,
+}
+
+/// Set when the response was cut off in this operation, inside its code block or before it.
+/// Such an operation is not applied until a continuation has been merged.
+/// `resume_after_line` is the 1-based response line after which a continuation is merged.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Truncation {
+    pub resume_after_line: u32,
+    pub last_complete_line: String,
+}
+
+/// 1-based position of a syntax error in the proposed content.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntaxError {
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+/// `original_valid` is `None` when the file is new.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntaxCheck {
+    pub language: String,
+    pub errors: Vec<SyntaxError>,
+    pub original_valid: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchBlock {
+    pub search: String,
+    pub replace: String,
+    pub applied: bool,
+    pub span: SourceSpan,
+}
+
+/// One independently selectable change, as a replacement of `old_lines` lines starting at
+/// 1-based `old_start` in the original file. `new_start` is its position with all hunks applied.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Hunk {
+    pub id: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LineStats {
+    pub added: u32,
+    pub removed: u32,
+}
+
+/// A path relative to the backup's project root and the SHA-256 of its content blob, or no hash
+/// if the path did not exist when the backup was taken.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFile {
+    pub path: String,
+    pub hash: Option<String>,
+}
+
+/// `created_at` is in milliseconds since the Unix epoch.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub id: String,
+    pub root_path: String,
+    pub created_at: f64,
+    pub summary: Option<ChangeSummary>,
+    pub files: Vec<BackupFile>,
+}
+
+/// An applied change set that can be undone by restoring `before_backup_id` and redone by
+/// restoring `after_backup_id`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub id: String,
+    pub created_at: f64,
+    pub summary: Option<ChangeSummary>,
+    pub files: Vec<String>,
+    pub before_backup_id: String,
+    pub after_backup_id: String,
+}
+
+/// Entries before `position` are applied; the rest can be redone.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectHistory {
+    pub entries: Vec<HistoryEntry>,
+    pub position: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictKind {
+    Modified,
+    Created,
+    Deleted,
+}
+
+/// A file that no longer matches the state an undo or redo expects.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryConflict {
+    pub file_path: String,
+    pub kind: ConflictKind,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryStepResult {
+    pub applied: bool,
+    pub conflicts: Vec<HistoryConflict>,
+    pub history: ProjectHistory,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum OperationStatus {
+    Pending,
+    Applied,
+    Error,
+    Identical,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionOperation {
+    pub operation: ChangeOperation,
+    pub status: OperationStatus,
+    pub error: Option<String>,
+    /// The backup this operation reverts to, when it was re-parsed after the session's backup
+    /// was taken.
+    #[serde(default)]
+    pub backup_id: Option<String>,
+}
+
+/// A review in progress, persisted so it can be resumed after a restart. Timestamps are
+/// milliseconds since the Unix epoch.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewSession {
+    pub id: String,
+    pub root_path: String,
+    pub raw_response: String,
+    pub operations: Vec<SessionOperation>,
+    pub backup_id: Option<String>,
+    pub created_at: f64,
+    pub updated_at: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewSessionInfo {
+    pub id: String,
+    pub root_path: String,
+    pub backup_id: Option<String>,
+    pub created_at: f64,
+    pub updated_at: f64,
+    pub pending_count: u32,
+    pub applied_count: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub content: String,
+}
+
+/// A unified-diff style hunk with surrounding context; line numbers are 1-based.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSummary {
+    pub files_created: u32,
+    pub files_modified: u32,
+    pub files_deleted: u32,
+    pub files_moved: u32,
+    pub lines_added: u32,
+    pub lines_removed: u32,
+    pub total_blocks: u32,
+    pub applied_blocks: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ProtectionLevel {
+    Deny,
+    Warn,
+}
+
+/// A gitignore-style pattern, relative to the project root, for paths responses may not touch.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtectedPathRule {
+    pub pattern: String,
+    pub level: ProtectionLevel,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtectedPathViolation {
+    pub file_path: String,
+    pub pattern: String,
+    pub level: ProtectionLevel,
+}
+
+/// Operations on denied paths are left out of `operations`; `violations` lists them along with
+/// warnings for the operations that were kept.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewResult {
+    pub operations: Vec<ChangeOperation>,
+    pub summary: ChangeSummary,
+    pub violations: Vec<ProtectedPathViolation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum PathSuggestionReason {
+    ContentMatch,
+    SimilarPath,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PathSuggestion {
+    pub file_path: String,
+    pub reason: PathSuggestionReason,
+    pub score: i32,
+    pub matched_blocks: u32,
+    pub content: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Type)]
@@ -87,13 +408,188 @@ pub enum CommandStreamEvent {
     Finish(String),
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+/// Whether file operations may go through symlinks. Even when they are followed, the resolved
+/// path must stay inside the project root.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum SymlinkPolicy {
+    #[default]
+    Follow,
+    Deny,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct IgnoreSettings {
     pub respect_gitignore: bool,
     pub custom_ignore_patterns: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectIgnoreConfig {
+    pub respect_gitignore: Option<bool>,
+    pub patterns: Vec<String>,
+}
+
+/// The contents of a project's `.repowizard.toml`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectConfig {
+    pub ignore: ProjectIgnoreConfig,
+    pub formatters: Option<Vec<FormatterConfig>>,
+    pub protected_paths: Option<Vec<ProtectedPathRule>>,
+    pub meta_prompts: Vec<MetaPrompt>,
+    pub token_budget: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectConfigError {
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+/// `config` is `None` when the file is missing or invalid.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectConfigStatus {
+    pub path: String,
+    pub exists: bool,
+    pub config: Option<ProjectConfig>,
+    pub errors: Vec<ProjectConfigError>,
+}
+
+/// The settings a project's config file can override.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSettings {
+    pub ignore: IgnoreSettings,
+    pub formatters: Vec<FormatterConfig>,
+    pub meta_prompts: Vec<MetaPrompt>,
+    pub token_budget: Option<u32>,
+}
+
+/// Formatter commands from a project's config file, which only run once the user has approved
+/// them. `fingerprint` identifies the exact set of globs and commands that was shown.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatterConsent {
+    pub fingerprint: String,
+    pub formatters: Vec<FormatterConfig>,
+    pub approved: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedProjectSettings {
+    pub settings: ProjectSettings,
+    pub config: ProjectConfigStatus,
+    /// Set when the config file defines formatters.
+    pub formatter_consent: Option<FormatterConsent>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum SandboxKind {
+    Worktree,
+    Copy,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxInfo {
+    pub id: String,
+    pub root_path: String,
+    pub sandbox_path: String,
+    pub kind: SandboxKind,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum FileChangeStatus {
+    Added,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxFileDiff {
+    pub file_path: String,
+    pub status: FileChangeStatus,
+    pub diff: Vec<DiffHunk>,
+    pub stats: LineStats,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxPromotion {
+    /// Paths relative to the project root that were written or removed.
+    pub promoted: Vec<String>,
+    /// Backup of those paths as they were before promotion.
+    pub backup_id: String,
+    /// Set when the changes were promoted but the sandbox could not be cleaned up.
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub file_path: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    pub in_changed_file: bool,
+}
+
+/// Outcome of a check command; `context` summarizes it for inclusion in a follow-up prompt.
+#[derive(Debug, Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationResult {
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+    pub diagnostics: Vec<Diagnostic>,
+    pub context: String,
+}
+
+/// A formatter command for files matching a gitignore-style `glob`. The file path is appended
+/// to `command`, or substituted for `{file}` when present.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatterConfig {
+    pub glob: String,
+    pub command: String,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatterResult {
+    pub file_path: String,
+    pub command: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FormattedChanges {
+    pub operations: Vec<ChangeOperation>,
+    pub results: Vec<FormatterResult>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchResult {
@@ -201,9 +697,91 @@ pub struct PromptEstimateInput {
     pub ignore_settings: Option<IgnoreSettings>,
 }
 
+#[derive(Debug, Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairPrompt {
+    pub prompt: String,
+    pub failed_blocks: u32,
+    pub total_tokens: u32,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct PromptEstimateResult {
     pub total_tokens: u32,
     pub missing_paths: Vec<String>,
 }
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamedPath {
+    pub from: String,
+    pub to: String,
+}
+
+/// Unignored paths under `root_path` that changed, as reported by the file watcher.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type, tauri_specta::Event)]
+#[serde(rename_all = "camelCase")]
+pub struct FsChangeEvent {
+    pub root_path: String,
+    pub created: Vec<String>,
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+    pub renamed: Vec<RenamedPath>,
+}
+
+/// The config file of the project at `root_path` changed and has been reloaded.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type, tauri_specta::Event)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectConfigChangedEvent {
+    pub root_path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum WatcherStatus {
+    Running,
+    Restarting,
+    LimitReached,
+    Failed,
+}
+
+/// A project's file watcher, the windows using it and its health.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherInfo {
+    pub root_path: String,
+    pub window_labels: Vec<String>,
+    pub settings: IgnoreSettings,
+    pub status: WatcherStatus,
+    pub last_error: Option<String>,
+    pub restarts: u32,
+    pub started_at: f64,
+    pub last_event_at: Option<f64>,
+}
+
+/// The OS ran out of file watches while watching `root_path`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchLimitExceeded {
+    pub root_path: String,
+    pub max_user_watches: Option<u32>,
+}
+
+/// A file under review whose content is no longer what the review was parsed against, nor what
+/// it would write. A `None` hash means the file does not exist.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewTargetChange {
+    pub file_path: String,
+    pub expected_hash: Option<String>,
+    pub current_hash: Option<String>,
+}
+
+/// Files of the review open for `root_path` that were changed outside the review.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Type, tauri_specta::Event)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewTargetChangedEvent {
+    pub root_path: String,
+    pub changes: Vec<ReviewTargetChange>,
+}
@@ -0,0 +1,131 @@
+use repo_wizard::services::review_service;
+use repo_wizard::types::ChangeSummary;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_backup_manifest_list_and_revert() {
+    let project = tempdir().unwrap();
+    let root = project.path();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/a.txt"), "a\n").unwrap();
+    fs::write(root.join("b.txt"), "b\n").unwrap();
+
+    let summary = ChangeSummary {
+        files_modified: 2,
+        ..Default::default()
+    };
+    let backup_id = review_service::backup_files(
+        root,
+        vec![PathBuf::from("src/a.txt"), PathBuf::from("b.txt")],
+        Some(summary.clone()),
+    )
+    .await
+    .unwrap();
+
+    let manifest = review_service::get_backup(&backup_id).await.unwrap();
+    assert_eq!(
+        manifest.root_path,
+        root.to_string_lossy().replace('\\', "/")
+    );
+    let paths: Vec<_> = manifest
+        .files
+        .iter()
+        .map(|file| file.path.as_str())
+        .collect();
+    assert_eq!(paths, vec!["src/a.txt", "b.txt"]);
+    assert_eq!(manifest.summary, Some(summary));
+
+    let backups = review_service::list_backups(root).await.unwrap();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(backups[0].id, backup_id);
+    let other = tempdir().unwrap();
+    assert!(review_service::list_backups(other.path())
+        .await
+        .unwrap()
+        .is_empty());
+
+    fs::write(root.join("src/a.txt"), "changed\n").unwrap();
+    fs::remove_file(root.join("b.txt")).unwrap();
+    let reverted = review_service::revert_backup(&backup_id).await.unwrap();
+    assert_eq!(reverted, vec!["src/a.txt", "b.txt"]);
+    assert_eq!(fs::read_to_string(root.join("src/a.txt")).unwrap(), "a\n");
+    assert_eq!(fs::read_to_string(root.join("b.txt")).unwrap(), "b\n");
+
+    review_service::delete_backup(&backup_id).await.unwrap();
+    assert!(review_service::get_backup(&backup_id).await.is_err());
+    assert!(review_service::get_backup("../escape").await.is_err());
+}
+
+#[tokio::test]
+async fn test_revert_undoes_creations_moves_and_deletions() {
+    let project = tempdir().unwrap();
+    let root = project.path();
+    fs::write(root.join("keep.txt"), "keep\n").unwrap();
+    fs::write(root.join("old.txt"), "old\n").unwrap();
+    fs::write(root.join("gone.txt"), "gone\n").unwrap();
+
+    let backup_id = review_service::backup_files(
+        root,
+        vec![
+            PathBuf::from("new/dir/created.txt"),
+            PathBuf::from("old.txt"),
+            PathBuf::from("moved/old.txt"),
+            PathBuf::from("gone.txt"),
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+    let manifest = review_service::get_backup(&backup_id).await.unwrap();
+    assert_eq!(manifest.files[0].hash, None);
+    assert!(manifest.files[1].hash.is_some());
+    assert!(review_service::read_file_from_backup(
+        &backup_id,
+        &PathBuf::from("new/dir/created.txt")
+    )
+    .await
+    .is_err());
+
+    // Apply the change set: create, move and delete.
+    fs::create_dir_all(root.join("new/dir")).unwrap();
+    fs::write(root.join("new/dir/created.txt"), "created\n").unwrap();
+    fs::create_dir_all(root.join("moved")).unwrap();
+    fs::rename(root.join("old.txt"), root.join("moved/old.txt")).unwrap();
+    fs::remove_file(root.join("gone.txt")).unwrap();
+
+    review_service::revert_backup(&backup_id).await.unwrap();
+
+    assert!(!root.join("new").exists());
+    assert!(!root.join("moved").exists());
+    assert_eq!(fs::read_to_string(root.join("old.txt")).unwrap(), "old\n");
+    assert_eq!(fs::read_to_string(root.join("gone.txt")).unwrap(), "gone\n");
+    assert_eq!(fs::read_to_string(root.join("keep.txt")).unwrap(), "keep\n");
+
+    review_service::delete_backup(&backup_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_backups_refuse_paths_outside_the_project() {
+    let parent = tempdir().unwrap();
+    let root = parent.path().join("project");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(parent.path().join("secret.txt"), "secret\n").unwrap();
+
+    for path in [
+        PathBuf::from("../secret.txt"),
+        parent.path().join("secret.txt"),
+    ] {
+        assert!(
+            review_service::backup_files(&root, vec![path.clone()], None)
+                .await
+                .is_err(),
+            "{path:?}"
+        );
+    }
+    assert!(review_service::list_backups(&root)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,135 @@
+#![cfg(unix)]
+
+use repo_wizard::services::{formatter_service, review_service};
+use repo_wizard::types::{ChangeOperation, FormatterConfig};
+use similar_asserts::assert_eq;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+const UPPERCASE: &str = "tr a-z A-Z < {file} > {file}.tmp && mv {file}.tmp {file}";
+
+fn formatters() -> Vec<FormatterConfig> {
+    vec![
+        FormatterConfig {
+            glob: "broken/".to_string(),
+            command: "echo 'syntax error' >&2; exit 3".to_string(),
+        },
+        FormatterConfig {
+            glob: "*.txt".to_string(),
+            command: UPPERCASE.to_string(),
+        },
+    ]
+}
+
+fn content_of<'a>(ops: &'a [ChangeOperation], path: &str) -> &'a str {
+    ops.iter()
+        .find_map(|op| match op {
+            ChangeOperation::Patch {
+                file_path, content, ..
+            }
+            | ChangeOperation::Overwrite {
+                file_path, content, ..
+            } if file_path == path => Some(content.as_str()),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_format_proposed_changes() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    fs::write(root.join("notes.txt"), "keep\n").unwrap();
+    let markdown = "OVERWRITE notes.txt\n```\nhello\n```\n\nCREATE broken/a.txt\n```\nx\n```\n\nCREATE readme.md\n```\nhello\n```\n";
+
+    let ops = review_service::process_markdown_changes(markdown, root.to_str().unwrap())
+        .await
+        .unwrap();
+    let formatted = review_service::format_proposed_changes(root, ops, formatters())
+        .await
+        .unwrap();
+
+    assert_eq!(content_of(&formatted.operations, "notes.txt"), "HELLO");
+    assert_eq!(content_of(&formatted.operations, "readme.md"), "hello");
+    assert_eq!(
+        fs::read_to_string(root.join("notes.txt")).unwrap(),
+        "keep\n"
+    );
+    assert!(matches!(
+        formatted.operations.iter().find(|op| matches!(op, ChangeOperation::Overwrite { file_path, .. } if file_path == "notes.txt")),
+        Some(ChangeOperation::Overwrite { stats, .. }) if stats.added == 1 && stats.removed == 1
+    ));
+
+    let mut results = formatted.results;
+    results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].file_path, "broken/a.txt");
+    assert!(!results[0].success);
+    assert!(results[0]
+        .error
+        .as_deref()
+        .unwrap()
+        .contains("exited with code 3: syntax error"));
+    assert_eq!(results[1].file_path, "notes.txt");
+    assert!(results[1].success);
+}
+
+#[tokio::test]
+async fn test_format_files_in_place() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    fs::write(root.join("a.txt"), "abc\n").unwrap();
+    fs::write(root.join("b.rs"), "fn main() {}\n").unwrap();
+
+    let results = formatter_service::format_files(
+        root,
+        vec!["a.txt".to_string(), "b.rs".to_string()],
+        formatters(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert!(results[0].success);
+    assert_eq!(read(root, "a.txt"), "ABC\n");
+    assert_eq!(read(root, "b.rs"), "fn main() {}\n");
+}
+
+#[tokio::test]
+async fn test_paths_outside_the_project_are_rejected() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path().join("project");
+    fs::create_dir(&root).unwrap();
+    let outside = temp_dir.path().join("outside.txt");
+    fs::write(&outside, "abc\n").unwrap();
+
+    let results = formatter_service::format_files(
+        &root,
+        vec![
+            "../outside.txt".to_string(),
+            outside.to_string_lossy().to_string(),
+        ],
+        formatters(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(results.len(), 2);
+    for result in &results {
+        assert!(!result.success);
+        assert!(result.error.as_deref().unwrap().contains("Access denied"));
+    }
+    assert_eq!(read(temp_dir.path(), "outside.txt"), "abc\n");
+
+    let escaped = temp_dir.path().join("escaped.txt");
+    for path in ["../escaped.txt", escaped.to_str().unwrap()] {
+        let formatted = formatter_service::format_content(&root, path, "abc\n", UPPERCASE).await;
+        assert!(formatted.is_err());
+    }
+    assert!(!escaped.exists());
+}
+
+fn read(root: &Path, path: &str) -> String {
+    fs::read_to_string(root.join(path)).unwrap()
+}
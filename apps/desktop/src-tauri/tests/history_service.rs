@@ -0,0 +1,117 @@
+use repo_wizard::services::{history_service, review_service};
+use repo_wizard::types::ConflictKind;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+/// Backs up `paths`, applies `apply` to the project, and records it in the history.
+async fn apply_change_set(data_dir: &Path, root: &Path, paths: &[&str], apply: impl FnOnce()) {
+    let backup_id =
+        review_service::backup_files(root, paths.iter().map(PathBuf::from).collect(), None)
+            .await
+            .unwrap();
+    apply();
+    history_service::record_applied(data_dir, root, &backup_id, None)
+        .await
+        .unwrap();
+    review_service::delete_backup(&backup_id).await.unwrap();
+}
+
+fn read(root: &Path, path: &str) -> Option<String> {
+    fs::read_to_string(root.join(path)).ok()
+}
+
+#[tokio::test]
+async fn test_undo_and_redo_change_sets() {
+    let data_dir = tempdir().unwrap();
+    let project = tempdir().unwrap();
+    let (data, root) = (data_dir.path(), project.path());
+    fs::write(root.join("a.txt"), "a1\n").unwrap();
+
+    apply_change_set(data, root, &["a.txt"], || {
+        fs::write(root.join("a.txt"), "a2\n").unwrap()
+    })
+    .await;
+    apply_change_set(data, root, &["b.txt"], || {
+        fs::write(root.join("b.txt"), "b\n").unwrap()
+    })
+    .await;
+    apply_change_set(data, root, &["a.txt"], || {
+        fs::write(root.join("a.txt"), "a3\n").unwrap()
+    })
+    .await;
+
+    let history = history_service::get_history(data, root).await.unwrap();
+    assert_eq!((history.entries.len(), history.position), (3, 3));
+
+    let result = history_service::undo(data, root).await.unwrap();
+    assert!(result.applied);
+    assert_eq!(read(root, "a.txt").as_deref(), Some("a2\n"));
+    history_service::undo(data, root).await.unwrap();
+    assert_eq!(read(root, "b.txt"), None);
+    assert_eq!(
+        history_service::get_history(data, root)
+            .await
+            .unwrap()
+            .position,
+        1
+    );
+
+    let result = history_service::redo(data, root).await.unwrap();
+    assert!(result.applied);
+    assert_eq!(read(root, "b.txt").as_deref(), Some("b\n"));
+    assert_eq!(result.history.position, 2);
+
+    // Recording a new change set discards what could have been redone.
+    apply_change_set(data, root, &["c.txt"], || {
+        fs::write(root.join("c.txt"), "c\n").unwrap()
+    })
+    .await;
+    let history = history_service::get_history(data, root).await.unwrap();
+    assert_eq!((history.entries.len(), history.position), (3, 3));
+    assert!(history_service::redo(data, root).await.is_err());
+}
+
+#[tokio::test]
+async fn test_conflicting_edits_are_reported_not_clobbered() {
+    let data_dir = tempdir().unwrap();
+    let project = tempdir().unwrap();
+    let (data, root) = (data_dir.path(), project.path());
+    fs::write(root.join("a.txt"), "a1\n").unwrap();
+
+    apply_change_set(data, root, &["a.txt", "new.txt"], || {
+        fs::write(root.join("a.txt"), "a2\n").unwrap();
+        fs::write(root.join("new.txt"), "new\n").unwrap();
+    })
+    .await;
+
+    fs::write(root.join("a.txt"), "edited by hand\n").unwrap();
+    fs::remove_file(root.join("new.txt")).unwrap();
+    let result = history_service::undo(data, root).await.unwrap();
+    assert!(!result.applied);
+    assert_eq!(result.history.position, 1);
+    let conflicts: Vec<_> = result
+        .conflicts
+        .iter()
+        .map(|c| (c.file_path.as_str(), c.kind.clone()))
+        .collect();
+    assert_eq!(
+        conflicts,
+        vec![
+            ("a.txt", ConflictKind::Modified),
+            ("new.txt", ConflictKind::Deleted)
+        ]
+    );
+    assert_eq!(read(root, "a.txt").as_deref(), Some("edited by hand\n"));
+
+    fs::write(root.join("a.txt"), "a2\n").unwrap();
+    fs::write(root.join("new.txt"), "new\n").unwrap();
+    assert!(history_service::undo(data, root).await.unwrap().applied);
+    assert_eq!(read(root, "a.txt").as_deref(), Some("a1\n"));
+    assert_eq!(read(root, "new.txt"), None);
+
+    fs::write(root.join("new.txt"), "someone else\n").unwrap();
+    let result = history_service::redo(data, root).await.unwrap();
+    assert!(!result.applied);
+    assert_eq!(result.conflicts[0].kind, ConflictKind::Created);
+}
@@ -0,0 +1,399 @@
+#![cfg(unix)]
+
+use indoc::indoc;
+use repo_wizard::services::project_service;
+use repo_wizard::types::{IgnoreSettings, ProjectSettings, SymlinkPolicy};
+use std::fs;
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::process::Command;
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_symlinks_cannot_escape_the_root() {
+    let outside = tempdir().unwrap();
+    let project = tempdir().unwrap();
+    let root = project.path();
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(outside.path().join("secret.txt"), "secret\n").unwrap();
+    symlink(outside.path(), root.join("vendor")).unwrap();
+    symlink(root.join("src"), root.join("src-link")).unwrap();
+    symlink(
+        outside.path().join("missing.txt"),
+        root.join("dangling.txt"),
+    )
+    .unwrap();
+
+    // Writing, deleting or moving through a link to outside the root is refused.
+    assert!(
+        project_service::write_file_content(&root.join("vendor/new.txt"), "x", root)
+            .await
+            .is_err()
+    );
+    assert!(!outside.path().join("new.txt").exists());
+    assert!(
+        project_service::delete_file(&root.join("vendor/secret.txt"), root)
+            .await
+            .is_err()
+    );
+    assert!(outside.path().join("secret.txt").exists());
+    assert!(project_service::move_file(
+        &root.join("vendor/secret.txt"),
+        &root.join("src/secret.txt"),
+        root
+    )
+    .await
+    .is_err());
+    assert!(
+        project_service::write_file_content(&root.join("dangling.txt"), "x", root)
+            .await
+            .is_err()
+    );
+    assert!(!outside.path().join("missing.txt").exists());
+
+    // Links that stay inside the root are followed by default.
+    project_service::write_file_content(&root.join("src-link/a.txt"), "a", root)
+        .await
+        .unwrap();
+    assert_eq!(fs::read_to_string(root.join("src/a.txt")).unwrap(), "a");
+
+    project_service::set_symlink_policy(root, SymlinkPolicy::Deny);
+    let denied = project_service::write_file_content(&root.join("src-link/b.txt"), "b", root).await;
+    assert!(denied.is_err());
+    assert!(!root.join("src/b.txt").exists());
+
+    // The policy only applies to the project it was set for.
+    let other = tempdir().unwrap();
+    fs::create_dir(other.path().join("src")).unwrap();
+    symlink(other.path().join("src"), other.path().join("src-link")).unwrap();
+    project_service::write_file_content(&other.path().join("src-link/b.txt"), "b", other.path())
+        .await
+        .unwrap();
+    project_service::set_symlink_policy(root, SymlinkPolicy::Follow);
+}
+
+fn mode(path: &std::path::Path) -> u32 {
+    fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+#[tokio::test]
+async fn test_writes_are_atomic_and_keep_permissions() {
+    let project = tempdir().unwrap();
+    let root = project.path();
+    let script = root.join("run.sh");
+    fs::write(&script, "#!/bin/sh\necho old\n").unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o750)).unwrap();
+
+    project_service::write_file_content(&script, "#!/bin/sh\necho new\n", root)
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(&script).unwrap(),
+        "#!/bin/sh\necho new\n"
+    );
+    assert_eq!(mode(&script), 0o750);
+
+    // New files get the same mode as any other newly created file, not the temp file's 0600.
+    let reference = root.join("reference.txt");
+    fs::File::create(&reference).unwrap();
+    let created = root.join("nested/created.txt");
+    project_service::write_file_content(&created, "created", root)
+        .await
+        .unwrap();
+    assert_eq!(mode(&created), mode(&reference));
+
+    // Writing through a symlink updates its target and leaves the link in place.
+    symlink(&script, root.join("run-link.sh")).unwrap();
+    project_service::write_file_content(&root.join("run-link.sh"), "linked", root)
+        .await
+        .unwrap();
+    assert!(fs::symlink_metadata(root.join("run-link.sh"))
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert_eq!(fs::read_to_string(&script).unwrap(), "linked");
+
+    // No temp files are left behind.
+    let mut names: Vec<_> = fs::read_dir(root)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["nested", "reference.txt", "run-link.sh", "run.sh"]
+    );
+}
+
+#[tokio::test]
+async fn test_denied_paths_cannot_be_changed() {
+    let project = tempdir().unwrap();
+    let root = project.path();
+    fs::create_dir(root.join(".git")).unwrap();
+    fs::write(root.join(".git/config"), "[core]\n").unwrap();
+    fs::write(root.join("notes.txt"), "notes\n").unwrap();
+
+    let error = project_service::write_file_content(&root.join(".git/config"), "x", root)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("is protected by `.git/`"));
+    assert!(
+        project_service::delete_file(&root.join(".git/config"), root)
+            .await
+            .is_err()
+    );
+    assert!(
+        project_service::move_file(&root.join("notes.txt"), &root.join(".env.local"), root)
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        fs::read_to_string(root.join(".git/config")).unwrap(),
+        "[core]\n"
+    );
+    assert!(root.join("notes.txt").exists());
+
+    // Paths that only warn can still be written.
+    project_service::write_file_content(&root.join("vendor/lib.js"), "x", root)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_project_config_file_applies_on_top_of_user_settings() {
+    let project = tempdir().unwrap();
+    let root = project.path();
+    fs::write(
+        root.join(".repowizard.toml"),
+        indoc! {r#"
+            [ignore]
+            patterns = ["dist/"]
+
+            [[protected_paths]]
+            pattern = "schema.sql"
+            level = "deny"
+        "#},
+    )
+    .unwrap();
+    let user = ProjectSettings {
+        ignore: IgnoreSettings {
+            respect_gitignore: true,
+            custom_ignore_patterns: ".git".to_string(),
+        },
+        formatters: Vec::new(),
+        meta_prompts: Vec::new(),
+        token_budget: None,
+    };
+
+    let resolved = project_service::resolve_project_settings(root, user.clone());
+    assert!(resolved.config.exists && resolved.config.errors.is_empty());
+    assert_eq!(
+        resolved.settings.ignore.custom_ignore_patterns,
+        ".git\ndist/"
+    );
+    // The file's rules replace the defaults.
+    assert!(
+        project_service::write_file_content(&root.join("schema.sql"), "x", root)
+            .await
+            .is_err()
+    );
+    project_service::write_file_content(&root.join(".env"), "x", root)
+        .await
+        .unwrap();
+
+    // An invalid file is reported and ignored until it is fixed and reloaded.
+    fs::write(
+        root.join(".repowizard.toml"),
+        "[ignore]\npatterns = \"dist/\"\n",
+    )
+    .unwrap();
+    let status = project_service::load_project_config(root);
+    assert!(status.config.is_none());
+    assert_eq!(status.errors[0].line, 2);
+    let resolved = project_service::resolve_project_settings(root, user);
+    assert_eq!(resolved.settings.ignore.custom_ignore_patterns, ".git");
+}
+
+#[tokio::test]
+async fn test_reloaded_config_replaces_protected_paths() {
+    let project = tempdir().unwrap();
+    let root = project.path();
+    project_service::write_file_content(&root.join("schema.sql"), "x", root)
+        .await
+        .unwrap();
+
+    fs::write(
+        root.join(".repowizard.toml"),
+        "[[protected_paths]]\npattern = \"schema.sql\"\nlevel = \"deny\"\n",
+    )
+    .unwrap();
+    project_service::load_project_config(root);
+    assert!(
+        project_service::write_file_content(&root.join("schema.sql"), "y", root)
+            .await
+            .is_err()
+    );
+
+    fs::remove_file(root.join(".repowizard.toml")).unwrap();
+    project_service::load_project_config(root);
+    project_service::write_file_content(&root.join("schema.sql"), "z", root)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_config_formatters_need_approval() {
+    let project = tempdir().unwrap();
+    let root = project.path();
+    let write_config = |command: &str| {
+        fs::write(
+            root.join(".repowizard.toml"),
+            format!("[[formatters]]\nglob = \"*.txt\"\ncommand = \"{command}\"\n"),
+        )
+        .unwrap();
+        project_service::load_project_config(root);
+    };
+    write_config("touch formatted");
+    let user = ProjectSettings {
+        ignore: IgnoreSettings {
+            respect_gitignore: true,
+            custom_ignore_patterns: String::new(),
+        },
+        formatters: Vec::new(),
+        meta_prompts: Vec::new(),
+        token_budget: None,
+    };
+
+    let resolved = project_service::resolve_project_settings(root, user.clone());
+    let consent = resolved.formatter_consent.unwrap();
+    assert!(!consent.approved);
+    assert_eq!(consent.formatters[0].command, "touch formatted");
+    assert!(resolved.settings.formatters.is_empty());
+
+    project_service::approve_project_formatters(root, &consent.fingerprint).unwrap();
+    let resolved = project_service::resolve_project_settings(root, user.clone());
+    assert!(resolved.formatter_consent.unwrap().approved);
+    assert_eq!(resolved.settings.formatters.len(), 1);
+
+    // Changing the commands withdraws the approval.
+    write_config("rm -rf formatted");
+    let resolved = project_service::resolve_project_settings(root, user);
+    assert!(!resolved.formatter_consent.unwrap().approved);
+    assert!(resolved.settings.formatters.is_empty());
+    assert!(project_service::approve_project_formatters(root, &consent.fingerprint).is_err());
+}
+
+#[tokio::test]
+async fn test_directories_are_listed_one_level_at_a_time() {
+    let project = tempdir().unwrap();
+    let root = project.path();
+    fs::create_dir_all(root.join("src/nested/deep")).unwrap();
+    fs::create_dir_all(root.join("build")).unwrap();
+    fs::write(root.join(".ignore"), "build/\n").unwrap();
+    fs::write(root.join("src/lib.rs"), "").unwrap();
+    fs::write(root.join("src/nested/mod.rs"), "").unwrap();
+    fs::write(root.join("build/out.o"), "").unwrap();
+    let settings = IgnoreSettings {
+        respect_gitignore: true,
+        custom_ignore_patterns: String::new(),
+    };
+
+    let top = project_service::list_directory(root, root, settings.clone(), None)
+        .await
+        .unwrap();
+    let children = top.children.unwrap();
+    let names: Vec<_> = children.iter().map(|node| node.name.as_str()).collect();
+    assert_eq!(names, vec!["src", ".ignore"]);
+    assert_eq!(top.child_count, Some(2));
+    assert!(children[0].children.is_none());
+    assert_eq!(children[0].child_count, Some(2));
+    assert_eq!(children[1].child_count, None);
+
+    let src = project_service::list_directory(root, &root.join("src"), settings.clone(), Some(2))
+        .await
+        .unwrap();
+    let nested = &src.children.unwrap()[0];
+    assert_eq!(nested.name, "nested");
+    let nested_children = nested.children.as_ref().unwrap();
+    assert_eq!(nested_children[0].name, "deep");
+    assert_eq!(nested_children[0].child_count, Some(0));
+    assert!(nested_children[0].children.is_none());
+
+    assert!(project_service::list_directory(
+        root,
+        &root.join("src/lib.rs"),
+        settings.clone(),
+        None
+    )
+    .await
+    .is_err());
+    assert!(
+        project_service::list_directory(root, &root.join(".."), settings, None)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_metadata_is_rolled_up_into_directories() {
+    let project = tempdir().unwrap();
+    let root = project.path();
+    fs::create_dir_all(root.join("src/assets")).unwrap();
+    fs::write(root.join("src/lib.rs"), "fn a() {}\n").unwrap();
+    fs::write(root.join("src/assets/logo.png"), [0u8, 159, 146, 150, 0, 1]).unwrap();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(root)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    };
+    git(&["init", "-q"]);
+    git(&["add", "src"]);
+    git(&["commit", "-qm", "init"]);
+    fs::write(root.join("src/lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+    fs::write(root.join("src/new.rs"), "").unwrap();
+
+    let settings = IgnoreSettings {
+        respect_gitignore: true,
+        custom_ignore_patterns: String::new(),
+    };
+    let tree = project_service::list_directory_recursive(root, settings, true)
+        .await
+        .unwrap();
+    let src = tree
+        .children
+        .unwrap()
+        .into_iter()
+        .find(|node| node.name == "src")
+        .unwrap();
+    let metadata = |name: &str| {
+        src.children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|node| node.name == name)
+            .unwrap()
+            .metadata
+            .clone()
+            .unwrap()
+    };
+
+    let lib = metadata("lib.rs");
+    assert_eq!(lib.size, 20.0);
+    assert_eq!(lib.is_binary, Some(false));
+    assert_eq!(lib.git_status.as_deref(), Some(" M"));
+    assert!(lib.modified_at.is_some());
+    assert_eq!(metadata("new.rs").git_status.as_deref(), Some("??"));
+    let assets = metadata("assets");
+    assert_eq!(assets.file_count, Some(1));
+    assert_eq!(assets.dirty_count, Some(0));
+
+    let totals = src.metadata.unwrap();
+    assert_eq!(totals.size, 26.0);
+    assert_eq!(totals.file_count, Some(3));
+    assert_eq!(totals.dirty_count, Some(2));
+    assert_eq!(totals.is_binary, None);
+}
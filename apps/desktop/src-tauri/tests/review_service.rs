@@ -1,6 +1,9 @@
 use indoc::indoc;
-use repo_wizard::services::review_service;
-use repo_wizard::types::ChangeOperation;
+use repo_wizard::services::{project_service, review_service};
+use repo_wizard::types::{
+    ChangeOperation, DiffLineKind, EditFormat, OperationSource, PathSuggestionReason,
+    ProtectedPathRule, ProtectionLevel,
+};
 use similar_asserts::assert_eq;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -27,13 +30,15 @@ fn apply_change_operations(temp_path: &Path, ops: Vec<ChangeOperation>) {
                 }
                 fs::write(dest_path, content).expect("Failed to write file");
             }
-            ChangeOperation::Delete { file_path } => {
+            ChangeOperation::Delete { file_path, .. } => {
                 let dest_path = temp_path.join(file_path);
                 if dest_path.exists() {
                     fs::remove_file(dest_path).expect("Failed to remove file");
                 }
             }
-            ChangeOperation::Move { from_path, to_path } => {
+            ChangeOperation::Move {
+                from_path, to_path, ..
+            } => {
                 let src_path = temp_path.join(from_path);
                 let dest_path = temp_path.join(to_path);
                 if let Some(parent) = dest_path.parent() {
@@ -380,3 +385,1048 @@ async fn test_malformed_patch_syntax() {
         "Should return 0 operations for malformed patch"
     );
 }
+
+// ============================================================================
+//  Tests: Path Suggestions for Missing Targets
+// ============================================================================
+
+#[tokio::test]
+async fn test_missing_patch_target_suggests_content_match() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_path = temp_dir.path();
+    fs::create_dir_all(temp_path.join("src/services")).unwrap();
+    fs::write(
+        temp_path.join("src/services/user.rs"),
+        "pub fn name() -> &'static str {\n    \"old\"\n}\n",
+    )
+    .unwrap();
+    fs::write(temp_path.join("src/services/other.rs"), "fn other() {}\n").unwrap();
+
+    let markdown = indoc! {r#"
+        PATCH src/service/user.rs
+        ```
+        <<<<<<< SEARCH
+            "old"
+        =======
+            "new"
+        >>>>>>> REPLACE
+        ```
+    "#};
+
+    let ops = review_service::process_markdown_changes(markdown, temp_path.to_str().unwrap())
+        .await
+        .expect("Failed to process changes");
+
+    match &ops[0] {
+        ChangeOperation::Patch {
+            applied_blocks,
+            path_suggestions,
+            ..
+        } => {
+            assert_eq!(*applied_blocks, 0);
+            let best = path_suggestions.first().expect("Expected a suggestion");
+            assert_eq!(best.file_path, "src/services/user.rs");
+            assert_eq!(best.reason, PathSuggestionReason::ContentMatch);
+            assert_eq!(best.matched_blocks, 1);
+            assert!(best.content.as_deref().unwrap().contains("\"new\""));
+        }
+        _ => panic!("Expected Patch operation"),
+    }
+}
+
+#[tokio::test]
+async fn test_missing_target_suggestions_skip_ignored_files() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_path = temp_dir.path();
+    fs::create_dir(temp_path.join(".git")).unwrap();
+    fs::write(temp_path.join(".gitignore"), "vendor/\n").unwrap();
+    fs::create_dir_all(temp_path.join("vendor/src/services")).unwrap();
+    fs::write(
+        temp_path.join("vendor/src/services/user.rs"),
+        "pub fn name() -> &'static str {\n    \"old\"\n}\n",
+    )
+    .unwrap();
+
+    let markdown = indoc! {r#"
+        PATCH src/service/user.rs
+        ```
+        <<<<<<< SEARCH
+            "old"
+        =======
+            "new"
+        >>>>>>> REPLACE
+        ```
+    "#};
+
+    let ops = review_service::process_markdown_changes(markdown, temp_path.to_str().unwrap())
+        .await
+        .expect("Failed to process changes");
+    let ChangeOperation::Patch {
+        path_suggestions, ..
+    } = &ops[0]
+    else {
+        panic!("Expected Patch operation");
+    };
+    assert!(
+        path_suggestions
+            .iter()
+            .all(|suggestion| !suggestion.file_path.starts_with("vendor/")),
+        "{path_suggestions:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_missing_overwrite_target_suggests_similar_path() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_path = temp_dir.path();
+    fs::create_dir_all(temp_path.join("src/components")).unwrap();
+    fs::write(temp_path.join("src/components/Header.tsx"), "export {};\n").unwrap();
+
+    let markdown = indoc! {"
+        OVERWRITE src/component/Header.tsx
+        ```tsx
+        export const Header = () => null;
+        ```
+    "};
+
+    let ops = review_service::process_markdown_changes(markdown, temp_path.to_str().unwrap())
+        .await
+        .expect("Failed to process changes");
+
+    match &ops[0] {
+        ChangeOperation::Overwrite {
+            path_suggestions, ..
+        } => {
+            let best = path_suggestions.first().expect("Expected a suggestion");
+            assert_eq!(best.file_path, "src/components/Header.tsx");
+            assert_eq!(best.reason, PathSuggestionReason::SimilarPath);
+            assert!(best.content.is_none());
+        }
+        _ => panic!("Expected Overwrite operation"),
+    }
+}
+
+#[tokio::test]
+async fn test_created_file_has_no_path_suggestions() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_path = temp_dir.path();
+    fs::write(temp_path.join("main.rs"), "fn main() {}\n").unwrap();
+
+    let markdown = indoc! {"
+        CREATE main2.rs
+        ```rust
+        fn main() {}
+        ```
+    "};
+
+    let ops = review_service::process_markdown_changes(markdown, temp_path.to_str().unwrap())
+        .await
+        .expect("Failed to process changes");
+
+    match &ops[0] {
+        ChangeOperation::Overwrite {
+            path_suggestions, ..
+        } => assert!(path_suggestions.is_empty()),
+        _ => panic!("Expected Overwrite operation"),
+    }
+}
+
+// ============================================================================
+//  Tests: Provenance
+// ============================================================================
+
+async fn run_provenance_case(markdown: &str) {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_path = temp_dir.path();
+    fs::write(temp_path.join("test.txt"), "Line 1\nLine 2\nLine 3\n").unwrap();
+    fs::write(temp_path.join("old.txt"), "old\n").unwrap();
+
+    let ops = review_service::process_markdown_changes(markdown, temp_path.to_str().unwrap())
+        .await
+        .expect("Failed to process changes");
+    assert_eq!(ops.len(), 2);
+
+    for op in &ops {
+        match op {
+            ChangeOperation::Patch { blocks, source, .. } => {
+                assert_eq!((source.span.start_line, source.span.end_line), (3, 10));
+                let text =
+                    &markdown[source.span.start_byte as usize..source.span.end_byte as usize];
+                assert!(text.starts_with("PATCH test.txt"));
+                assert!(text.ends_with("```"));
+                assert_eq!(source.explanation, "I'll rename the helper.");
+
+                assert_eq!(blocks.len(), 1);
+                assert!(blocks[0].applied);
+                assert_eq!((blocks[0].span.start_line, blocks[0].span.end_line), (5, 9));
+                let block_text =
+                    &markdown[blocks[0].span.start_byte as usize..blocks[0].span.end_byte as usize];
+                assert!(block_text.starts_with("<<<<<<< SEARCH"));
+                assert!(block_text.ends_with(">>>>>>> REPLACE"));
+            }
+            ChangeOperation::Delete { source, .. } => {
+                assert_eq!((source.span.start_line, source.span.end_line), (13, 13));
+                let text =
+                    &markdown[source.span.start_byte as usize..source.span.end_byte as usize];
+                assert_eq!(text, "DELETE old.txt");
+                assert_eq!(source.explanation, "Then delete the old file.");
+            }
+            _ => panic!("Unexpected operation: {op:?}"),
+        }
+    }
+}
+
+const PROVENANCE_MARKDOWN: &str = indoc! {"
+    I'll rename the helper.
+
+    PATCH test.txt
+    ```
+    <<<<<<< SEARCH
+    Line 2
+    =======
+    Line 2 Modified
+    >>>>>>> REPLACE
+    ```
+
+    Then delete the old file.
+    DELETE old.txt
+"};
+
+#[tokio::test]
+async fn test_operation_source_spans() {
+    run_provenance_case(PROVENANCE_MARKDOWN).await;
+}
+
+#[tokio::test]
+async fn test_operation_source_spans_crlf() {
+    run_provenance_case(&PROVENANCE_MARKDOWN.replace('\n', "\r\n")).await;
+}
+
+// ============================================================================
+//  Truncated Responses
+// ============================================================================
+
+#[tokio::test]
+async fn test_truncated_overwrite_is_flagged() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path().to_str().unwrap();
+    let markdown = "OVERWRITE src/main.rs\n```rust\nfn main() {\n    println!(\"hi\");\n    let x";
+
+    let ops = review_service::process_markdown_changes(markdown, root)
+        .await
+        .unwrap();
+    assert_eq!(ops.len(), 1);
+    let ChangeOperation::Overwrite {
+        content,
+        truncation: Some(truncation),
+        ..
+    } = &ops[0]
+    else {
+        panic!("Expected a truncated overwrite, got {:?}", ops[0]);
+    };
+    assert_eq!(content, "fn main() {\n    println!(\"hi\");");
+    assert_eq!(truncation.resume_after_line, 4);
+    assert_eq!(truncation.last_complete_line, "    println!(\"hi\");");
+}
+
+#[tokio::test]
+async fn test_truncated_patch_keeps_complete_blocks() {
+    let temp_dir = tempdir().unwrap();
+    fs::write(temp_dir.path().join("test.txt"), "Line 1\nLine 2\nLine 3\n").unwrap();
+    let root = temp_dir.path().to_str().unwrap();
+    let markdown = indoc! {"
+        PATCH test.txt
+        ```
+        <<<<<<< SEARCH
+        Line 1
+        =======
+        Line 1 Modified
+        >>>>>>> REPLACE
+        <<<<<<< SEARCH
+        Line 3
+        ======="};
+
+    let ops = review_service::process_markdown_changes(markdown, root)
+        .await
+        .unwrap();
+    let ChangeOperation::Patch {
+        content,
+        total_blocks,
+        truncation: Some(truncation),
+        ..
+    } = &ops[0]
+    else {
+        panic!("Expected a truncated patch, got {:?}", ops[0]);
+    };
+    assert_eq!(*total_blocks, 1);
+    assert_eq!(content, "Line 1 Modified\nLine 2\nLine 3\n");
+    assert_eq!(truncation.resume_after_line, 7);
+    assert_eq!(truncation.last_complete_line, ">>>>>>> REPLACE");
+
+    let prompt = review_service::build_continuation_prompt(markdown).unwrap();
+    assert!(prompt.contains("PATCH block for `test.txt`"));
+    assert!(prompt.contains(">>>>>>> REPLACE"));
+    assert!(prompt.contains("<<<<<<< SEARCH"));
+}
+
+#[tokio::test]
+async fn test_merge_continuation_completes_response() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path().to_str().unwrap();
+    let markdown = "CREATE notes.txt\n```\nalpha\nbeta\ngam";
+    let continuation = indoc! {"
+        CREATE notes.txt
+        ```text
+        beta
+        gamma
+        ```
+
+        DELETE old.txt
+    "};
+
+    let merged = review_service::merge_continuation(markdown, continuation).unwrap();
+    assert_eq!(
+        merged,
+        "CREATE notes.txt\n```\nalpha\nbeta\ngamma\n```\n\nDELETE old.txt\n"
+    );
+
+    let ops = review_service::process_markdown_changes(&merged, root)
+        .await
+        .unwrap();
+    assert_eq!(ops.len(), 2);
+    assert!(matches!(
+        &ops[0],
+        ChangeOperation::Overwrite { content, truncation: None, .. } if content == "alpha\nbeta\ngamma"
+    ));
+}
+
+#[tokio::test]
+async fn test_command_cut_off_before_its_block_is_flagged() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path().to_str().unwrap();
+    let markdown = "DELETE old.txt\n\nCREATE notes.txt\n";
+
+    let ops = review_service::process_markdown_changes(markdown, root)
+        .await
+        .unwrap();
+    let Some(ChangeOperation::Overwrite {
+        content,
+        truncation: Some(truncation),
+        ..
+    }) = ops
+        .iter()
+        .find(|op| matches!(op, ChangeOperation::Overwrite { .. }))
+    else {
+        panic!("Expected a truncated create, got {:?}", ops);
+    };
+    assert_eq!(content, "");
+    assert_eq!(truncation.resume_after_line, 3);
+
+    let prompt = review_service::build_continuation_prompt(markdown).unwrap();
+    assert!(prompt.contains("before its code block"), "{prompt}");
+
+    let merged = review_service::merge_continuation(markdown, "```text\nnotes\n```\n").unwrap();
+    assert_eq!(
+        merged,
+        "DELETE old.txt\n\nCREATE notes.txt\n```text\nnotes\n```\n"
+    );
+    let ops = review_service::process_markdown_changes(&merged, root)
+        .await
+        .unwrap();
+    assert!(ops.iter().any(|op| matches!(
+        op,
+        ChangeOperation::Overwrite { content, truncation: None, .. } if content == "notes"
+    )));
+}
+
+#[tokio::test]
+async fn test_truncated_operations_are_not_applied() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path().to_str().unwrap();
+    let markdown = "OVERWRITE src/main.rs\n```rust\nfn main() {\n";
+
+    let ops = review_service::process_markdown_changes(markdown, root)
+        .await
+        .unwrap();
+    let ChangeOperation::Overwrite { hunks, .. } = &ops[0] else {
+        panic!("Expected an overwrite, got {:?}", ops[0]);
+    };
+    let ids = hunks.iter().map(|hunk| hunk.id.clone()).collect();
+    let error = review_service::apply_selected_hunks(temp_dir.path(), ops[0].clone(), ids)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("cut off"), "{error}");
+}
+
+#[test]
+fn test_continuation_requires_truncated_response() {
+    let markdown = "DELETE old.txt\n";
+    assert!(review_service::build_continuation_prompt(markdown).is_err());
+    assert!(review_service::merge_continuation(markdown, "more").is_err());
+}
+
+// ============================================================================
+//  Repair Prompts
+// ============================================================================
+
+#[tokio::test]
+async fn test_repair_prompt_lists_failed_blocks() {
+    let temp_dir = tempdir().unwrap();
+    fs::write(temp_dir.path().join("test.txt"), "Line 1\nLine 2\nLine 3\n").unwrap();
+    let markdown = indoc! {"
+        PATCH test.txt
+        ```
+        <<<<<<< SEARCH
+        Line 1
+        =======
+        Line 1 Modified
+        >>>>>>> REPLACE
+        <<<<<<< SEARCH
+        Line 4
+        =======
+        Line 4 Modified
+        >>>>>>> REPLACE
+        ```
+    "};
+
+    let ops = review_service::process_markdown_changes(markdown, temp_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    let repair = review_service::build_repair_prompt(temp_dir.path(), ops, EditFormat::Diff)
+        .await
+        .unwrap();
+
+    assert_eq!(repair.failed_blocks, 1);
+    assert!(repair.total_tokens > 0);
+    assert!(repair.prompt.contains("## test.txt"));
+    assert!(repair.prompt.contains("Line 4\n=======\nLine 4 Modified"));
+    assert!(!repair.prompt.contains("Line 1 Modified"));
+    assert!(repair
+        .prompt
+        .contains("Current content:\n\n````\nLine 1\nLine 2\nLine 3\n````"));
+    assert!(repair
+        .prompt
+        .contains("<<<<<<< SEARCH ... ======= ... >>>>>>> REPLACE"));
+}
+
+#[tokio::test]
+async fn test_repair_prompt_windows_large_files() {
+    let temp_dir = tempdir().unwrap();
+    let content: String = (1..=500).map(|i| format!("line {i}\n")).collect();
+    fs::write(temp_dir.path().join("big.txt"), content).unwrap();
+    let markdown = indoc! {"
+        PATCH big.txt
+        ```
+        <<<<<<< SEARCH
+        line 300
+        line 301 typo
+        =======
+        line 300 changed
+        >>>>>>> REPLACE
+        ```
+    "};
+
+    let ops = review_service::process_markdown_changes(markdown, temp_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    let repair = review_service::build_repair_prompt(temp_dir.path(), ops, EditFormat::Diff)
+        .await
+        .unwrap();
+
+    assert!(repair.prompt.contains("lines 285-316 of 500"));
+    assert!(repair.prompt.contains("line 285\n"));
+    assert!(!repair.prompt.contains("line 284\n"));
+}
+
+#[tokio::test]
+async fn test_repair_prompt_shows_head_and_tail_for_unmatched_blocks() {
+    let temp_dir = tempdir().unwrap();
+    let content: String = (1..=500).map(|i| format!("line {i}\n")).collect();
+    fs::write(temp_dir.path().join("big.txt"), content).unwrap();
+    let markdown = indoc! {"
+        PATCH big.txt
+        ```
+        <<<<<<< SEARCH
+        nothing like this
+        =======
+        something else
+        >>>>>>> REPLACE
+        ```
+    "};
+
+    let ops = review_service::process_markdown_changes(markdown, temp_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    let repair = review_service::build_repair_prompt(temp_dir.path(), ops, EditFormat::Diff)
+        .await
+        .unwrap();
+
+    assert!(repair
+        .prompt
+        .contains("No part of the current content, 500 lines long, resembles the failed blocks"));
+    assert!(repair.prompt.contains("lines 1-100 of 500"));
+    assert!(repair.prompt.contains("lines 401-500 of 500"));
+    assert!(repair.prompt.contains("line 1\n"));
+    assert!(repair.prompt.contains("line 500\n"));
+    assert!(!repair.prompt.contains("line 101\n"));
+    assert!(!repair.prompt.contains("line 400\n"));
+}
+
+#[tokio::test]
+async fn test_repair_prompt_leaves_out_files_outside_the_project() {
+    let temp_dir = tempdir().unwrap();
+    let project = temp_dir.path().join("project");
+    fs::create_dir(&project).unwrap();
+    fs::write(project.join("test.txt"), "Line 1\n").unwrap();
+    fs::write(temp_dir.path().join("secret.txt"), "top secret\n").unwrap();
+    let markdown = indoc! {"
+        PATCH test.txt
+        ```
+        <<<<<<< SEARCH
+        Line 4
+        =======
+        Line 4 Modified
+        >>>>>>> REPLACE
+        ```
+    "};
+
+    let mut ops = review_service::process_markdown_changes(markdown, project.to_str().unwrap())
+        .await
+        .unwrap();
+    let mut outside = ops[0].clone();
+    if let ChangeOperation::Patch { file_path, .. } = &mut outside {
+        *file_path = "../secret.txt".to_string();
+    }
+    ops.push(outside);
+    let repair = review_service::build_repair_prompt(&project, ops, EditFormat::Diff)
+        .await
+        .unwrap();
+
+    assert!(repair.prompt.contains("## test.txt"));
+    assert!(!repair.prompt.contains("secret"));
+}
+
+#[tokio::test]
+async fn test_repair_prompt_requires_failed_blocks() {
+    let temp_dir = tempdir().unwrap();
+    let ops = review_service::process_markdown_changes(
+        "DELETE old.txt\n",
+        temp_dir.path().to_str().unwrap(),
+    )
+    .await
+    .unwrap();
+    assert!(
+        review_service::build_repair_prompt(temp_dir.path(), ops, EditFormat::Diff)
+            .await
+            .is_err()
+    );
+}
+
+// ============================================================================
+//  Hunk Selection
+// ============================================================================
+
+fn op_hunk_ids(op: &ChangeOperation) -> Vec<String> {
+    match op {
+        ChangeOperation::Patch { hunks, .. } | ChangeOperation::Overwrite { hunks, .. } => {
+            hunks.iter().map(|hunk| hunk.id.clone()).collect()
+        }
+        _ => panic!("Expected a patch or overwrite, got {:?}", op),
+    }
+}
+
+#[tokio::test]
+async fn test_patch_blocks_become_selectable_hunks() {
+    let temp_dir = tempdir().unwrap();
+    fs::write(
+        temp_dir.path().join("test.txt"),
+        "one\ntwo\nthree\nfour\nfive\n",
+    )
+    .unwrap();
+    let root = temp_dir.path().to_str().unwrap();
+    let markdown = indoc! {"
+        PATCH test.txt
+        ```
+        <<<<<<< SEARCH
+        one
+        =======
+        ONE
+        uno
+        >>>>>>> REPLACE
+        <<<<<<< SEARCH
+        three
+        =======
+        THREE
+        >>>>>>> REPLACE
+        <<<<<<< SEARCH
+        five
+        =======
+        >>>>>>> REPLACE
+        ```
+    "};
+
+    let ops = review_service::process_markdown_changes(markdown, root)
+        .await
+        .unwrap();
+    let ChangeOperation::Patch { hunks, .. } = &ops[0] else {
+        panic!("Expected a patch");
+    };
+    assert_eq!(
+        hunks
+            .iter()
+            .map(|h| (h.old_start, h.old_lines, h.new_start, h.new_lines))
+            .collect::<Vec<_>>(),
+        vec![(1, 1, 1, 2), (3, 1, 4, 1), (5, 1, 6, 1)]
+    );
+
+    let reparsed = review_service::process_markdown_changes(markdown, root)
+        .await
+        .unwrap();
+    let ids = op_hunk_ids(&ops[0]);
+    assert_eq!(ids, op_hunk_ids(&reparsed[0]));
+
+    let all = review_service::apply_selected_hunks(temp_dir.path(), ops[0].clone(), ids.clone())
+        .await
+        .unwrap();
+    assert_eq!(all, "ONE\nuno\ntwo\nTHREE\nfour\n\n");
+
+    let subset = review_service::apply_selected_hunks(
+        temp_dir.path(),
+        ops[0].clone(),
+        vec![ids[2].clone(), ids[0].clone()],
+    )
+    .await
+    .unwrap();
+    assert_eq!(subset, "ONE\nuno\ntwo\nthree\nfour\n\n");
+}
+
+#[tokio::test]
+async fn test_overwrite_diff_hunks_can_be_applied_separately() {
+    let temp_dir = tempdir().unwrap();
+    fs::write(temp_dir.path().join("test.txt"), "a\nb\nc\nd\ne\n").unwrap();
+    let root = temp_dir.path().to_str().unwrap();
+    let markdown = indoc! {"
+        OVERWRITE test.txt
+        ```
+        a
+        B
+        c
+        e
+        f
+        ```
+    "};
+
+    let ops = review_service::process_markdown_changes(markdown, root)
+        .await
+        .unwrap();
+    let ids = op_hunk_ids(&ops[0]);
+    assert_eq!(ids.len(), 3);
+
+    let only_last =
+        review_service::apply_selected_hunks(temp_dir.path(), ops[0].clone(), vec![ids[2].clone()])
+            .await
+            .unwrap();
+    assert_eq!(only_last, "a\nb\nc\nd\ne\nf");
+
+    let none = review_service::apply_selected_hunks(temp_dir.path(), ops[0].clone(), vec![])
+        .await
+        .unwrap();
+    assert_eq!(none, "a\nb\nc\nd\ne\n");
+
+    fs::write(temp_dir.path().join("test.txt"), "a\nchanged\nc\nd\ne\n").unwrap();
+    assert!(review_service::apply_selected_hunks(
+        temp_dir.path(),
+        ops[0].clone(),
+        vec![ids[0].clone()],
+    )
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_dependent_patch_blocks_fall_back_to_diff_hunks() {
+    let temp_dir = tempdir().unwrap();
+    fs::write(temp_dir.path().join("test.txt"), "alpha\nbeta\n").unwrap();
+    let root = temp_dir.path().to_str().unwrap();
+    let markdown = indoc! {"
+        PATCH test.txt
+        ```
+        <<<<<<< SEARCH
+        alpha
+        =======
+        gamma
+        >>>>>>> REPLACE
+        <<<<<<< SEARCH
+        gamma
+        =======
+        delta
+        >>>>>>> REPLACE
+        ```
+    "};
+
+    let ops = review_service::process_markdown_changes(markdown, root)
+        .await
+        .unwrap();
+    let ChangeOperation::Patch { content, .. } = &ops[0] else {
+        panic!("Expected a patch");
+    };
+    let ids = op_hunk_ids(&ops[0]);
+    assert_eq!(ids.len(), 1);
+    let rebuilt = review_service::apply_selected_hunks(temp_dir.path(), ops[0].clone(), ids)
+        .await
+        .unwrap();
+    assert_eq!(&rebuilt, content);
+}
+
+// ============================================================================
+//  Diffs & Statistics
+// ============================================================================
+
+#[tokio::test]
+async fn test_operations_carry_diff_stats_and_summary() {
+    let temp_dir = tempdir().unwrap();
+    let original: String = (1..=10).map(|i| format!("line {i}\n")).collect();
+    fs::write(temp_dir.path().join("test.txt"), &original).unwrap();
+    fs::write(temp_dir.path().join("old.txt"), "bye\n").unwrap();
+    let root = temp_dir.path().to_str().unwrap();
+    let markdown = indoc! {"
+        PATCH test.txt
+        ```
+        <<<<<<< SEARCH
+        line 5
+        =======
+        line five
+        line 5.5
+        >>>>>>> REPLACE
+        <<<<<<< SEARCH
+        missing
+        =======
+        nothing
+        >>>>>>> REPLACE
+        ```
+
+        CREATE new.txt
+        ```
+        hello
+        world
+        ```
+
+        DELETE old.txt
+    "};
+
+    let ops = review_service::process_markdown_changes(markdown, root)
+        .await
+        .unwrap();
+    let patch = ops
+        .iter()
+        .find(|op| matches!(op, ChangeOperation::Patch { .. }))
+        .unwrap();
+    let ChangeOperation::Patch {
+        original_hash,
+        diff,
+        stats,
+        ..
+    } = patch
+    else {
+        unreachable!();
+    };
+    assert_eq!(original_hash.as_ref().map(String::len), Some(64));
+    assert_eq!((stats.added, stats.removed), (2, 1));
+    assert_eq!(diff.len(), 1);
+    let hunk = &diff[0];
+    assert_eq!(
+        (
+            hunk.old_start,
+            hunk.old_lines,
+            hunk.new_start,
+            hunk.new_lines
+        ),
+        (2, 7, 2, 8)
+    );
+    assert_eq!(hunk.lines[3].kind, DiffLineKind::Removed);
+    assert_eq!(hunk.lines[3].content, "line 5");
+    assert_eq!(hunk.lines[4].kind, DiffLineKind::Added);
+    assert_eq!(hunk.lines[4].content, "line five");
+
+    let created = ops
+        .iter()
+        .find(|op| matches!(op, ChangeOperation::Overwrite { .. }))
+        .unwrap();
+    assert!(matches!(
+        created,
+        ChangeOperation::Overwrite { original_hash: None, stats, .. } if stats.added == 2
+    ));
+
+    let summary = review_service::summarize_changes(&ops);
+    assert_eq!(summary.files_created, 1);
+    assert_eq!(summary.files_modified, 1);
+    assert_eq!(summary.files_deleted, 1);
+    assert_eq!(summary.files_moved, 0);
+    assert_eq!((summary.lines_added, summary.lines_removed), (4, 1));
+    assert_eq!((summary.total_blocks, summary.applied_blocks), (2, 1));
+}
+
+// ============================================================================
+//  Syntax Checks
+// ============================================================================
+
+#[tokio::test]
+async fn test_proposed_contents_are_syntax_checked() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_path = temp_dir.path();
+    fs::write(temp_path.join("config.json"), "{\"a\": 1,}\n").unwrap();
+    fs::write(temp_path.join("lib.rs"), "fn a() {}\n").unwrap();
+
+    let markdown = indoc! {r#"
+        PATCH config.json
+        ```
+        <<<<<<< SEARCH
+        {"a": 1,}
+        =======
+        {"a": 1}
+        >>>>>>> REPLACE
+        ```
+
+        PATCH lib.rs
+        ```rust
+        <<<<<<< SEARCH
+        fn a() {}
+        =======
+        fn a() {
+            let x = ;
+        }
+        >>>>>>> REPLACE
+        ```
+
+        CREATE notes.txt
+        ```
+        hello
+        ```
+    "#};
+
+    let ops = review_service::process_markdown_changes(markdown, temp_path.to_str().unwrap())
+        .await
+        .unwrap();
+    let syntax_of = |path: &str| {
+        ops.iter()
+            .find_map(|op| match op {
+                ChangeOperation::Patch {
+                    file_path, syntax, ..
+                }
+                | ChangeOperation::Overwrite {
+                    file_path, syntax, ..
+                } if file_path == path => Some(syntax.clone()),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    let json = syntax_of("config.json").unwrap();
+    assert_eq!(json.language, "json");
+    assert!(json.errors.is_empty());
+    assert_eq!(json.original_valid, Some(false));
+
+    let rust = syntax_of("lib.rs").unwrap();
+    assert_eq!(rust.language, "rust");
+    assert_eq!(rust.errors.len(), 1);
+    assert_eq!((rust.errors[0].line, rust.errors[0].column), (2, 13));
+    assert_eq!(rust.original_valid, Some(true));
+
+    assert!(syntax_of("notes.txt").is_none());
+}
+
+// ============================================================================
+//  Protected Paths
+// ============================================================================
+
+#[tokio::test]
+async fn test_review_drops_denied_paths_and_reports_warnings() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let root = temp_dir.path().to_str().unwrap();
+
+    let markdown = indoc! {r#"
+        CREATE .env
+        ```
+        TOKEN=abc
+        ```
+
+        CREATE vendor/lib.js
+        ```
+        export {};
+        ```
+
+        CREATE src/main.rs
+        ```rust
+        fn main() {}
+        ```
+    "#};
+
+    let review = review_service::review_changes(markdown, root)
+        .await
+        .unwrap();
+    let mut paths: Vec<_> = review
+        .operations
+        .iter()
+        .map(|op| match op {
+            ChangeOperation::Overwrite { file_path, .. } => file_path.as_str(),
+            other => panic!("unexpected operation {other:?}"),
+        })
+        .collect();
+    paths.sort();
+    assert_eq!(paths, vec!["src/main.rs", "vendor/lib.js"]);
+    assert_eq!(review.summary.files_created, 2);
+
+    let mut levels: Vec<_> = review
+        .violations
+        .iter()
+        .map(|v| (v.file_path.as_str(), v.pattern.as_str(), v.level.clone()))
+        .collect();
+    levels.sort_by_key(|(path, ..)| *path);
+    assert_eq!(
+        levels,
+        vec![
+            (".env", ".env*", ProtectionLevel::Deny),
+            ("vendor/lib.js", "vendor/", ProtectionLevel::Warn),
+        ]
+    );
+
+    // A project's own rules replace the defaults.
+    project_service::set_protected_paths(
+        temp_dir.path(),
+        vec![ProtectedPathRule {
+            pattern: "src/**/*.rs".to_string(),
+            level: ProtectionLevel::Deny,
+        }],
+    )
+    .unwrap();
+    let review = review_service::review_changes(markdown, root)
+        .await
+        .unwrap();
+    assert_eq!(review.operations.len(), 2);
+    assert_eq!(review.violations.len(), 1);
+    assert_eq!(review.violations[0].file_path, "src/main.rs");
+}
+
+#[tokio::test]
+async fn test_paths_outside_the_project_are_denied_in_review() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let root = temp_dir.path().to_str().unwrap();
+    let markdown = indoc! {r#"
+        PATCH /etc/hosts
+        ```
+        <<<<<<< SEARCH
+        localhost
+        =======
+        example
+        >>>>>>> REPLACE
+        ```
+
+        DELETE src/../../outside.txt
+
+        CREATE src/./ok.txt
+        ```
+        ok
+        ```
+    "#};
+
+    let review = review_service::review_changes(markdown, root)
+        .await
+        .unwrap();
+    let mut denied: Vec<_> = review
+        .violations
+        .iter()
+        .map(|v| (v.file_path.as_str(), v.level.clone()))
+        .collect();
+    denied.sort_by_key(|(path, _)| *path);
+    assert_eq!(
+        denied,
+        vec![
+            ("/etc/hosts", ProtectionLevel::Deny),
+            ("src/../../outside.txt", ProtectionLevel::Deny),
+        ]
+    );
+    assert_eq!(review.operations.len(), 1);
+}
+
+#[tokio::test]
+async fn test_edits_to_files_under_review_are_reported_and_can_be_reparsed() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let root_str = root.to_str().unwrap();
+    let target = root.join("test.txt");
+    fs::write(&target, "Line 1\nLine 2\n").unwrap();
+
+    let markdown = indoc! {r#"
+        PATCH test.txt
+        ```
+        <<<<<<< SEARCH
+        Line 1
+        =======
+        Line 1 Mod
+        >>>>>>> REPLACE
+        ```
+    "#};
+    let operations = review_service::process_markdown_changes(markdown, root_str)
+        .await
+        .unwrap();
+    review_service::track_review(root, markdown, &operations);
+    let changed = || review_service::changed_review_targets(root, [target.as_path()]);
+
+    // Neither the original nor the proposed content is a conflict.
+    assert!(changed().is_empty());
+    apply_change_operations(root, operations.clone());
+    assert!(changed().is_empty());
+
+    fs::write(&target, "Line 1\nLine 2 edited\n").unwrap();
+    let changes = changed();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].file_path, "test.txt");
+    assert_ne!(changes[0].current_hash, changes[0].expected_hash);
+    // The same edit is only reported once.
+    assert!(changed().is_empty());
+
+    let rerun = review_service::rerun_review(root_str, &["test.txt".to_string()])
+        .await
+        .unwrap();
+    match rerun.as_slice() {
+        [ChangeOperation::Patch { content, .. }] => {
+            assert_eq!(content, "Line 1 Mod\nLine 2 edited\n")
+        }
+        other => panic!("unexpected operations {other:?}"),
+    }
+    // The re-parsed operation expects the edited content.
+    assert!(changed().is_empty());
+
+    review_service::untrack_review(root);
+    fs::write(&target, "something else\n").unwrap();
+    assert!(changed().is_empty());
+    assert!(review_service::rerun_review(root_str, &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_both_ends_of_a_move_under_review_are_tracked() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let from = root.join("old.txt");
+    let to = root.join("new.txt");
+    fs::write(&from, "content\n").unwrap();
+
+    let operations = vec![ChangeOperation::Move {
+        from_path: "old.txt".to_string(),
+        to_path: "new.txt".to_string(),
+        source: OperationSource::default(),
+    }];
+    review_service::track_review(root, "MOVE old.txt new.txt\n", &operations);
+    let changed = || review_service::changed_review_targets(root, [from.as_path(), to.as_path()]);
+
+    assert!(changed().is_empty());
+    apply_change_operations(root, operations);
+    assert!(changed().is_empty());
+
+    fs::write(&to, "edited\n").unwrap();
+    fs::write(&from, "recreated\n").unwrap();
+    let paths: Vec<String> = changed().into_iter().map(|c| c.file_path).collect();
+    assert_eq!(paths, vec!["new.txt", "old.txt"]);
+
+    review_service::untrack_review(root);
+}
@@ -0,0 +1,250 @@
+use repo_wizard::services::{review_service, sandbox_service};
+use repo_wizard::types::{FileChangeStatus, SandboxKind};
+use similar_asserts::assert_eq;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+const CHANGES: &str = "OVERWRITE src/main.txt\n```\nnew main\n```\n\nCREATE src/added.txt\n```\nadded\n```\n\nDELETE old.txt\n";
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn git(root: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(root)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+#[tokio::test]
+async fn test_copy_sandbox_lifecycle() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    write(root, "src/main.txt", "old main\n");
+    write(root, "old.txt", "old\n");
+
+    let sandbox = sandbox_service::create_sandbox(root, SandboxKind::Copy)
+        .await
+        .unwrap();
+    let sandbox_path = Path::new(&sandbox.sandbox_path).to_owned();
+    assert_eq!(
+        fs::read_to_string(sandbox_path.join("src/main.txt")).unwrap(),
+        "old main\n"
+    );
+
+    let ops = review_service::process_markdown_changes(CHANGES, root.to_str().unwrap())
+        .await
+        .unwrap();
+    sandbox_service::apply_to_sandbox(&sandbox.id, ops)
+        .await
+        .unwrap();
+
+    // The real tree is untouched until promotion.
+    assert_eq!(
+        fs::read_to_string(root.join("src/main.txt")).unwrap(),
+        "old main\n"
+    );
+    assert!(!root.join("src/added.txt").exists());
+
+    #[cfg(unix)]
+    {
+        let result = sandbox_service::run_in_sandbox(&sandbox.id, "cat src/main.txt", 30)
+            .await
+            .unwrap();
+        assert_eq!(result.exit_code, Some(0));
+        assert_eq!(result.stdout, "new main");
+    }
+
+    let diffs = sandbox_service::diff_sandbox(&sandbox.id).await.unwrap();
+    let statuses: Vec<(&str, FileChangeStatus)> = diffs
+        .iter()
+        .map(|d| (d.file_path.as_str(), d.status.clone()))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            ("old.txt", FileChangeStatus::Deleted),
+            ("src/added.txt", FileChangeStatus::Added),
+            ("src/main.txt", FileChangeStatus::Modified),
+        ]
+    );
+
+    let promotion = sandbox_service::promote_sandbox(&sandbox.id).await.unwrap();
+    assert_eq!(promotion.promoted.len(), 3);
+    assert_eq!(promotion.warning, None);
+    assert_eq!(
+        fs::read_to_string(root.join("src/main.txt")).unwrap(),
+        "new main"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("src/added.txt")).unwrap(),
+        "added"
+    );
+    assert!(!root.join("old.txt").exists());
+    assert!(!sandbox_path.exists());
+    assert!(sandbox_service::diff_sandbox(&sandbox.id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_promotion_copies_bytes_and_can_be_reverted() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let original: &[u8] = &[0x89, b'P', b'N', b'G', 0xff, 0x00, 0xfe];
+    fs::write(root.join("image.bin"), original).unwrap();
+
+    let sandbox = sandbox_service::create_sandbox(root, SandboxKind::Copy)
+        .await
+        .unwrap();
+    let ops = review_service::process_markdown_changes(
+        "CREATE notes.txt\n```\nnotes\n```\n\nOVERWRITE image.bin\n```\ntext\n```\n",
+        root.to_str().unwrap(),
+    )
+    .await
+    .unwrap();
+    sandbox_service::apply_to_sandbox(&sandbox.id, ops)
+        .await
+        .unwrap();
+    // A command run in the sandbox turns the file back into binary content.
+    let promoted_bytes: &[u8] = &[0x89, b'P', b'N', b'G', 0xff, 0x01, 0xfe];
+    fs::write(
+        Path::new(&sandbox.sandbox_path).join("image.bin"),
+        promoted_bytes,
+    )
+    .unwrap();
+
+    let promotion = sandbox_service::promote_sandbox(&sandbox.id).await.unwrap();
+    assert_eq!(fs::read(root.join("notes.txt")).unwrap(), b"notes");
+    assert_eq!(fs::read(root.join("image.bin")).unwrap(), promoted_bytes);
+
+    review_service::revert_backup(&promotion.backup_id)
+        .await
+        .unwrap();
+    assert!(!root.join("notes.txt").exists());
+    assert_eq!(fs::read(root.join("image.bin")).unwrap(), original);
+}
+
+#[tokio::test]
+async fn test_worktree_sandbox_includes_uncommitted_changes() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    git(root, &["init", "-q"]);
+    write(root, "src/main.txt", "committed\n");
+    write(root, "old.txt", "old\n");
+    git(root, &["add", "."]);
+    git(root, &["commit", "-q", "-m", "initial"]);
+    write(root, "src/main.txt", "uncommitted\n");
+    write(root, "untracked.txt", "untracked\n");
+
+    let sandbox = sandbox_service::create_sandbox(root, SandboxKind::Worktree)
+        .await
+        .unwrap();
+    let sandbox_path = Path::new(&sandbox.sandbox_path).to_owned();
+    assert_eq!(
+        fs::read_to_string(sandbox_path.join("src/main.txt")).unwrap(),
+        "uncommitted\n"
+    );
+    assert_eq!(
+        fs::read_to_string(sandbox_path.join("untracked.txt")).unwrap(),
+        "untracked\n"
+    );
+
+    let ops = review_service::process_markdown_changes(CHANGES, root.to_str().unwrap())
+        .await
+        .unwrap();
+    sandbox_service::apply_to_sandbox(&sandbox.id, ops)
+        .await
+        .unwrap();
+    assert_eq!(
+        sandbox_service::diff_sandbox(&sandbox.id)
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+
+    sandbox_service::discard_sandbox(&sandbox.id).unwrap();
+    assert!(!sandbox_path.exists());
+    assert_eq!(
+        fs::read_to_string(root.join("src/main.txt")).unwrap(),
+        "uncommitted\n"
+    );
+}
+
+#[tokio::test]
+async fn test_promotion_refuses_files_changed_since_creation() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    write(root, "src/main.txt", "old main\n");
+    write(root, "old.txt", "old\n");
+
+    let sandbox = sandbox_service::create_sandbox(root, SandboxKind::Copy)
+        .await
+        .unwrap();
+    let ops = review_service::process_markdown_changes(CHANGES, root.to_str().unwrap())
+        .await
+        .unwrap();
+    sandbox_service::apply_to_sandbox(&sandbox.id, ops)
+        .await
+        .unwrap();
+    write(root, "src/main.txt", "edited meanwhile\n");
+
+    let error = sandbox_service::promote_sandbox(&sandbox.id)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("src/main.txt"), "{error}");
+    assert!(!error.contains("old.txt"), "{error}");
+    assert_eq!(
+        fs::read_to_string(root.join("src/main.txt")).unwrap(),
+        "edited meanwhile\n"
+    );
+    assert!(root.join("old.txt").exists());
+    assert!(!root.join("src/added.txt").exists());
+    // The sandbox is still there to inspect or discard.
+    sandbox_service::discard_sandbox(&sandbox.id).unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_sandboxes_link_ignored_directories() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    git(root, &["init", "-q"]);
+    write(root, ".gitignore", "target/\nnode_modules/\n");
+    write(root, "src/main.txt", "main\n");
+    write(root, "target/debug/app", "binary\n");
+    write(root, "web/node_modules/dep/index.js", "dep\n");
+    git(root, &["add", "."]);
+    git(root, &["commit", "-q", "-m", "initial"]);
+
+    for kind in [SandboxKind::Copy, SandboxKind::Worktree] {
+        let sandbox = sandbox_service::create_sandbox(root, kind).await.unwrap();
+        let sandbox_path = Path::new(&sandbox.sandbox_path).to_owned();
+        for dir in ["target", "web/node_modules"] {
+            assert!(
+                fs::symlink_metadata(sandbox_path.join(dir))
+                    .unwrap()
+                    .file_type()
+                    .is_symlink(),
+                "{dir}"
+            );
+        }
+        assert_eq!(
+            fs::read_to_string(sandbox_path.join("web/node_modules/dep/index.js")).unwrap(),
+            "dep\n"
+        );
+
+        sandbox_service::discard_sandbox(&sandbox.id).unwrap();
+        assert!(!sandbox_path.exists());
+        assert!(root.join("target/debug/app").exists());
+    }
+}
@@ -0,0 +1,103 @@
+use repo_wizard::services::{review_service, session_service};
+use repo_wizard::types::{ChangeOperation, OperationSource, OperationStatus};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+fn delete_op(file_path: &str) -> ChangeOperation {
+    ChangeOperation::Delete {
+        file_path: file_path.to_string(),
+        source: OperationSource::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_session_round_trip() {
+    let data_dir = tempdir().unwrap();
+    let project = tempdir().unwrap();
+    let root = project.path().to_str().unwrap();
+    fs::write(project.path().join("a.txt"), "a\n").unwrap();
+
+    let backup_id =
+        review_service::backup_files(project.path(), vec![PathBuf::from("a.txt")], None)
+            .await
+            .unwrap();
+    let session = session_service::create_session(
+        data_dir.path(),
+        root,
+        "DELETE a.txt\nDELETE b.txt\n".to_string(),
+        vec![delete_op("a.txt"), delete_op("b.txt")],
+        Some(backup_id.clone()),
+    )
+    .await
+    .unwrap();
+
+    let mut resumed = session_service::resume_session(data_dir.path(), root, &session.id)
+        .await
+        .unwrap();
+    assert_eq!(resumed, session);
+    assert!(resumed
+        .operations
+        .iter()
+        .all(|op| op.status == OperationStatus::Pending));
+
+    resumed.operations[0].status = OperationStatus::Applied;
+    // A re-parsed operation reverts to its own backup.
+    fs::write(project.path().join("b.txt"), "b\n").unwrap();
+    let rerun_backup_id =
+        review_service::backup_files(project.path(), vec![PathBuf::from("b.txt")], None)
+            .await
+            .unwrap();
+    resumed.operations[1].backup_id = Some(rerun_backup_id.clone());
+    session_service::save_session(data_dir.path(), resumed)
+        .await
+        .unwrap();
+
+    let sessions = session_service::list_sessions(data_dir.path(), root)
+        .await
+        .unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, session.id);
+    assert_eq!(
+        (sessions[0].pending_count, sessions[0].applied_count),
+        (1, 1)
+    );
+    assert_eq!(sessions[0].backup_id.as_deref(), Some(backup_id.as_str()));
+
+    // Sessions are scoped to their project.
+    let other = tempdir().unwrap();
+    assert!(
+        session_service::list_sessions(data_dir.path(), other.path().to_str().unwrap())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    session_service::discard_session(data_dir.path(), root, &session.id)
+        .await
+        .unwrap();
+    assert!(session_service::list_sessions(data_dir.path(), root)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(
+        review_service::read_file_from_backup(&backup_id, &PathBuf::from("a.txt"))
+            .await
+            .is_err()
+    );
+    assert!(
+        review_service::read_file_from_backup(&rerun_backup_id, &PathBuf::from("b.txt"))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_session_ids_are_validated() {
+    let data_dir = tempdir().unwrap();
+    assert!(
+        session_service::resume_session(data_dir.path(), "/project", "../escape")
+            .await
+            .is_err()
+    );
+}
@@ -0,0 +1,61 @@
+#![cfg(unix)]
+
+use repo_wizard::services::verification_service;
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_verification_links_diagnostics_to_changed_files() {
+    let temp_dir = tempdir().unwrap();
+    let command = "echo \"src/app.ts(3,1): error TS1005: ';' expected.\"; echo \"src/util.ts(9,2): error TS2304: Cannot find name 'x'.\"; exit 2";
+
+    let result = verification_service::run_verification(
+        temp_dir.path(),
+        command,
+        30,
+        vec!["src/util.ts".to_string()],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result.exit_code, Some(2));
+    assert!(!result.timed_out);
+    assert_eq!(result.diagnostics.len(), 2);
+    assert_eq!(result.diagnostics[0].file_path, "src/util.ts");
+    assert!(result.diagnostics[0].in_changed_file);
+    assert!(!result.diagnostics[1].in_changed_file);
+    assert!(result.context.contains("failed with exit code 2"));
+    assert!(result.context.contains(
+        "Diagnostics in the files that were just changed:\n- src/util.ts:9:2: error: TS2304: Cannot find name 'x'.\n"
+    ));
+}
+
+#[tokio::test]
+async fn test_verification_times_out() {
+    let temp_dir = tempdir().unwrap();
+
+    let result = verification_service::run_verification(temp_dir.path(), "sleep 5", 1, vec![])
+        .await
+        .unwrap();
+
+    assert!(result.timed_out);
+    assert_eq!(result.exit_code, None);
+    assert!(result.context.contains("timed out after 1s"));
+}
+
+#[tokio::test]
+async fn test_verification_includes_unparsed_output() {
+    let temp_dir = tempdir().unwrap();
+
+    let result = verification_service::run_verification(
+        temp_dir.path(),
+        "echo 'boom' >&2; exit 1",
+        30,
+        vec![],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result.stderr, "boom\n");
+    assert!(result.diagnostics.is_empty());
+    assert!(result.context.contains("Output:\n\n````\nboom\n````"));
+}
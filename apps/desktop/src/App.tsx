@@ -24,6 +24,12 @@ import { TabbedPanel } from './components/TabbedPanel';
 import { FileSearchModal } from './components/workspace/FileSearchModal';
 import { WorkspaceSidebar } from './components/workspace/WorkspaceSidebar';
 import { useKeyboardShortcuts } from './hooks/useKeyboardShortcuts';
+import { AppError } from './lib/error';
+import {
+  discardReviewSession,
+  listReviewSessions,
+  resumeReviewSession,
+} from './services/tauriApi';
 import { useDialogStore } from './store/dialogStore';
 import { useFileSearchStore } from './store/fileSearchStore';
 import { useReviewStore } from './store/reviewStore';
@@ -306,6 +312,38 @@ function App() {
     updateTitle();
   }, [rootPath]);
 
+  // Offer to pick up a review that was still open when the project was last closed.
+  useEffect(() => {
+    if (!rootPath) return;
+    const offerSessionRestore = async () => {
+      try {
+        const sessions = await listReviewSessions(rootPath);
+        const unfinished = sessions.find((s) => s.pendingCount > 0);
+        if (!unfinished || useReviewStore.getState().isReviewing) return;
+
+        const updatedAt = new Date(unfinished.updatedAt).toLocaleString();
+        const confirmed = await openDialog({
+          title: 'Restore Review?',
+          content: `A review last updated ${updatedAt} still has ${unfinished.pendingCount} pending and ${unfinished.appliedCount} applied change(s). Would you like to continue it?`,
+          type: 'confirm',
+          status: 'info',
+          confirmText: 'Restore',
+          cancelText: 'Discard',
+        });
+        if (useWorkspaceStore.getState().rootPath !== rootPath) return;
+        if (confirmed) {
+          const session = await resumeReviewSession(rootPath, unfinished.id);
+          useReviewStore.getState().resumeSession(session);
+        } else {
+          await discardReviewSession(rootPath, unfinished.id);
+        }
+      } catch (e) {
+        console.warn(new AppError('Failed to restore review session', e));
+      }
+    };
+    offerSessionRestore();
+  }, [rootPath, openDialog]);
+
   useEffect(() => {
     const showUpdateDialog = async () => {
       if (status === 'ready' && updateInfo) {
@@ -5,7 +5,7 @@
 
 
 export const commands = {
-async openProjectWindow(rootPath: string) : Promise<Result<null, string>> {
+async openProjectWindow(rootPath: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("open_project_window", { rootPath }) };
 } catch (e) {
@@ -13,7 +13,7 @@ async openProjectWindow(rootPath: string) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async createNewWindow() : Promise<Result<null, string>> {
+async createNewWindow() : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("create_new_window") };
 } catch (e) {
@@ -21,7 +21,7 @@ async createNewWindow() : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async registerWindowProject(rootPath: string | null) : Promise<Result<null, string>> {
+async registerWindowProject(rootPath: string | null) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("register_window_project", { rootPath }) };
 } catch (e) {
@@ -29,7 +29,7 @@ async registerWindowProject(rootPath: string | null) : Promise<Result<null, stri
     else return { status: "error", error: e  as any };
 }
 },
-async closeWindow() : Promise<Result<null, string>> {
+async closeWindow() : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("close_window") };
 } catch (e) {
@@ -37,15 +37,23 @@ async closeWindow() : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async listDirectoryRecursive(path: string, settings: IgnoreSettings) : Promise<Result<FileNode, string>> {
+async listDirectoryRecursive(path: string, settings: IgnoreSettings, withMetadata: boolean | null) : Promise<Result<FileNode, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("list_directory_recursive", { path, settings }) };
+    return { status: "ok", data: await TAURI_INVOKE("list_directory_recursive", { path, settings, withMetadata }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getRelativePath(fullPath: string, rootPath: string) : Promise<Result<string, string>> {
+async listDirectory(rootPath: string, path: string, settings: IgnoreSettings, depth: number | null) : Promise<Result<FileNode, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_directory", { rootPath, path, settings, depth }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRelativePath(fullPath: string, rootPath: string) : Promise<Result<string, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_relative_path", { fullPath, rootPath }) };
 } catch (e) {
@@ -53,7 +61,7 @@ async getRelativePath(fullPath: string, rootPath: string) : Promise<Result<strin
     else return { status: "error", error: e  as any };
 }
 },
-async readFileContent(path: string) : Promise<Result<string, string>> {
+async readFileContent(path: string) : Promise<Result<string, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("read_file_content", { path }) };
 } catch (e) {
@@ -61,7 +69,7 @@ async readFileContent(path: string) : Promise<Result<string, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async readFileAsBase64(path: string) : Promise<Result<string, string>> {
+async readFileAsBase64(path: string) : Promise<Result<string, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("read_file_as_base64", { path }) };
 } catch (e) {
@@ -69,7 +77,7 @@ async readFileAsBase64(path: string) : Promise<Result<string, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async countTokens(text: string) : Promise<Result<number, string>> {
+async countTokens(text: string) : Promise<Result<number, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("count_tokens", { text }) };
 } catch (e) {
@@ -77,7 +85,7 @@ async countTokens(text: string) : Promise<Result<number, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async countTokensForFiles(paths: string[]) : Promise<Result<FileTokenInfo[], string>> {
+async countTokensForFiles(paths: string[]) : Promise<Result<FileTokenInfo[], string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("count_tokens_for_files", { paths }) };
 } catch (e) {
@@ -85,7 +93,7 @@ async countTokensForFiles(paths: string[]) : Promise<Result<FileTokenInfo[], str
     else return { status: "error", error: e  as any };
 }
 },
-async estimatePromptTokens(input: PromptEstimateInput) : Promise<Result<PromptEstimateResult, string>> {
+async estimatePromptTokens(input: PromptEstimateInput) : Promise<Result<PromptEstimateResult, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("estimate_prompt_tokens", { input }) };
 } catch (e) {
@@ -93,7 +101,7 @@ async estimatePromptTokens(input: PromptEstimateInput) : Promise<Result<PromptEs
     else return { status: "error", error: e  as any };
 }
 },
-async isBinaryFile(path: string) : Promise<Result<boolean, string>> {
+async isBinaryFile(path: string) : Promise<Result<boolean, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("is_binary_file", { path }) };
 } catch (e) {
@@ -101,7 +109,7 @@ async isBinaryFile(path: string) : Promise<Result<boolean, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async fileExists(path: string) : Promise<Result<boolean, string>> {
+async fileExists(path: string) : Promise<Result<boolean, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("file_exists", { path }) };
 } catch (e) {
@@ -109,7 +117,7 @@ async fileExists(path: string) : Promise<Result<boolean, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async writeFileContent(path: string, content: string, rootPath: string) : Promise<Result<null, string>> {
+async writeFileContent(path: string, content: string, rootPath: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("write_file_content", { path, content, rootPath }) };
 } catch (e) {
@@ -117,7 +125,31 @@ async writeFileContent(path: string, content: string, rootPath: string) : Promis
     else return { status: "error", error: e  as any };
 }
 },
-async deleteFile(filePath: string, rootPath: string) : Promise<Result<null, string>> {
+async setSymlinkPolicy(rootPath: string, policy: SymlinkPolicy) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_symlink_policy", { rootPath, policy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setProtectedPaths(rootPath: string, rules: ProtectedPathRule[]) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_protected_paths", { rootPath, rules }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getProtectedPaths(rootPath: string) : Promise<Result<ProtectedPathRule[], string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_protected_paths", { rootPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteFile(filePath: string, rootPath: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("delete_file", { filePath, rootPath }) };
 } catch (e) {
@@ -125,7 +157,7 @@ async deleteFile(filePath: string, rootPath: string) : Promise<Result<null, stri
     else return { status: "error", error: e  as any };
 }
 },
-async moveFile(from: string, to: string, rootPath: string) : Promise<Result<null, string>> {
+async moveFile(from: string, to: string, rootPath: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("move_file", { from, to, rootPath }) };
 } catch (e) {
@@ -133,15 +165,15 @@ async moveFile(from: string, to: string, rootPath: string) : Promise<Result<null
     else return { status: "error", error: e  as any };
 }
 },
-async backupFiles(rootPath: string, filePaths: string[]) : Promise<Result<string, string>> {
+async backupFiles(rootPath: string, filePaths: string[], summary: ChangeSummary | null) : Promise<Result<string, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("backup_files", { rootPath, filePaths }) };
+    return { status: "ok", data: await TAURI_INVOKE("backup_files", { rootPath, filePaths, summary }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async revertFileFromBackup(rootPath: string, backupId: string, relativePath: string) : Promise<Result<null, string>> {
+async revertFileFromBackup(rootPath: string, backupId: string, relativePath: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("revert_file_from_backup", { rootPath, backupId, relativePath }) };
 } catch (e) {
@@ -149,7 +181,7 @@ async revertFileFromBackup(rootPath: string, backupId: string, relativePath: str
     else return { status: "error", error: e  as any };
 }
 },
-async readFileFromBackup(backupId: string, relativePath: string) : Promise<Result<string, string>> {
+async readFileFromBackup(backupId: string, relativePath: string) : Promise<Result<string, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("read_file_from_backup", { backupId, relativePath }) };
 } catch (e) {
@@ -157,7 +189,7 @@ async readFileFromBackup(backupId: string, relativePath: string) : Promise<Resul
     else return { status: "error", error: e  as any };
 }
 },
-async deleteBackup(backupId: string) : Promise<Result<null, string>> {
+async deleteBackup(backupId: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("delete_backup", { backupId }) };
 } catch (e) {
@@ -165,7 +197,103 @@ async deleteBackup(backupId: string) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async parseChangesFromMarkdown(markdown: string, rootPath: string) : Promise<Result<ChangeOperation[], string>> {
+async listBackups(rootPath: string) : Promise<Result<BackupManifest[], string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_backups", { rootPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getBackup(backupId: string) : Promise<Result<BackupManifest, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_backup", { backupId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async revertBackup(backupId: string) : Promise<Result<string[], string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("revert_backup", { backupId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async recordHistoryEntry(rootPath: string, backupId: string, summary: ChangeSummary | null) : Promise<Result<ProjectHistory, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("record_history_entry", { rootPath, backupId, summary }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getHistory(rootPath: string) : Promise<Result<ProjectHistory, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_history", { rootPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async undoChangeSet(rootPath: string) : Promise<Result<HistoryStepResult, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("undo_change_set", { rootPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async redoChangeSet(rootPath: string) : Promise<Result<HistoryStepResult, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("redo_change_set", { rootPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createReviewSession(rootPath: string, rawResponse: string, operations: ChangeOperation[], backupId: string | null) : Promise<Result<ReviewSession, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_review_session", { rootPath, rawResponse, operations, backupId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async saveReviewSession(session: ReviewSession) : Promise<Result<ReviewSession, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_review_session", { session }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listReviewSessions(rootPath: string) : Promise<Result<ReviewSessionInfo[], string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_review_sessions", { rootPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async resumeReviewSession(rootPath: string, sessionId: string) : Promise<Result<ReviewSession, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("resume_review_session", { rootPath, sessionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async discardReviewSession(rootPath: string, sessionId: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("discard_review_session", { rootPath, sessionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async parseChangesFromMarkdown(markdown: string, rootPath: string) : Promise<Result<ReviewResult, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("parse_changes_from_markdown", { markdown, rootPath }) };
 } catch (e) {
@@ -173,7 +301,135 @@ async parseChangesFromMarkdown(markdown: string, rootPath: string) : Promise<Res
     else return { status: "error", error: e  as any };
 }
 },
-async isGitRepository(path: string) : Promise<Result<boolean, string>> {
+async trackReview(rootPath: string, markdown: string, operations: ChangeOperation[]) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("track_review", { rootPath, markdown, operations }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async untrackReview(rootPath: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("untrack_review", { rootPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async rerunReview(rootPath: string, filePaths: string[]) : Promise<Result<ChangeOperation[], string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rerun_review", { rootPath, filePaths }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async applySelectedHunks(rootPath: string, operation: ChangeOperation, hunkIds: string[]) : Promise<Result<string, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("apply_selected_hunks", { rootPath, operation, hunkIds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async formatProposedChanges(rootPath: string, operations: ChangeOperation[], formatters: FormatterConfig[]) : Promise<Result<FormattedChanges, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("format_proposed_changes", { rootPath, operations, formatters }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async formatFiles(rootPath: string, paths: string[], formatters: FormatterConfig[]) : Promise<Result<FormatterResult[], string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("format_files", { rootPath, paths, formatters }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async runVerification(rootPath: string, command: string, timeoutSecs: number, changedFiles: string[]) : Promise<Result<VerificationResult, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("run_verification", { rootPath, command, timeoutSecs, changedFiles }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createSandbox(rootPath: string, kind: SandboxKind) : Promise<Result<SandboxInfo, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_sandbox", { rootPath, kind }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async applyToSandbox(sandboxId: string, operations: ChangeOperation[]) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("apply_to_sandbox", { sandboxId, operations }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async runInSandbox(sandboxId: string, command: string, timeoutSecs: number) : Promise<Result<VerificationResult, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("run_in_sandbox", { sandboxId, command, timeoutSecs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async diffSandbox(sandboxId: string) : Promise<Result<SandboxFileDiff[], string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("diff_sandbox", { sandboxId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async promoteSandbox(sandboxId: string) : Promise<Result<SandboxPromotion, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("promote_sandbox", { sandboxId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async discardSandbox(sandboxId: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("discard_sandbox", { sandboxId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async buildContinuationPrompt(markdown: string) : Promise<Result<string, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("build_continuation_prompt", { markdown }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async mergeContinuation(markdown: string, continuation: string) : Promise<Result<string, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("merge_continuation", { markdown, continuation }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async buildRepairPrompt(rootPath: string, operations: ChangeOperation[], editFormat: EditFormat) : Promise<Result<RepairPrompt, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("build_repair_prompt", { rootPath, operations, editFormat }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async isGitRepository(path: string) : Promise<Result<boolean, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("is_git_repository", { path }) };
 } catch (e) {
@@ -181,7 +437,7 @@ async isGitRepository(path: string) : Promise<Result<boolean, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async getGitStatus(repoPath: string) : Promise<Result<GitStatus, string>> {
+async getGitStatus(repoPath: string) : Promise<Result<GitStatus, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_git_status", { repoPath }) };
 } catch (e) {
@@ -189,7 +445,7 @@ async getGitStatus(repoPath: string) : Promise<Result<GitStatus, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async getRecentCommits(repoPath: string, count: number) : Promise<Result<Commit[], string>> {
+async getRecentCommits(repoPath: string, count: number) : Promise<Result<Commit[], string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_recent_commits", { repoPath, count }) };
 } catch (e) {
@@ -197,7 +453,7 @@ async getRecentCommits(repoPath: string, count: number) : Promise<Result<Commit[
     else return { status: "error", error: e  as any };
 }
 },
-async getGitDiff(repoPath: string, option: DiffOption) : Promise<Result<string, string>> {
+async getGitDiff(repoPath: string, option: DiffOption) : Promise<Result<string, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_git_diff", { repoPath, option }) };
 } catch (e) {
@@ -205,7 +461,7 @@ async getGitDiff(repoPath: string, option: DiffOption) : Promise<Result<string,
     else return { status: "error", error: e  as any };
 }
 },
-async resolvePath(path: string, cwd: string | null) : Promise<Result<string, string>> {
+async resolvePath(path: string, cwd: string | null) : Promise<Result<string, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("resolve_path", { path, cwd }) };
 } catch (e) {
@@ -213,7 +469,7 @@ async resolvePath(path: string, cwd: string | null) : Promise<Result<string, str
     else return { status: "error", error: e  as any };
 }
 },
-async startPtySession(rootPath: string, command: string | null, onEvent: TAURI_CHANNEL<CommandStreamEvent>) : Promise<Result<null, string>> {
+async startPtySession(rootPath: string, command: string | null, onEvent: TAURI_CHANNEL<CommandStreamEvent>) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("start_pty_session", { rootPath, command, onEvent }) };
 } catch (e) {
@@ -221,7 +477,7 @@ async startPtySession(rootPath: string, command: string | null, onEvent: TAURI_C
     else return { status: "error", error: e  as any };
 }
 },
-async resizePty(rows: number, cols: number) : Promise<Result<null, string>> {
+async resizePty(rows: number, cols: number) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("resize_pty", { rows, cols }) };
 } catch (e) {
@@ -229,7 +485,7 @@ async resizePty(rows: number, cols: number) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async writeToPty(text: string) : Promise<Result<null, string>> {
+async writeToPty(text: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("write_to_pty", { text }) };
 } catch (e) {
@@ -237,7 +493,7 @@ async writeToPty(text: string) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async killPty() : Promise<Result<null, string>> {
+async killPty() : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("kill_pty") };
 } catch (e) {
@@ -245,7 +501,7 @@ async killPty() : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async getCliStatus() : Promise<Result<CliStatusResult, string>> {
+async getCliStatus() : Promise<Result<CliStatusResult, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_cli_status") };
 } catch (e) {
@@ -253,7 +509,7 @@ async getCliStatus() : Promise<Result<CliStatusResult, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async installCliShim() : Promise<Result<CliInstallResult, string>> {
+async installCliShim() : Promise<Result<CliInstallResult, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("install_cli_shim") };
 } catch (e) {
@@ -261,7 +517,31 @@ async installCliShim() : Promise<Result<CliInstallResult, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async startWatching(rootPath: string, settings: IgnoreSettings) : Promise<Result<null, string>> {
+async loadProjectConfig(rootPath: string) : Promise<Result<ProjectConfigStatus, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("load_project_config", { rootPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async resolveProjectSettings(rootPath: string, settings: ProjectSettings) : Promise<Result<ResolvedProjectSettings, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("resolve_project_settings", { rootPath, settings }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async approveProjectFormatters(rootPath: string, fingerprint: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("approve_project_formatters", { rootPath, fingerprint }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async startWatching(rootPath: string, settings: IgnoreSettings) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("start_watching", { rootPath, settings }) };
 } catch (e) {
@@ -269,7 +549,7 @@ async startWatching(rootPath: string, settings: IgnoreSettings) : Promise<Result
     else return { status: "error", error: e  as any };
 }
 },
-async stopWatching(rootPath: string) : Promise<Result<null, string>> {
+async stopWatching(rootPath: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("stop_watching", { rootPath }) };
 } catch (e) {
@@ -277,7 +557,23 @@ async stopWatching(rootPath: string) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async searchFiles(query: string, rootPath: string, settings: IgnoreSettings, limit: number | null) : Promise<Result<SearchResult[], string>> {
+async restartWatcher(rootPath: string) : Promise<Result<null, string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("restart_watcher", { rootPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listWatchers() : Promise<Result<WatcherInfo[], string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_watchers") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async searchFiles(query: string, rootPath: string, settings: IgnoreSettings, limit: number | null) : Promise<Result<SearchResult[], string | { message: string; violation: ProtectedPathViolation } | { message: string; watchLimit: WatchLimitExceeded }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("search_files", { query, rootPath, settings, limit }) };
 } catch (e) {
@@ -290,6 +586,16 @@ async searchFiles(query: string, rootPath: string, settings: IgnoreSettings, lim
 /** user-defined events **/
 
 
+export const events = __makeEvents__<{
+fsChangeEvent: FsChangeEvent,
+projectConfigChangedEvent: ProjectConfigChangedEvent,
+reviewTargetChangedEvent: ReviewTargetChangedEvent
+}>({
+fsChangeEvent: "fs-change-event",
+projectConfigChangedEvent: "project-config-changed-event",
+reviewTargetChangedEvent: "review-target-changed-event"
+})
+
 
 /** user-defined constants **/
 
@@ -297,29 +603,216 @@ async searchFiles(query: string, rootPath: string, settings: IgnoreSettings, lim
 
 /** user-defined types **/
 
-export type ChangeOperation = { type: "patch"; filePath: string; content: string; isNewFile: boolean; totalBlocks: number; appliedBlocks: number } | { type: "overwrite"; filePath: string; content: string; isNewFile: boolean } | { type: "delete"; filePath: string } | { type: "move"; fromPath: string; toPath: string }
+/**
+ * 1-based position of a syntax error in the proposed content.
+ */
+export type SyntaxError = { line: number; column: number; message: string }
+/**
+ * A file that no longer matches the state an undo or redo expects.
+ */
+export type HistoryConflict = { filePath: string; kind: ConflictKind }
+/**
+ * A file under review whose content is no longer what the review was parsed against, nor what
+ * it would write. A `None` hash means the file does not exist.
+ */
+export type ReviewTargetChange = { filePath: string; expectedHash: string | null; currentHash: string | null }
+/**
+ * A formatter command for files matching a gitignore-style `glob`. The file path is appended
+ * to `command`, or substituted for `{file}` when present.
+ */
+export type FormatterConfig = { glob: string; command: string }
+/**
+ * A gitignore-style pattern, relative to the project root, for paths responses may not touch.
+ */
+export type ProtectedPathRule = { pattern: string; level: ProtectionLevel }
+/**
+ * A path relative to the backup's project root and the SHA-256 of its content blob, or no hash
+ * if the path did not exist when the backup was taken.
+ */
+export type BackupFile = { path: string; hash: string | null }
+/**
+ * A project's file watcher, the windows using it and its health.
+ */
+export type WatcherInfo = { rootPath: string; windowLabels: string[]; settings: IgnoreSettings; status: WatcherStatus; lastError: string | null; restarts: number; startedAt: number; lastEventAt: number | null }
+/**
+ * A review in progress, persisted so it can be resumed after a restart. Timestamps are
+ * milliseconds since the Unix epoch.
+ */
+export type ReviewSession = { id: string; rootPath: string; rawResponse: string; operations: SessionOperation[]; backupId: string | null; createdAt: number; updatedAt: number }
+/**
+ * A unified-diff style hunk with surrounding context; line numbers are 1-based.
+ */
+export type DiffHunk = { oldStart: number; oldLines: number; newStart: number; newLines: number; lines: DiffLine[] }
+/**
+ * An applied change set that can be undone by restoring `before_backup_id` and redone by
+ * restoring `after_backup_id`.
+ */
+export type HistoryEntry = { id: string; createdAt: number; summary: ChangeSummary | null; files: string[]; beforeBackupId: string; afterBackupId: string }
+/**
+ * Entries before `position` are applied; the rest can be redone.
+ */
+export type ProjectHistory = { entries: HistoryEntry[]; position: number }
+/**
+ * Files of the review open for `root_path` that were changed outside the review.
+ */
+export type ReviewTargetChangedEvent = { rootPath: string; changes: ReviewTargetChange[] }
+/**
+ * For directories, `size`, `tokens` and `modifiedAt` are rolled up from the files below.
+ */
+export type FileMetadata = { size: number; modifiedAt: number | null; isBinary: boolean | null; 
+/**
+ * Two-letter `git status --porcelain` code, e.g. ` M` or `??`.
+ */
+gitStatus: string | null; 
+/**
+ * Only counts that are already cached; files that were never counted are left out.
+ */
+tokens: number | null; fileCount: number | null; dirtyCount: number | null }
+/**
+ * Formatter commands from a project's config file, which only run once the user has approved
+ * them. `fingerprint` identifies the exact set of globs and commands that was shown.
+ */
+export type FormatterConsent = { fingerprint: string; formatters: FormatterConfig[]; approved: boolean }
+/**
+ * Location in the original response: a half-open byte range and 1-based, inclusive lines.
+ */
+export type SourceSpan = { startByte: number; endByte: number; startLine: number; endLine: number }
+/**
+ * One independently selectable change, as a replacement of `old_lines` lines starting at
+ * 1-based `old_start` in the original file. `new_start` is its position with all hunks applied.
+ */
+export type Hunk = { id: string; oldStart: number; oldLines: number; newStart: number; newLines: number; oldText: string; newText: string }
+/**
+ * Operations on denied paths are left out of `operations`; `violations` lists them along with
+ * warnings for the operations that were kept.
+ */
+export type ReviewResult = { operations: ChangeOperation[]; summary: ChangeSummary; violations: ProtectedPathViolation[] }
+/**
+ * Outcome of a check command; `context` summarizes it for inclusion in a follow-up prompt.
+ */
+export type VerificationResult = { command: string; exitCode: number | null; timedOut: boolean; stdout: string; stderr: string; diagnostics: Diagnostic[]; context: string }
+/**
+ * Set when the response was cut off in this operation, inside its code block or before it.
+ * Such an operation is not applied until a continuation has been merged.
+ * `resume_after_line` is the 1-based response line after which a continuation is merged.
+ */
+export type Truncation = { resumeAfterLine: number; lastCompleteLine: string }
+/**
+ * The OS ran out of file watches while watching `root_path`.
+ */
+export type WatchLimitExceeded = { rootPath: string; maxUserWatches: number | null }
+/**
+ * The config file of the project at `root_path` changed and has been reloaded.
+ */
+export type ProjectConfigChangedEvent = { rootPath: string }
+/**
+ * The contents of a project's `.repowizard.toml`.
+ */
+export type ProjectConfig = { ignore: ProjectIgnoreConfig; formatters: FormatterConfig[] | null; protectedPaths: ProtectedPathRule[] | null; metaPrompts: MetaPrompt[]; tokenBudget: number | null }
+/**
+ * The settings a project's config file can override.
+ */
+export type ProjectSettings = { ignore: IgnoreSettings; formatters: FormatterConfig[]; metaPrompts: MetaPrompt[]; tokenBudget: number | null }
+/**
+ * Unignored paths under `root_path` that changed, as reported by the file watcher.
+ */
+export type FsChangeEvent = { rootPath: string; created: string[]; modified: string[]; removed: string[]; renamed: RenamedPath[] }
+/**
+ * Whether file operations may go through symlinks. Even when they are followed, the resolved
+ * path must stay inside the project root.
+ */
+export type SymlinkPolicy = "follow" | "deny"
+/**
+ * `config` is `None` when the file is missing or invalid.
+ */
+export type ProjectConfigStatus = { path: string; exists: boolean; config: ProjectConfig | null; errors: ProjectConfigError[] }
+/**
+ * `created_at` is in milliseconds since the Unix epoch.
+ */
+export type BackupManifest = { id: string; rootPath: string; createdAt: number; summary: ChangeSummary | null; files: BackupFile[] }
+/**
+ * `original_valid` is `None` when the file is new.
+ */
+export type SyntaxCheck = { language: string; errors: SyntaxError[]; originalValid: boolean | null }
+export type ChangeOperation = { type: "patch"; filePath: string; content: string; isNewFile: boolean; totalBlocks: number; appliedBlocks: number; blocks: PatchBlock[]; hunks: Hunk[]; originalHash: string | null; diff: DiffHunk[]; stats: LineStats; pathSuggestions: PathSuggestion[]; source: OperationSource; truncation: Truncation | null; syntax: SyntaxCheck | null } | { type: "overwrite"; filePath: string; content: string; isNewFile: boolean; hunks: Hunk[]; originalHash: string | null; diff: DiffHunk[]; stats: LineStats; pathSuggestions: PathSuggestion[]; source: OperationSource; truncation: Truncation | null; syntax: SyntaxCheck | null } | { type: "delete"; filePath: string; source: OperationSource } | { type: "move"; fromPath: string; toPath: string; source: OperationSource }
+export type ChangeSummary = { filesCreated: number; filesModified: number; filesDeleted: number; filesMoved: number; linesAdded: number; linesRemoved: number; totalBlocks: number; appliedBlocks: number }
 export type CliInstallResult = { message: string }
 export type CliStatus = "installed" | "not_installed" | "error"
 export type CliStatusResult = { status: CliStatus; error: string | null }
 export type CommandStreamEvent = { type: "stdout"; data: number[] } | { type: "stderr"; data: number[] } | { type: "error"; data: string } | { type: "finish"; data: string }
 export type Commit = { hash: string; message: string; author: string; date: string }
 export type ComposerMode = "edit" | "qa"
+export type ConflictKind = "modified" | "created" | "deleted"
+export type Diagnostic = { filePath: string; line: number | null; column: number | null; severity: DiagnosticSeverity; message: string; inChangedFile: boolean }
+export type DiagnosticSeverity = "error" | "warning"
+export type DiffLine = { kind: DiffLineKind; content: string }
+export type DiffLineKind = "context" | "added" | "removed"
 export type DiffOption = { type: "workspace" } | { type: "commit"; hash: string }
 export type EditFormat = "diff" | "whole"
-export type FileNode = { path: string; name: string; children?: FileNode[] | null; isDirectory: boolean }
+export type FileChangeStatus = "added" | "modified" | "deleted"
+export type FileNode = { path: string; name: string; children?: FileNode[] | null; isDirectory: boolean; 
+/**
+ * Set on directories from a depth-limited listing, including those whose children were
+ * not loaded.
+ */
+childCount?: number | null; metadata?: FileMetadata | null }
 export type FileTokenInfo = { path: string; exists: boolean; isBinary: boolean; tokens: number }
 export type FileTreeConfig = { scope: FileTreeScope; maxFilesPerDirectory: number | null; ignorePatterns: string }
 export type FileTreeScope = "all" | "selected"
+export type FormattedChanges = { operations: ChangeOperation[]; results: FormatterResult[] }
+export type FormatterResult = { filePath: string; command: string; success: boolean; error: string | null }
 export type GitStatus = { hasStagedChanges: boolean; hasUnstagedChanges: boolean }
+export type HistoryStepResult = { applied: boolean; conflicts: HistoryConflict[]; history: ProjectHistory }
 export type IgnoreSettings = { respectGitignore: boolean; customIgnorePatterns: string }
+export type LineStats = { added: number; removed: number }
 export type MagicPromptType = "file-tree" | "git-diff" | "terminal-command"
 export type MetaPrompt = { id: string; name: string; content: string; mode: PromptMode; promptType: PromptType; magicType: MagicPromptType | null; fileTreeConfig: FileTreeConfig | null; gitDiffConfig: DiffOption | null; terminalCommandConfig: TerminalCommandConfig | null; enabled: boolean }
+export type OperationSource = { span: SourceSpan; explanation: string }
+export type OperationStatus = "pending" | "applied" | "error" | "identical"
+export type PatchBlock = { search: string; replace: string; applied: boolean; span: SourceSpan }
+export type PathSuggestion = { filePath: string; reason: PathSuggestionReason; score: number; matchedBlocks: number; content: string | null }
+export type PathSuggestionReason = "contentMatch" | "similarPath"
+export type ProjectConfigError = { line: number; column: number; message: string }
+export type ProjectIgnoreConfig = { respectGitignore: boolean | null; patterns: string[] }
 export type PromptEstimateInput = { selectedFilePaths: string[]; instructions: string; customSystemPrompt: string; editFormat: EditFormat; composerMode: ComposerMode; metaPrompts: MetaPrompt[]; rootPath: string | null; fileTree: FileNode | null; ignoreSettings: IgnoreSettings | null }
 export type PromptEstimateResult = { totalTokens: number; missingPaths: string[] }
 export type PromptMode = "universal" | "edit" | "qa"
 export type PromptType = "meta" | "magic"
+export type ProtectedPathViolation = { filePath: string; pattern: string; level: ProtectionLevel }
+export type ProtectionLevel = "deny" | "warn"
+export type RenamedPath = { from: string; to: string }
+export type RepairPrompt = { prompt: string; failedBlocks: number; totalTokens: number }
+export type ResolvedProjectSettings = { settings: ProjectSettings; config: ProjectConfigStatus; 
+/**
+ * Set when the config file defines formatters.
+ */
+formatterConsent: FormatterConsent | null }
+export type ReviewSessionInfo = { id: string; rootPath: string; backupId: string | null; createdAt: number; updatedAt: number; pendingCount: number; appliedCount: number }
+export type SandboxFileDiff = { filePath: string; status: FileChangeStatus; diff: DiffHunk[]; stats: LineStats }
+export type SandboxInfo = { id: string; rootPath: string; sandboxPath: string; kind: SandboxKind }
+export type SandboxKind = "worktree" | "copy"
+export type SandboxPromotion = { 
+/**
+ * Paths relative to the project root that were written or removed.
+ */
+promoted: string[]; 
+/**
+ * Backup of those paths as they were before promotion.
+ */
+backupId: string; 
+/**
+ * Set when the changes were promoted but the sandbox could not be cleaned up.
+ */
+warning: string | null }
 export type SearchResult = { path: string; relativePath: string; name: string; parentDir: string; score: number; isDirectory: boolean }
+export type SessionOperation = { operation: ChangeOperation; status: OperationStatus; error: string | null; 
+/**
+ * The backup this operation reverts to, when it was re-parsed after the session's backup
+ * was taken.
+ */
+backupId?: string | null }
 export type TerminalCommandConfig = { command: string }
+export type WatcherStatus = "running" | "restarting" | "limitReached" | "failed"
 
 /** tauri-specta globals **/
 
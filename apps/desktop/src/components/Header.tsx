@@ -3,12 +3,16 @@ import {
   ArrowUpCircle,
   CheckCircle,
   DownloadCloud,
+  Redo2,
   RefreshCw,
   Settings,
+  Undo2,
 } from 'lucide-react';
-import { useState } from 'react';
+import { useEffect, useState } from 'react';
 import { cn } from '../lib/utils';
+import { useChangeHistoryStore } from '../store/changeHistoryStore';
 import { useUpdateStore } from '../store/updateStore';
+import { useWorkspaceStore } from '../store/workspaceStore';
 import { Button } from './common/Button';
 import { SettingsModal } from './common/SettingsModal';
 
@@ -78,6 +82,48 @@ function VersionStatus() {
   );
 }
 
+function ChangeHistoryControls() {
+  const rootPath = useWorkspaceStore((s) => s.rootPath);
+  const { history, isStepping, load, undo, redo } = useChangeHistoryStore();
+
+  useEffect(() => {
+    if (rootPath) load(rootPath);
+  }, [rootPath, load]);
+
+  if (!rootPath || !history) return null;
+
+  const { entries, position } = history;
+  const describe = (index: number) => {
+    const entry = entries[index];
+    return entry ? ` (${entry.files.join(', ')})` : '';
+  };
+  const iconButtonStyle =
+    'p-1.5 rounded-md hover:bg-gray-100 text-gray-600 hover:text-gray-900 dark:hover:bg-gray-700 dark:text-gray-400 dark:hover:text-gray-100 disabled:opacity-40 disabled:pointer-events-none';
+
+  return (
+    <>
+      <button
+        type="button"
+        onClick={undo}
+        disabled={isStepping || position === 0}
+        className={iconButtonStyle}
+        title={`Undo applied change set${describe(position - 1)}`}
+      >
+        <Undo2 size={18} />
+      </button>
+      <button
+        type="button"
+        onClick={redo}
+        disabled={isStepping || position >= entries.length}
+        className={iconButtonStyle}
+        title={`Redo change set${describe(position)}`}
+      >
+        <Redo2 size={18} />
+      </button>
+    </>
+  );
+}
+
 export function Header() {
   const [isSettingsModalOpen, setIsSettingsModalOpen] = useState(false);
 
@@ -88,6 +134,7 @@ export function Header() {
           Repo Wizard
         </h1>
         <div className="flex items-center gap-2">
+          <ChangeHistoryControls />
           <VersionStatus />
           <button
             type="button"
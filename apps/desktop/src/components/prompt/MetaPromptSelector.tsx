@@ -10,7 +10,10 @@ import { AnimatePresence, motion } from 'motion/react';
 import { useEffect, useMemo, useRef, useState } from 'react';
 import { cn } from '../../lib/utils';
 import { useComposerStore } from '../../store/composerStore';
-import { useSettingsStore } from '../../store/settingsStore';
+import {
+  selectMetaPrompts,
+  useSettingsStore,
+} from '../../store/settingsStore';
 import type { MetaPrompt } from '../../types/prompt';
 import { Button } from '../common/Button';
 import { Checkbox } from '../common/Checkbox';
@@ -75,7 +78,7 @@ export function MetaPromptSelector({
   const [isSelectorOpen, setIsSelectorOpen] = useState(false);
   const selectorContainerRef = useRef<HTMLDivElement>(null);
 
-  const { metaPrompts: promptDefs } = useSettingsStore();
+  const promptDefs = useSettingsStore(selectMetaPrompts);
   const { enabledMetaPromptIds, setEnabledMetaPromptIds } = useComposerStore();
 
   const allPromptsForMode = useMemo<MetaPrompt[]>(() => {
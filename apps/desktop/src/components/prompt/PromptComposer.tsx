@@ -66,6 +66,9 @@ export function PromptComposer() {
 
   const { estimatedTokens, generateAndCopyPrompt, isCopied, isGenerating } =
     usePromptGenerator();
+  const tokenBudget = useSettingsStore(
+    (s) => s.projectSettings?.tokenBudget ?? null
+  );
   const {
     startReview,
     reenterReview,
@@ -249,8 +252,16 @@ export function PromptComposer() {
           onUndo={undoInstructions}
           onRedo={redoInstructions}
         />
-        <div className="text-right text-xs text-gray-500 dark:text-gray-400 mb-2">
+        <div
+          className={cn(
+            'text-right text-xs mb-2',
+            tokenBudget !== null && estimatedTokens > tokenBudget
+              ? 'text-red-600 dark:text-red-400'
+              : 'text-gray-500 dark:text-gray-400'
+          )}
+        >
           Estimated Tokens: ~{formatTokenCount(estimatedTokens)}
+          {tokenBudget !== null && ` / ${formatTokenCount(tokenBudget)}`}
         </div>
         <Button
           onClick={() => generateAndCopyPrompt()}
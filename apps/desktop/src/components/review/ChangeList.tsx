@@ -3,12 +3,13 @@ import {
   Check,
   CheckCheck,
   CircleDot,
+  RefreshCw,
   Undo,
 } from 'lucide-react';
 import { useReviewSession } from '../../hooks/useReviewSession';
 import { cn } from '../../lib/utils';
 import { useDialogStore } from '../../store/dialogStore';
-import { useReviewStore } from '../../store/reviewStore';
+import { isRerunnable, useReviewStore } from '../../store/reviewStore';
 import { useWorkspaceStore } from '../../store/workspaceStore';
 import type { ReviewChange } from '../../types/review';
 import { Button } from '../common/Button';
@@ -233,6 +234,63 @@ const ChangeItem = ({ change }: { change: ReviewChange }) => {
   );
 };
 
+const describeFiles = (paths: string[]) =>
+  paths.length === 1 ? `${paths[0]} was` : `${paths.length} files were`;
+
+const StaleTargetsNotice = () => {
+  const { changes, changedTargetPaths, rerunChangedTargets } =
+    useReviewStore();
+  if (changedTargetPaths.length === 0) return null;
+
+  const rerunnable = changedTargetPaths.filter((path) =>
+    isRerunnable(changes, path)
+  );
+  const manual = changedTargetPaths.filter(
+    (path) => !rerunnable.includes(path)
+  );
+  return (
+    <div className="mb-2 p-2 bg-yellow-50 dark:bg-yellow-500/10 text-yellow-800 dark:text-yellow-200 rounded-md text-xs flex-shrink-0">
+      {rerunnable.length > 0 && (
+        <>
+          <div className="flex items-start gap-2">
+            <AlertTriangle size={14} className="flex-shrink-0 mt-0.5" />
+            <div className="min-w-0 break-words">
+              {describeFiles(rerunnable)} changed on disk after this review
+              was parsed. Applying may overwrite those edits.
+            </div>
+          </div>
+          <Button
+            onClick={rerunChangedTargets}
+            variant="ghost"
+            size="sm"
+            className="mt-1 w-full text-yellow-800 hover:bg-yellow-100 dark:text-yellow-200 dark:hover:bg-yellow-500/20"
+            title="Parse the response again against the current files"
+            leftIcon={<RefreshCw size={14} />}
+          >
+            Re-run Review
+          </Button>
+        </>
+      )}
+      {manual.length > 0 && (
+        <div
+          className={cn(
+            'flex items-start gap-2',
+            rerunnable.length > 0 && 'mt-2'
+          )}
+          title={manual.join('\n')}
+        >
+          <AlertTriangle size={14} className="flex-shrink-0 mt-0.5" />
+          <div className="min-w-0 break-words">
+            {describeFiles(manual)} changed on disk after the review touched
+            them, which re-running can't update. Reverting restores the
+            review's backup over those edits, so revert or re-apply by hand.
+          </div>
+        </div>
+      )}
+    </div>
+  );
+};
+
 export function ChangeList() {
   const { changes } = useReviewStore();
   const { triggerFileTreeRefresh } = useWorkspaceStore();
@@ -274,6 +332,7 @@ export function ChangeList() {
           </Button>
         </div>
       </div>
+      <StaleTargetsNotice />
       <div className="flex-grow overflow-y-auto pr-1 min-h-0">
         <div className="flex flex-col gap-1">
           {changes.map((change) => (
@@ -35,15 +35,23 @@ function FileNodeComponent({
   const toggleDirectorySelection = useWorkspaceStore(
     (state) => state.toggleDirectorySelection
   );
+  const expandDirectory = useWorkspaceStore((state) => state.expandDirectory);
   const totalLeaves = useWorkspaceStore(
     (state) => state.fileIndex?.totalLeavesByPath.get(node.path) ?? 0
   );
   const selectedCount = useWorkspaceStore(
     (state) => state.selectedCountByPath.get(node.path) ?? 0
   );
+  // Files in directories that haven't been listed can't be selected yet.
+  const hasUnlisted = useWorkspaceStore(
+    (state) =>
+      node.isDirectory &&
+      (state.fileIndex?.unloadedDirectories.has(node.path) === true ||
+        state.fileIndex?.incompleteDirectories.has(node.path) === true)
+  );
   const isSelected = useWorkspaceStore((state) => {
     if (node.isDirectory) {
-      return totalLeaves > 0 && selectedCount === totalLeaves;
+      return totalLeaves > 0 && selectedCount === totalLeaves && !hasUnlisted;
     }
     return state.selectedFilePathSet.has(node.path);
   });
@@ -52,7 +60,9 @@ function FileNodeComponent({
   const isDirectory = node.isDirectory;
 
   const isIndeterminate =
-    isDirectory && selectedCount > 0 && selectedCount < totalLeaves;
+    isDirectory &&
+    selectedCount > 0 &&
+    (selectedCount < totalLeaves || hasUnlisted);
 
   const handleCheckboxChange = (e: React.ChangeEvent<HTMLInputElement>) => {
     const isChecked = e.target.checked;
@@ -69,6 +79,14 @@ function FileNodeComponent({
     }
   };
 
+  const toggleOpen = () => {
+    // Directories from a depth-limited listing load their children when opened.
+    if (!isOpen && !node.children && node.childCount != null) {
+      expandDirectory(node.path);
+    }
+    setIsOpen(!isOpen);
+  };
+
   const handleDisplayFile = () => {
     if (!isDirectory) {
       setActiveFilePath(node.path);
@@ -95,7 +113,7 @@ function FileNodeComponent({
           onClick={(e) => {
             e.stopPropagation();
             if (isDirectory) {
-              setIsOpen(!isOpen);
+              toggleOpen();
             } else {
               handleDisplayFile();
             }
@@ -131,7 +149,7 @@ function FileNodeComponent({
           onClick={(e) => {
             e.stopPropagation();
             if (isDirectory) {
-              setIsOpen(!isOpen);
+              toggleOpen();
             } else {
               handleDisplayFile();
             }
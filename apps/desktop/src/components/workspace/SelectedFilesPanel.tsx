@@ -13,6 +13,8 @@ export function SelectedFilesPanel() {
     removeSelectedFilePath,
     setActiveFilePath,
     refreshCounter,
+    pendingModifiedPaths,
+    takeModifiedPaths,
     setSelectedFilePaths,
     activeFilePath,
   } = useWorkspaceStore();
@@ -21,6 +23,8 @@ export function SelectedFilesPanel() {
     selectedFilePaths,
     rootPath,
     refreshCounter,
+    pendingModifiedPaths,
+    takeModifiedPaths,
     onMissingPath: removeSelectedFilePath,
   });
 
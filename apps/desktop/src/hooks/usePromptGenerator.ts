@@ -15,7 +15,7 @@ import {
 } from '../store/commandRunnerStore';
 import { useComposerStore } from '../store/composerStore';
 import { useHistoryStore } from '../store/historyStore';
-import { useSettingsStore } from '../store/settingsStore';
+import { selectMetaPrompts, useSettingsStore } from '../store/settingsStore';
 import { useWorkspaceStore } from '../store/workspaceStore';
 import type { MetaPrompt } from '../types/prompt';
 
@@ -28,10 +28,10 @@ export function usePromptGenerator() {
   const {
     customSystemPrompt,
     editFormat,
-    metaPrompts: promptDefs,
     respectGitignore,
     customIgnorePatterns,
   } = useSettingsStore();
+  const promptDefs = useSettingsStore(selectMetaPrompts);
 
   const [isCopied, setIsCopied] = useState(false);
   const [isGenerating, setIsGenerating] = useState(false);
@@ -116,7 +116,7 @@ export function usePromptGenerator() {
       });
       try {
         const fileTree = needsFileTree
-          ? useWorkspaceStore.getState().fileTree
+          ? await useWorkspaceStore.getState().getFullFileTree()
           : null;
         const { totalTokens, missingPaths } = await estimatePromptTokens({
           selectedFilePaths,
@@ -195,7 +195,7 @@ export function usePromptGenerator() {
       );
 
       const fileTree = needsFileTree
-        ? useWorkspaceStore.getState().fileTree
+        ? await useWorkspaceStore.getState().getFullFileTree()
         : null;
       const { fullPrompt, terminalCommandToRun } = await buildPrompt({
         files,
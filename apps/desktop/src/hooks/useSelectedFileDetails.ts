@@ -14,6 +14,8 @@ type UseSelectedFileDetailsParams = {
   selectedFilePaths: string[];
   rootPath: string | null;
   refreshCounter: number;
+  pendingModifiedPaths: Set<string>;
+  takeModifiedPaths: () => string[];
   onMissingPath: (path: string) => void;
 };
 
@@ -21,6 +23,8 @@ export function useSelectedFileDetails({
   selectedFilePaths,
   rootPath,
   refreshCounter,
+  pendingModifiedPaths,
+  takeModifiedPaths,
   onMissingPath,
 }: UseSelectedFileDetailsParams) {
   const detailsByPathRef = useRef(new Map<string, SelectedFileDetail>());
@@ -30,6 +34,9 @@ export function useSelectedFileDetails({
 
   useEffect(() => {
     if (!rootPath || selectedFilePaths.length === 0) {
+      if (pendingModifiedPaths.size > 0) {
+        takeModifiedPaths();
+      }
       detailsByPathRef.current.clear();
       setDetails([]);
       return;
@@ -43,6 +50,14 @@ export function useSelectedFileDetails({
     if (shouldReset) {
       detailsByPathRef.current.clear();
     }
+    // Only the files that were edited need recounting. Taking them runs this
+    // effect again, which fetches them.
+    if (pendingModifiedPaths.size > 0) {
+      for (const path of takeModifiedPaths()) {
+        detailsByPathRef.current.delete(path);
+      }
+      return;
+    }
 
     const selectedSet = new Set(selectedFilePaths);
     for (const path of detailsByPathRef.current.keys()) {
@@ -127,7 +142,14 @@ export function useSelectedFileDetails({
     return () => {
       cancelled = true;
     };
-  }, [selectedFilePaths, rootPath, refreshCounter, onMissingPath]);
+  }, [
+    selectedFilePaths,
+    rootPath,
+    refreshCounter,
+    pendingModifiedPaths,
+    takeModifiedPaths,
+    onMissingPath,
+  ]);
 
   const totalTokens = useMemo(
     () => details.reduce((sum, file) => sum + file.tokens, 0),
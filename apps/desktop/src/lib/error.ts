@@ -1,3 +1,5 @@
+import type { ProtectedPathViolation, WatchLimitExceeded } from '../bindings';
+
 export class AppError extends Error {
   public readonly originalError?: unknown;
 
@@ -31,3 +33,34 @@ export function isFileNotFoundError(error: unknown): boolean {
     errorMessage.includes('file not found')
   );
 }
+
+/**
+ * Extracts the protected-path violation behind an error, if the backend refused
+ * to touch a protected file.
+ * @param error The error object.
+ * @returns The violation, or `null` for any other error.
+ */
+export function getProtectedPathViolation(
+  error: unknown
+): ProtectedPathViolation | null {
+  const raw = error instanceof AppError ? error.originalError : error;
+  if (typeof raw === 'object' && raw !== null && 'violation' in raw) {
+    return (raw as { violation: ProtectedPathViolation }).violation;
+  }
+  return null;
+}
+
+/**
+ * Extracts the details of an error raised when the OS ran out of file watches.
+ * @param error The error object.
+ * @returns The exhausted limit, or `null` for any other error.
+ */
+export function getWatchLimitExceeded(
+  error: unknown
+): WatchLimitExceeded | null {
+  const raw = error instanceof AppError ? error.originalError : error;
+  if (typeof raw === 'object' && raw !== null && 'watchLimit' in raw) {
+    return (raw as { watchLimit: WatchLimitExceeded }).watchLimit;
+  }
+  return null;
+}
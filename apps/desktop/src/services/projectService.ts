@@ -1,6 +1,10 @@
 import type { FileNode, IgnoreSettings } from '../bindings';
 import { showErrorDialog } from '../lib/errorHandler';
-import { deleteBackup, listDirectoryRecursive } from './tauriApi';
+import {
+  deleteBackup,
+  listDirectory,
+  listDirectoryRecursive,
+} from './tauriApi';
 
 export async function loadFileTree(
   path: string,
@@ -9,6 +13,25 @@ export async function loadFileTree(
   return listDirectoryRecursive(path, settings);
 }
 
+export async function loadDirectory(
+  rootPath: string,
+  path: string,
+  settings: IgnoreSettings,
+  depth: number | null = null
+): Promise<FileNode> {
+  return listDirectory(rootPath, path, settings, depth);
+}
+
+/** Returns a copy of `tree` with the node at `node.path` replaced. */
+export function replaceNode(tree: FileNode, node: FileNode): FileNode {
+  if (tree.path === node.path) return node;
+  if (!tree.children || !node.path.startsWith(`${tree.path}/`)) return tree;
+  return {
+    ...tree,
+    children: tree.children.map((child) => replaceNode(child, node)),
+  };
+}
+
 export async function cleanupBackup(backupId: string) {
   try {
     await deleteBackup(backupId);
@@ -1,14 +1,26 @@
+import type { ChangeOperation } from '../bindings';
+import { AppError } from '../lib/error';
 import { createReviewChange, type ReviewChange } from '../types/review';
 import * as tauriApi from './tauriApi';
 
+/** Paths, relative to the project root, that applying `operation` touches. */
+export function getOperationPaths(operation: ChangeOperation): string[] {
+  switch (operation.type) {
+    case 'patch':
+    case 'overwrite':
+    case 'delete':
+      return [operation.filePath];
+    case 'move':
+      return [operation.fromPath, operation.toPath];
+  }
+}
+
 export async function processAndStartReview(
   markdown: string,
   rootPath: string
 ) {
-  const parsedOperations = await tauriApi.parseChangesFromMarkdown(
-    markdown,
-    rootPath
-  );
+  const { operations: parsedOperations, summary } =
+    await tauriApi.parseChangesFromMarkdown(markdown, rootPath);
   if (parsedOperations.length === 0) {
     return { changes: [], backupId: null };
   }
@@ -17,20 +29,11 @@ export async function processAndStartReview(
 
   const filesToSnapshot = new Set<string>();
   initialChanges.forEach(({ operation }) => {
-    switch (operation.type) {
-      case 'patch':
-      case 'overwrite':
-      case 'delete':
-        filesToSnapshot.add(operation.filePath);
-        break;
-      case 'move':
-        filesToSnapshot.add(operation.fromPath);
-        break;
-    }
+    for (const path of getOperationPaths(operation)) filesToSnapshot.add(path);
   });
 
   const fileList = Array.from(filesToSnapshot).filter((p) => p);
-  const backupId = await tauriApi.backupFiles(rootPath, fileList);
+  const backupId = await tauriApi.backupFiles(rootPath, fileList, summary);
 
   const changes = await Promise.all(
     initialChanges.map(async (change): Promise<ReviewChange> => {
@@ -76,6 +79,11 @@ export async function applyChange(change: ReviewChange, rootPath: string) {
   switch (operation.type) {
     case 'patch':
     case 'overwrite':
+      // Content cut off mid-response stays partial until a continuation is merged.
+      if (operation.truncation)
+        throw new AppError(
+          `The change to ${operation.filePath} was cut off. Merge a continuation of the response before applying it.`
+        );
       await tauriApi.writeFileContent(
         getAbsPath(operation.filePath),
         operation.content,
@@ -1,20 +1,43 @@
 import type { Channel } from '@tauri-apps/api/core';
 import {
+  type BackupManifest,
   type ChangeOperation,
+  type ChangeSummary,
   type CliInstallResult,
   type CliStatusResult,
   type CommandStreamEvent,
   type Commit,
   commands,
   type DiffOption,
+  type EditFormat,
   type FileNode,
   type FileTokenInfo,
+  type FormattedChanges,
+  type FormatterConfig,
+  type FormatterResult,
   type GitStatus,
+  type HistoryStepResult,
   type IgnoreSettings,
+  type ProjectConfigStatus,
+  type ProjectHistory,
+  type ProjectSettings,
   type PromptEstimateInput,
   type PromptEstimateResult,
+  type ProtectedPathRule,
+  type RepairPrompt,
+  type ResolvedProjectSettings,
   type Result,
+  type ReviewResult,
+  type ReviewSession,
+  type ReviewSessionInfo,
+  type SandboxFileDiff,
+  type SandboxInfo,
+  type SandboxKind,
+  type SandboxPromotion,
   type SearchResult,
+  type SymlinkPolicy,
+  type VerificationResult,
+  type WatcherInfo,
 } from '../bindings';
 import { AppError } from '../lib/error';
 
@@ -24,15 +47,31 @@ async function unwrap<T, E>(promise: Promise<Result<T, E>>): Promise<T> {
   if (result.status === 'ok') {
     return result.data;
   } else {
-    throw new AppError(String(result.error), result.error);
+    const message =
+      typeof result.error === 'object' &&
+      result.error !== null &&
+      'message' in result.error
+        ? String(result.error.message)
+        : String(result.error);
+    throw new AppError(message, result.error);
   }
 }
 
+export const listDirectory = async (
+  rootPath: string,
+  path: string,
+  settings: IgnoreSettings,
+  depth: number | null = null
+): Promise<FileNode> => {
+  return unwrap(commands.listDirectory(rootPath, path, settings, depth));
+};
+
 export const listDirectoryRecursive = async (
   path: string,
-  settings: IgnoreSettings
+  settings: IgnoreSettings,
+  withMetadata = false
 ): Promise<FileNode> => {
-  return unwrap(commands.listDirectoryRecursive(path, settings));
+  return unwrap(commands.listDirectoryRecursive(path, settings, withMetadata));
 };
 
 export const readFileAsBase64 = async (path: string): Promise<string> => {
@@ -86,6 +125,26 @@ export const writeFileContent = async (
   await unwrap(commands.writeFileContent(path, content, rootPath));
 };
 
+export const setSymlinkPolicy = async (
+  rootPath: string,
+  policy: SymlinkPolicy
+): Promise<void> => {
+  await unwrap(commands.setSymlinkPolicy(rootPath, policy));
+};
+
+export const setProtectedPaths = async (
+  rootPath: string,
+  rules: ProtectedPathRule[]
+): Promise<void> => {
+  await unwrap(commands.setProtectedPaths(rootPath, rules));
+};
+
+export const getProtectedPaths = async (
+  rootPath: string
+): Promise<ProtectedPathRule[]> => {
+  return unwrap(commands.getProtectedPaths(rootPath));
+};
+
 export const deleteFile = async (
   filePath: string,
   rootPath: string
@@ -103,9 +162,10 @@ export const moveFile = async (
 
 export const backupFiles = async (
   rootPath: string,
-  filePaths: string[]
+  filePaths: string[],
+  summary: ChangeSummary | null = null
 ): Promise<string> => {
-  return unwrap(commands.backupFiles(rootPath, filePaths));
+  return unwrap(commands.backupFiles(rootPath, filePaths, summary));
 };
 
 export const revertFileFromBackup = async (
@@ -127,6 +187,20 @@ export const deleteBackup = async (backupId: string): Promise<void> => {
   await unwrap(commands.deleteBackup(backupId));
 };
 
+export const listBackups = async (
+  rootPath: string
+): Promise<BackupManifest[]> => {
+  return unwrap(commands.listBackups(rootPath));
+};
+
+export const getBackup = async (backupId: string): Promise<BackupManifest> => {
+  return unwrap(commands.getBackup(backupId));
+};
+
+export const revertBackup = async (backupId: string): Promise<string[]> => {
+  return unwrap(commands.revertBackup(backupId));
+};
+
 export const getRelativePath = async (
   fullPath: string,
   rootPath: string
@@ -134,13 +208,189 @@ export const getRelativePath = async (
   return unwrap(commands.getRelativePath(fullPath, rootPath));
 };
 
+export const recordHistoryEntry = async (
+  rootPath: string,
+  backupId: string,
+  summary: ChangeSummary | null = null
+): Promise<ProjectHistory> => {
+  return unwrap(commands.recordHistoryEntry(rootPath, backupId, summary));
+};
+
+export const getHistory = async (rootPath: string): Promise<ProjectHistory> => {
+  return unwrap(commands.getHistory(rootPath));
+};
+
+export const undoChangeSet = async (
+  rootPath: string
+): Promise<HistoryStepResult> => {
+  return unwrap(commands.undoChangeSet(rootPath));
+};
+
+export const redoChangeSet = async (
+  rootPath: string
+): Promise<HistoryStepResult> => {
+  return unwrap(commands.redoChangeSet(rootPath));
+};
+
+export const createReviewSession = async (
+  rootPath: string,
+  rawResponse: string,
+  operations: ChangeOperation[],
+  backupId: string | null
+): Promise<ReviewSession> => {
+  return unwrap(
+    commands.createReviewSession(rootPath, rawResponse, operations, backupId)
+  );
+};
+
+export const saveReviewSession = async (
+  session: ReviewSession
+): Promise<ReviewSession> => {
+  return unwrap(commands.saveReviewSession(session));
+};
+
+export const listReviewSessions = async (
+  rootPath: string
+): Promise<ReviewSessionInfo[]> => {
+  return unwrap(commands.listReviewSessions(rootPath));
+};
+
+export const resumeReviewSession = async (
+  rootPath: string,
+  sessionId: string
+): Promise<ReviewSession> => {
+  return unwrap(commands.resumeReviewSession(rootPath, sessionId));
+};
+
+export const discardReviewSession = async (
+  rootPath: string,
+  sessionId: string
+): Promise<void> => {
+  await unwrap(commands.discardReviewSession(rootPath, sessionId));
+};
+
 export const parseChangesFromMarkdown = async (
   markdown: string,
   rootPath: string
-): Promise<ChangeOperation[]> => {
+): Promise<ReviewResult> => {
   return unwrap(commands.parseChangesFromMarkdown(markdown, rootPath));
 };
 
+export const trackReview = async (
+  rootPath: string,
+  markdown: string,
+  operations: ChangeOperation[]
+): Promise<void> => {
+  await unwrap(commands.trackReview(rootPath, markdown, operations));
+};
+
+export const untrackReview = async (rootPath: string): Promise<void> => {
+  await unwrap(commands.untrackReview(rootPath));
+};
+
+export const rerunReview = async (
+  rootPath: string,
+  filePaths: string[]
+): Promise<ChangeOperation[]> => {
+  return unwrap(commands.rerunReview(rootPath, filePaths));
+};
+
+export const applySelectedHunks = async (
+  rootPath: string,
+  operation: ChangeOperation,
+  hunkIds: string[]
+): Promise<string> => {
+  return unwrap(commands.applySelectedHunks(rootPath, operation, hunkIds));
+};
+
+export const formatProposedChanges = async (
+  rootPath: string,
+  operations: ChangeOperation[],
+  formatters: FormatterConfig[]
+): Promise<FormattedChanges> => {
+  return unwrap(
+    commands.formatProposedChanges(rootPath, operations, formatters)
+  );
+};
+
+export const formatFiles = async (
+  rootPath: string,
+  paths: string[],
+  formatters: FormatterConfig[]
+): Promise<FormatterResult[]> => {
+  return unwrap(commands.formatFiles(rootPath, paths, formatters));
+};
+
+export const runVerification = async (
+  rootPath: string,
+  command: string,
+  timeoutSecs: number,
+  changedFiles: string[]
+): Promise<VerificationResult> => {
+  return unwrap(
+    commands.runVerification(rootPath, command, timeoutSecs, changedFiles)
+  );
+};
+
+export const createSandbox = async (
+  rootPath: string,
+  kind: SandboxKind
+): Promise<SandboxInfo> => {
+  return unwrap(commands.createSandbox(rootPath, kind));
+};
+
+export const applyToSandbox = async (
+  sandboxId: string,
+  operations: ChangeOperation[]
+): Promise<void> => {
+  await unwrap(commands.applyToSandbox(sandboxId, operations));
+};
+
+export const runInSandbox = async (
+  sandboxId: string,
+  command: string,
+  timeoutSecs: number
+): Promise<VerificationResult> => {
+  return unwrap(commands.runInSandbox(sandboxId, command, timeoutSecs));
+};
+
+export const diffSandbox = async (
+  sandboxId: string
+): Promise<SandboxFileDiff[]> => {
+  return unwrap(commands.diffSandbox(sandboxId));
+};
+
+export const promoteSandbox = async (
+  sandboxId: string
+): Promise<SandboxPromotion> => {
+  return unwrap(commands.promoteSandbox(sandboxId));
+};
+
+export const discardSandbox = async (sandboxId: string): Promise<void> => {
+  await unwrap(commands.discardSandbox(sandboxId));
+};
+
+export const buildContinuationPrompt = async (
+  markdown: string
+): Promise<string> => {
+  return unwrap(commands.buildContinuationPrompt(markdown));
+};
+
+export const mergeContinuation = async (
+  markdown: string,
+  continuation: string
+): Promise<string> => {
+  return unwrap(commands.mergeContinuation(markdown, continuation));
+};
+
+export const buildRepairPrompt = async (
+  rootPath: string,
+  operations: ChangeOperation[],
+  editFormat: EditFormat
+): Promise<RepairPrompt> => {
+  return unwrap(commands.buildRepairPrompt(rootPath, operations, editFormat));
+};
+
 export const isGitRepository = async (path: string): Promise<boolean> => {
   return unwrap(commands.isGitRepository(path));
 };
@@ -208,6 +458,26 @@ export const installCliShim = async (): Promise<CliInstallResult> => {
   return unwrap(commands.installCliShim());
 };
 
+export const loadProjectConfig = async (
+  rootPath: string
+): Promise<ProjectConfigStatus> => {
+  return unwrap(commands.loadProjectConfig(rootPath));
+};
+
+export const resolveProjectSettings = async (
+  rootPath: string,
+  settings: ProjectSettings
+): Promise<ResolvedProjectSettings> => {
+  return unwrap(commands.resolveProjectSettings(rootPath, settings));
+};
+
+export const approveProjectFormatters = async (
+  rootPath: string,
+  fingerprint: string
+): Promise<void> => {
+  await unwrap(commands.approveProjectFormatters(rootPath, fingerprint));
+};
+
 export const startWatching = async (
   rootPath: string,
   settings: IgnoreSettings
@@ -223,6 +493,14 @@ export const stopWatching = async (rootPath: string): Promise<void> => {
   }
 };
 
+export const restartWatcher = async (rootPath: string): Promise<void> => {
+  await unwrap(commands.restartWatcher(rootPath));
+};
+
+export const listWatchers = async (): Promise<WatcherInfo[]> => {
+  return unwrap(commands.listWatchers());
+};
+
 export const searchFiles = async (
   query: string,
   rootPath: string,
@@ -0,0 +1,84 @@
+import { create } from 'zustand';
+import type { HistoryStepResult, ProjectHistory } from '../bindings';
+import { AppError } from '../lib/error';
+import { showErrorDialog } from '../lib/errorHandler';
+import {
+  deleteBackup,
+  getHistory,
+  recordHistoryEntry,
+  redoChangeSet,
+  undoChangeSet,
+} from '../services/tauriApi';
+import { useDialogStore } from './dialogStore';
+import { useWorkspaceStore } from './workspaceStore';
+
+interface ChangeHistoryState {
+  history: ProjectHistory | null;
+  isStepping: boolean;
+
+  load: (rootPath: string) => Promise<void>;
+  /** Records a change set applied after `backupId` was taken, then drops that backup. */
+  record: (rootPath: string, backupId: string) => Promise<void>;
+  undo: () => Promise<void>;
+  redo: () => Promise<void>;
+}
+
+const showConflicts = (action: string, result: HistoryStepResult) =>
+  useDialogStore.getState().open({
+    title: `Cannot ${action}`,
+    content: `These files were changed since: ${result.conflicts
+      .map((c) => `${c.filePath} (${c.kind})`)
+      .join(', ')}`,
+    status: 'warning',
+  });
+
+export const useChangeHistoryStore = create<ChangeHistoryState>(
+  (set, get) => {
+    const step = async (
+      action: string,
+      run: (rootPath: string) => Promise<HistoryStepResult>
+    ) => {
+      const { rootPath, triggerFileTreeRefresh } =
+        useWorkspaceStore.getState();
+      if (!rootPath || get().isStepping) return;
+      set({ isStepping: true });
+      try {
+        const result = await run(rootPath);
+        set({ history: result.history });
+        if (result.applied) {
+          triggerFileTreeRefresh();
+        } else {
+          showConflicts(action, result);
+        }
+      } catch (e) {
+        showErrorDialog(new AppError(`Failed to ${action}`, e));
+      } finally {
+        set({ isStepping: false });
+      }
+    };
+
+    return {
+      history: null,
+      isStepping: false,
+      load: async (rootPath) => {
+        try {
+          set({ history: await getHistory(rootPath) });
+        } catch (e) {
+          set({ history: null });
+          console.warn(new AppError('Failed to load change history', e));
+        }
+      },
+      record: async (rootPath, backupId) => {
+        try {
+          set({ history: await recordHistoryEntry(rootPath, backupId) });
+        } catch (e) {
+          console.warn(new AppError('Failed to record change history', e));
+        } finally {
+          deleteBackup(backupId).catch(() => {});
+        }
+      },
+      undo: () => step('undo', undoChangeSet),
+      redo: () => step('redo', redoChangeSet),
+    };
+  }
+);
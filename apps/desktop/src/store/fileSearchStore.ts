@@ -138,6 +138,7 @@ export const useFileSearchStore = create<FileSearchState>((set, get) => ({
       // Handle directory selection
       try {
         // Get all files within this directory from the workspace store's file tree
+        await useWorkspaceStore.getState().loadSubtree(filePath);
         const { fileTree } = useWorkspaceStore.getState();
         if (!fileTree) return;
 
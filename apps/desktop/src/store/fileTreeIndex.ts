@@ -5,6 +5,10 @@ export type FileTreeIndex = {
   descendantLeavesByPath: Map<string, string[]>;
   parentByPath: Map<string, string | null>;
   filePaths: Set<string>;
+  /** Directories whose children have not been listed yet. */
+  unloadedDirectories: Set<string>;
+  /** Directories with unlisted directories somewhere below them. */
+  incompleteDirectories: Set<string>;
 };
 
 export function buildFileTreeIndex(root: FileNode): FileTreeIndex {
@@ -12,6 +16,8 @@ export function buildFileTreeIndex(root: FileNode): FileTreeIndex {
   const descendantLeavesByPath = new Map<string, string[]>();
   const parentByPath = new Map<string, string | null>();
   const filePaths = new Set<string>();
+  const unloadedDirectories = new Set<string>();
+  const incompleteDirectories = new Set<string>();
 
   const walk = (node: FileNode, parent: string | null): string[] => {
     parentByPath.set(node.path, parent);
@@ -27,7 +33,15 @@ export function buildFileTreeIndex(root: FileNode): FileTreeIndex {
     if (node.children) {
       for (const child of node.children) {
         leaves.push(...walk(child, node.path));
+        if (
+          unloadedDirectories.has(child.path) ||
+          incompleteDirectories.has(child.path)
+        ) {
+          incompleteDirectories.add(node.path);
+        }
       }
+    } else {
+      unloadedDirectories.add(node.path);
     }
 
     totalLeavesByPath.set(node.path, leaves.length);
@@ -42,9 +56,35 @@ export function buildFileTreeIndex(root: FileNode): FileTreeIndex {
     descendantLeavesByPath,
     parentByPath,
     filePaths,
+    unloadedDirectories,
+    incompleteDirectories,
   };
 }
 
+/**
+ * Finds the unlisted directories that hide `paths`, with the depth each must
+ * be listed to for the paths to appear in the tree.
+ */
+export function findHiddenAncestors(
+  paths: string[],
+  fileIndex: FileTreeIndex
+): Map<string, number> {
+  const depths = new Map<string, number>();
+  for (const path of paths) {
+    if (fileIndex.filePaths.has(path)) {
+      continue;
+    }
+    for (const dir of fileIndex.unloadedDirectories) {
+      if (!path.startsWith(`${dir}/`)) {
+        continue;
+      }
+      const depth = path.slice(dir.length + 1).split('/').length;
+      depths.set(dir, Math.max(depths.get(dir) ?? 0, depth));
+    }
+  }
+  return depths;
+}
+
 export function normalizeSelectedPaths(
   paths: string[],
   fileIndex: FileTreeIndex | null
@@ -1,9 +1,23 @@
-import { create } from 'zustand';
-import type { ChangeOperation } from '../bindings';
+import { create, type StoreApi } from 'zustand';
+import type {
+  ChangeOperation,
+  ReviewSession,
+  ReviewTargetChange,
+} from '../bindings';
 import { AppError } from '../lib/error';
 import { showErrorDialog } from '../lib/errorHandler';
 import * as reviewService from '../services/reviewService';
-import type { ReviewChange } from '../types/review';
+import {
+  backupFiles,
+  createReviewSession,
+  discardReviewSession,
+  rerunReview,
+  saveReviewSession,
+  trackReview,
+  untrackReview,
+} from '../services/tauriApi';
+import { createReviewChange, type ReviewChange } from '../types/review';
+import { useChangeHistoryStore } from './changeHistoryStore';
 import { useWorkspaceStore } from './workspaceStore';
 
 interface ReviewState {
@@ -11,14 +25,22 @@ interface ReviewState {
   changes: ReviewChange[];
   activeChangeId: string | null;
   sessionBaseBackupId: string | null;
+  /** The persisted copy of this review, so it can be restored after a restart. */
+  session: ReviewSession | null;
   errors: Record<string, string>;
+  markdown: string | null;
+  /** Files under review that were changed outside the review. */
+  changedTargetPaths: string[];
   lastReview: {
     changes: ReviewChange[];
     sessionBaseBackupId: string | null;
+    session: ReviewSession | null;
+    markdown: string | null;
   } | null;
 
   // Actions
   startReview: (markdown: string) => Promise<void>;
+  resumeSession: (session: ReviewSession) => void;
   endReview: () => void;
   reenterReview: () => void;
   clearReviewSession: () => void;
@@ -27,11 +49,14 @@ interface ReviewState {
   revertChange: (id: string) => Promise<void>;
   applyAllPendingChanges: () => Promise<void>;
   revertAllAppliedChanges: () => Promise<void>;
+  markTargetsChanged: (changes: ReviewTargetChange[]) => void;
+  rerunChangedTargets: () => Promise<void>;
 }
 
 const initialState: Omit<
   ReviewState,
   | 'startReview'
+  | 'resumeSession'
   | 'endReview'
   | 'reenterReview'
   | 'clearReviewSession'
@@ -40,15 +65,187 @@ const initialState: Omit<
   | 'revertChange'
   | 'applyAllPendingChanges'
   | 'revertAllAppliedChanges'
+  | 'markTargetsChanged'
+  | 'rerunChangedTargets'
 > = {
   isReviewing: false,
   changes: [],
   activeChangeId: null,
   sessionBaseBackupId: null,
+  session: null,
   errors: {},
+  markdown: null,
+  changedTargetPaths: [],
   lastReview: null,
 };
 
+const getFilePath = (operation: ChangeOperation): string | null =>
+  operation.type === 'move' ? null : operation.filePath;
+
+/** Whether re-running the review can update the change to `path`. */
+export const isRerunnable = (changes: ReviewChange[], path: string) =>
+  changes.some(
+    (c) =>
+      getFilePath(c.operation) === path &&
+      (c.status === 'pending' || c.status === 'error')
+  );
+
+/** Backups taken when changes were re-run, beyond the review's own. */
+const getRerunBackupIds = (changes: ReviewChange[]) =>
+  new Set(changes.flatMap((c) => (c.baseBackupId ? [c.baseBackupId] : [])));
+
+/** Deletes the backups of a review that has no session to delete them with. */
+const cleanupReviewBackups = (
+  sessionBaseBackupId: string | null,
+  changes: ReviewChange[]
+) => {
+  if (sessionBaseBackupId) reviewService.cleanupBackup(sessionBaseBackupId);
+  for (const backupId of getRerunBackupIds(changes)) {
+    reviewService.cleanupBackup(backupId);
+  }
+};
+
+/** Drops the paths an apply or revert just reconciled with the review. */
+const withoutPaths = (paths: string[], change: ReviewChange) => {
+  const done = new Set(reviewService.getOperationPaths(change.operation));
+  return paths.filter((path) => !done.has(path));
+};
+
+const startTracking = (
+  rootPath: string,
+  markdown: string,
+  changes: ReviewChange[]
+) => {
+  trackReview(
+    rootPath,
+    markdown,
+    changes.map((c) => c.operation)
+  ).catch((e) => console.warn(new AppError('Failed to track review', e)));
+};
+
+const stopTracking = () => {
+  const { rootPath } = useWorkspaceStore.getState();
+  if (!rootPath) return;
+  untrackReview(rootPath).catch((e) =>
+    console.warn(new AppError('Failed to stop tracking review', e))
+  );
+};
+
+let pendingSave: Promise<void> = Promise.resolve();
+
+/** Writes the current review state to its session, one save at a time. */
+const persistSession = (get: () => ReviewState) => {
+  pendingSave = pendingSave.then(async () => {
+    const { session, changes, errors, sessionBaseBackupId } = get();
+    if (!session) return;
+    try {
+      const saved = await saveReviewSession({
+        ...session,
+        backupId: sessionBaseBackupId,
+        operations: changes.map((c) => ({
+          operation: c.operation,
+          status: c.status,
+          error: errors[c.id] ?? null,
+          backupId: c.baseBackupId ?? null,
+        })),
+      });
+      if (useReviewStore.getState().session?.id === saved.id) {
+        useReviewStore.setState({ session: saved });
+      }
+    } catch (e) {
+      console.warn(new AppError('Failed to save review session', e));
+    }
+  });
+  return pendingSave;
+};
+
+/**
+ * Runs file operations for `changes` and records them as one entry in the project's change
+ * history, so the set can be undone or redone later even after the review has ended. `run`
+ * returns whether it changed anything.
+ */
+const withHistory = async (
+  rootPath: string,
+  changes: ReviewChange[],
+  run: () => Promise<boolean>
+) => {
+  const paths = new Set(
+    changes.flatMap((c) => reviewService.getOperationPaths(c.operation))
+  );
+  const backupId = await backupFiles(rootPath, [...paths]).catch((e) => {
+    console.warn(new AppError('Failed to back up files for history', e));
+    return null;
+  });
+  let changed = false;
+  try {
+    changed = await run();
+  } finally {
+    if (backupId && !changed) {
+      reviewService.cleanupBackup(backupId).catch(() => {});
+    }
+  }
+  if (backupId && changed) {
+    await useChangeHistoryStore.getState().record(rootPath, backupId);
+  }
+};
+
+type SetReviewState = StoreApi<ReviewState>['setState'];
+
+/** Applies `change` and updates its status, returning whether it was applied. */
+const applyOne = async (
+  set: SetReviewState,
+  rootPath: string,
+  change: ReviewChange
+) => {
+  try {
+    await reviewService.applyChange(change, rootPath);
+    set((state) => ({
+      changes: state.changes.map((c) =>
+        c.id === change.id ? { ...c, status: 'applied' } : c
+      ),
+      changedTargetPaths: withoutPaths(state.changedTargetPaths, change),
+    }));
+    updateWorkspaceOnFileChange(change.operation, 'apply');
+    return true;
+  } catch (e: unknown) {
+    set((state) => ({
+      errors: { ...state.errors, [change.id]: String(e) },
+      changes: state.changes.map((c) =>
+        c.id === change.id ? { ...c, status: 'error' } : c
+      ),
+    }));
+    return false;
+  }
+};
+
+/** Reverts `change` to `backupId`, returning whether it was reverted. */
+const revertOne = async (
+  set: SetReviewState,
+  rootPath: string,
+  change: ReviewChange,
+  backupId: string
+) => {
+  try {
+    await reviewService.revertChange(change, backupId, rootPath);
+    set((state) => ({
+      changes: state.changes.map((c) =>
+        c.id === change.id ? { ...c, status: 'pending' } : c
+      ),
+      changedTargetPaths: withoutPaths(state.changedTargetPaths, change),
+    }));
+    updateWorkspaceOnFileChange(change.operation, 'revert');
+    return true;
+  } catch (e: unknown) {
+    showErrorDialog(new AppError(`Failed to revert change ${change.id}`, e));
+    return false;
+  }
+};
+
+const discardSession = (session: ReviewSession) =>
+  discardReviewSession(session.rootPath, session.id).catch((e) =>
+    console.warn(new AppError('Failed to discard review session', e))
+  );
+
 const updateWorkspaceOnFileChange = (
   operation: ChangeOperation,
   direction: 'apply' | 'revert'
@@ -103,8 +300,10 @@ export const useReviewStore = create<ReviewState>((set, get) => ({
     if (!rootPath) return;
 
     const lastReview = get().lastReview;
-    if (lastReview?.sessionBaseBackupId) {
-      reviewService.cleanupBackup(lastReview.sessionBaseBackupId);
+    if (lastReview?.session) {
+      discardSession(lastReview.session);
+    } else if (lastReview) {
+      cleanupReviewBackups(lastReview.sessionBaseBackupId, lastReview.changes);
     }
 
     const { changes, backupId } = await reviewService.processAndStartReview(
@@ -113,10 +312,24 @@ export const useReviewStore = create<ReviewState>((set, get) => ({
     );
     if (changes.length === 0) return;
 
+    const session = await createReviewSession(
+      rootPath,
+      markdown,
+      changes.map((c) => c.operation),
+      backupId
+    ).catch((e) => {
+      console.warn(new AppError('Failed to create review session', e));
+      return null;
+    });
+
+    startTracking(rootPath, markdown, changes);
     set({
       isReviewing: true,
       changes,
       sessionBaseBackupId: backupId,
+      session,
+      markdown,
+      changedTargetPaths: [],
       activeChangeId:
         changes.find((c) => c.status === 'pending')?.id ??
         changes[0]?.id ??
@@ -124,31 +337,80 @@ export const useReviewStore = create<ReviewState>((set, get) => ({
       errors: {},
       lastReview: null,
     });
+    // Statuses such as `identical` are only known once the review has started.
+    await persistSession(get);
+  },
+  resumeSession: (session) => {
+    const changes = session.operations.map(
+      ({ operation, status, backupId }) => ({
+        ...createReviewChange(operation),
+        status,
+        baseBackupId: backupId ?? null,
+      })
+    );
+    const errors: Record<string, string> = {};
+    session.operations.forEach(({ error }, index) => {
+      if (error) errors[changes[index].id] = error;
+    });
+
+    startTracking(session.rootPath, session.rawResponse, changes);
+    set({
+      isReviewing: true,
+      changes,
+      sessionBaseBackupId: session.backupId,
+      session,
+      markdown: session.rawResponse,
+      changedTargetPaths: [],
+      activeChangeId:
+        changes.find((c) => c.status === 'pending')?.id ??
+        changes[0]?.id ??
+        null,
+      errors,
+      lastReview: null,
+    });
   },
   endReview: () => {
-    const { sessionBaseBackupId, changes } = get();
+    const { sessionBaseBackupId, session, changes, markdown } = get();
     const wasAnythingApplied = changes.some((c) => c.status === 'applied');
-    if (sessionBaseBackupId && !wasAnythingApplied) {
-      reviewService.cleanupBackup(sessionBaseBackupId);
+    if (!wasAnythingApplied) {
+      if (session) {
+        discardSession(session);
+      } else {
+        cleanupReviewBackups(sessionBaseBackupId, changes);
+      }
     }
+    stopTracking();
     set({
       isReviewing: false,
-      lastReview: wasAnythingApplied ? { changes, sessionBaseBackupId } : null,
+      lastReview: wasAnythingApplied
+        ? { changes, sessionBaseBackupId, session, markdown }
+        : null,
       changes: [],
       activeChangeId: null,
       sessionBaseBackupId: null,
+      session: null,
       errors: {},
+      markdown: null,
+      changedTargetPaths: [],
     });
   },
   reenterReview: () => {
+    const { rootPath } = useWorkspaceStore.getState();
+    const { lastReview } = get();
+    if (rootPath && lastReview?.markdown) {
+      startTracking(rootPath, lastReview.markdown, lastReview.changes);
+    }
     set((state) => {
       if (!state.lastReview) return state;
-      const { changes, sessionBaseBackupId } = state.lastReview;
+      const { changes, sessionBaseBackupId, session, markdown } =
+        state.lastReview;
       return {
         ...state,
         isReviewing: true,
         changes,
         sessionBaseBackupId,
+        session,
+        markdown,
         activeChangeId:
           changes.find((c) => c.status !== 'identical')?.id ??
           changes[0]?.id ??
@@ -165,59 +427,104 @@ export const useReviewStore = create<ReviewState>((set, get) => ({
     const change = changes.find((c) => c.id === id);
     if (!change || change.status !== 'pending' || !rootPath) return;
 
-    try {
-      await reviewService.applyChange(change, rootPath);
-      set((state) => ({
-        changes: state.changes.map((c) =>
-          c.id === id ? { ...c, status: 'applied' } : c
-        ),
-      }));
-      updateWorkspaceOnFileChange(change.operation, 'apply');
-    } catch (e: unknown) {
-      set((state) => ({
-        errors: { ...state.errors, [id]: String(e) },
-        changes: state.changes.map((c) =>
-          c.id === id ? { ...c, status: 'error' } : c
-        ),
-      }));
-    }
+    await withHistory(rootPath, [change], () =>
+      applyOne(set, rootPath, change)
+    );
+    await persistSession(get);
   },
   revertChange: async (id) => {
     const { changes, sessionBaseBackupId } = get();
     const { rootPath } = useWorkspaceStore.getState();
     const change = changes.find((c) => c.id === id);
-    if (
-      !change ||
-      change.status !== 'applied' ||
-      !rootPath ||
-      !sessionBaseBackupId
-    )
+    const backupId = change?.baseBackupId ?? sessionBaseBackupId;
+    if (!change || change.status !== 'applied' || !rootPath || !backupId)
       return;
 
-    try {
-      await reviewService.revertChange(change, sessionBaseBackupId, rootPath);
-      set((state) => ({
-        changes: state.changes.map((c) =>
-          c.id === id ? { ...c, status: 'pending' } : c
-        ),
-      }));
-      updateWorkspaceOnFileChange(change.operation, 'revert');
-    } catch (e: unknown) {
-      showErrorDialog(new AppError(`Failed to revert change ${id}`, e));
-    }
+    await withHistory(rootPath, [change], () =>
+      revertOne(set, rootPath, change, backupId)
+    );
+    await persistSession(get);
   },
+  // Applying or reverting everything is one step in the change history.
   applyAllPendingChanges: async () => {
-    const { changes, applyChange } = get();
-    for (const change of changes.filter((c) => c.status === 'pending')) {
-      await applyChange(change.id);
-    }
+    const { rootPath } = useWorkspaceStore.getState();
+    const pending = get().changes.filter((c) => c.status === 'pending');
+    if (!rootPath || pending.length === 0) return;
+
+    await withHistory(rootPath, pending, async () => {
+      let applied = false;
+      for (const change of pending) {
+        applied = (await applyOne(set, rootPath, change)) || applied;
+      }
+      return applied;
+    });
+    await persistSession(get);
   },
   revertAllAppliedChanges: async () => {
-    const { changes, revertChange } = get();
-    for (const change of [...changes]
+    const { rootPath } = useWorkspaceStore.getState();
+    const { changes, sessionBaseBackupId } = get();
+    const applied = [...changes]
       .reverse()
-      .filter((c) => c.status === 'applied')) {
-      await revertChange(change.id);
+      .filter((c) => c.status === 'applied');
+    if (!rootPath || applied.length === 0) return;
+
+    await withHistory(rootPath, applied, async () => {
+      let reverted = false;
+      for (const change of applied) {
+        const backupId = change.baseBackupId ?? sessionBaseBackupId;
+        if (!backupId) continue;
+        reverted =
+          (await revertOne(set, rootPath, change, backupId)) || reverted;
+      }
+      return reverted;
+    });
+    await persistSession(get);
+  },
+  markTargetsChanged: (targets) => {
+    set((state) => {
+      const paths = new Set(state.changedTargetPaths);
+      for (const { filePath } of targets) paths.add(filePath);
+      return { changedTargetPaths: [...paths] };
+    });
+  },
+  rerunChangedTargets: async () => {
+    const { rootPath } = useWorkspaceStore.getState();
+    const { changes, changedTargetPaths } = get();
+    if (!rootPath) return;
+
+    // Applied changes already overwrote the file; they stay listed until
+    // they are reverted or applied again.
+    const rerunnable = changedTargetPaths.filter((path) =>
+      isRerunnable(changes, path)
+    );
+    if (rerunnable.length === 0) return;
+    try {
+      // The re-run changes start from the files as edited, so reverting them
+      // must restore those edits rather than the review's backup.
+      const backupId = await backupFiles(rootPath, rerunnable);
+      const operations = await rerunReview(rootPath, rerunnable);
+      const rerun = new Map(operations.map((op) => [getFilePath(op), op]));
+      const previousBackupIds = getRerunBackupIds(get().changes);
+      set((state) => ({
+        changedTargetPaths: state.changedTargetPaths.filter(
+          (path) => !rerun.has(path)
+        ),
+        changes: state.changes.map((c) => {
+          if (c.status !== 'pending' && c.status !== 'error') return c;
+          const operation = rerun.get(getFilePath(c.operation));
+          return operation
+            ? { ...c, operation, status: 'pending', baseBackupId: backupId }
+            : c;
+        }),
+      }));
+      const inUse = getRerunBackupIds(get().changes);
+      if (!inUse.has(backupId)) reviewService.cleanupBackup(backupId);
+      for (const id of previousBackupIds) {
+        if (!inUse.has(id)) reviewService.cleanupBackup(id);
+      }
+      await persistSession(get);
+    } catch (e: unknown) {
+      showErrorDialog(new AppError('Failed to re-run review', e));
     }
   },
 }));
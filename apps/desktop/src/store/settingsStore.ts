@@ -1,8 +1,16 @@
 import { Store as TauriStore } from '@tauri-apps/plugin-store';
 import { temporal } from 'zundo';
 import { create } from 'zustand';
+import type { FormatterConsent, MetaPrompt } from '../bindings';
+import { AppError } from '../lib/error';
+import {
+  approveProjectFormatters,
+  resolveProjectSettings,
+} from '../services/tauriApi';
 import type { EditFormat, MetaPromptDefinition } from '../types/prompt';
 import type { AppSettings } from '../types/settings';
+import { useComposerStore } from './composerStore';
+import { useDialogStore } from './dialogStore';
 
 const SETTINGS_FILE = 'app-settings.json';
 
@@ -26,8 +34,50 @@ const defaultSettings: AppSettings = {
   enableClipboardReview: true,
   showPasteResponseArea: true,
   theme: 'system',
+  approvedProjectFormatters: {},
 };
 
+/** The open project's `.repowizard.toml` applied on top of the user's settings. */
+interface ProjectSettingsState {
+  rootPath: string;
+  metaPrompts: MetaPromptDefinition[];
+  tokenBudget: number | null;
+}
+
+const toBindingPrompt = (def: MetaPromptDefinition): MetaPrompt => ({
+  ...def,
+  magicType: def.magicType ?? null,
+  fileTreeConfig: def.fileTreeConfig ?? null,
+  gitDiffConfig: def.gitDiffConfig ?? null,
+  terminalCommandConfig: def.terminalCommandConfig ?? null,
+  enabled: false,
+});
+
+const fromBindingPrompt = ({
+  enabled: _enabled,
+  ...prompt
+}: MetaPrompt): MetaPromptDefinition => ({
+  ...prompt,
+  magicType: prompt.magicType ?? undefined,
+  fileTreeConfig: prompt.fileTreeConfig ?? undefined,
+  gitDiffConfig: prompt.gitDiffConfig ?? undefined,
+  terminalCommandConfig: prompt.terminalCommandConfig ?? undefined,
+});
+
+const askedFormatterFingerprints = new Set<string>();
+
+const askToApproveFormatters = (consent: FormatterConsent) =>
+  useDialogStore.getState().open({
+    title: 'Allow Project Formatters?',
+    content: `This project's .repowizard.toml wants to run these commands on files you apply: ${consent.formatters
+      .map((f) => `\`${f.command}\` for ${f.glob}`)
+      .join(', ')}. Only allow them if you trust this project.`,
+    type: 'confirm',
+    status: 'warning',
+    confirmText: 'Allow',
+    cancelText: 'Not Now',
+  });
+
 const normalizeMetaPrompts = (
   prompts: MetaPromptDefinition[]
 ): MetaPromptDefinition[] => {
@@ -59,6 +109,11 @@ interface SettingsState extends AppSettings {
   setEnableClipboardReview: (value: boolean) => void;
   setShowPasteResponseArea: (value: boolean) => void;
 
+  /** Set while a project is open; its meta prompts include the config file's. */
+  projectSettings: ProjectSettingsState | null;
+  resolveProjectSettings: (rootPath: string) => Promise<void>;
+  clearProjectSettings: () => void;
+
   // Internal
   _isInitialized: boolean;
   _hasHydrated: boolean;
@@ -94,6 +149,7 @@ export const useSettingsStore = create<SettingsState>()(
             enableClipboardReview: state.enableClipboardReview,
             showPasteResponseArea: state.showPasteResponseArea,
             theme: state.theme,
+            approvedProjectFormatters: state.approvedProjectFormatters,
           };
           const store = await getTauriStore();
           await store.set('state', stateToSave);
@@ -133,13 +189,82 @@ export const useSettingsStore = create<SettingsState>()(
           // Clear history so we don't undo back to empty state
           useSettingsStore.temporal.getState().clear();
 
-          useSettingsStore.subscribe((state) => {
+          useSettingsStore.subscribe((state, prev) => {
             if (state._hasHydrated) {
               debouncedSave(state);
             }
+            if (
+              state.projectSettings &&
+              state.metaPrompts !== prev.metaPrompts
+            ) {
+              get().resolveProjectSettings(state.projectSettings.rootPath);
+            }
           });
         },
 
+        projectSettings: null,
+        resolveProjectSettings: async (rootPath) => {
+          const state = get();
+          try {
+            const resolved = await resolveProjectSettings(rootPath, {
+              ignore: {
+                respectGitignore: state.respectGitignore,
+                customIgnorePatterns: state.customIgnorePatterns,
+              },
+              formatters: [],
+              metaPrompts: state.metaPrompts.map(toBindingPrompt),
+              tokenBudget: null,
+            });
+
+            // Project prompts the file enables start out enabled the first time they appear.
+            const known = new Set(
+              get().projectSettings?.rootPath === rootPath
+                ? get().projectSettings?.metaPrompts.map((p) => p.id)
+                : []
+            );
+            const newlyEnabled = (resolved.config.config?.metaPrompts ?? [])
+              .filter((p) => p.enabled && !known.has(p.id))
+              .map((p) => p.id);
+            if (newlyEnabled.length > 0) {
+              const { enabledMetaPromptIds, setEnabledMetaPromptIds } =
+                useComposerStore.getState();
+              setEnabledMetaPromptIds([
+                ...new Set([...enabledMetaPromptIds, ...newlyEnabled]),
+              ]);
+            }
+
+            set({
+              projectSettings: {
+                rootPath,
+                metaPrompts:
+                  resolved.settings.metaPrompts.map(fromBindingPrompt),
+                tokenBudget: resolved.settings.tokenBudget,
+              },
+            });
+
+            const consent = resolved.formatterConsent;
+            if (!consent || consent.approved) return;
+            const { fingerprint } = consent;
+            if (get().approvedProjectFormatters[rootPath] !== fingerprint) {
+              if (askedFormatterFingerprints.has(fingerprint)) return;
+              askedFormatterFingerprints.add(fingerprint);
+              if (!(await askToApproveFormatters(consent))) return;
+              set((s) => ({
+                approvedProjectFormatters: {
+                  ...s.approvedProjectFormatters,
+                  [rootPath]: fingerprint,
+                },
+              }));
+            }
+            await approveProjectFormatters(rootPath, fingerprint);
+          } catch (e) {
+            console.warn(
+              new AppError(`Failed to resolve project settings: ${rootPath}`, e)
+            );
+          }
+        },
+        clearProjectSettings: () => set({ projectSettings: null }),
+
         setRespectGitignore: (value) => set({ respectGitignore: value }),
         setCustomIgnorePatterns: (value) =>
           set({ customIgnorePatterns: value }),
@@ -173,4 +298,8 @@ export const useSettingsStore = create<SettingsState>()(
   )
 );
 
+/** The meta prompts in effect: the user's, with the open project's applied on top. */
+export const selectMetaPrompts = (state: SettingsState) =>
+  state.projectSettings?.metaPrompts ?? state.metaPrompts;
+
 useSettingsStore.getState()._init();
@@ -1,17 +1,21 @@
-import { listen } from '@tauri-apps/api/event';
 import { Store as TauriStore } from '@tauri-apps/plugin-store';
 import { create } from 'zustand';
-import type { FileNode } from '../bindings';
-import { AppError } from '../lib/error';
+import { events, type FileNode, type FsChangeEvent } from '../bindings';
+import { AppError, getWatchLimitExceeded } from '../lib/error';
 import { showErrorDialog } from '../lib/errorHandler';
 import * as projectService from '../services/projectService';
-import { startWatching, stopWatching } from '../services/tauriApi';
+import {
+  loadProjectConfig,
+  startWatching,
+  stopWatching,
+} from '../services/tauriApi';
 import type { ComposerMode, PromptHistoryEntry } from '../types/prompt';
 import { type ComposerState, useComposerStore } from './composerStore';
 import {
   buildFileTreeIndex,
   buildSelectedCounts,
   type FileTreeIndex,
+  findHiddenAncestors,
   normalizeSelectedPaths,
 } from './fileTreeIndex';
 import { type HistoryState, useHistoryStore } from './historyStore';
@@ -50,18 +54,29 @@ interface WorkspaceState {
   selectedFilePathIndex: Map<string, number>;
   selectedCountByPath: Map<string, number>;
   refreshCounter: number;
+  /** Files edited since `takeModifiedPaths` was last called. */
+  pendingModifiedPaths: Set<string>;
 
   // Actions
   setRootPath: (rootPath: string) => Promise<void>;
   closeProject: () => Promise<void>;
   loadFileTree: () => Promise<void>;
+  expandDirectory: (path: string) => Promise<void>;
+  loadSubtree: (path: string) => Promise<void>;
+  revealPaths: (paths: string[]) => Promise<void>;
+  getFullFileTree: () => Promise<FileNode | null>;
   setFileTree: (fileTree: FileNode | null) => void;
   setActiveFilePath: (path: string | null) => void;
   setSelectedFilePaths: (paths: string[]) => void;
   addSelectedFilePath: (path: string) => void;
   removeSelectedFilePath: (path: string) => void;
-  toggleDirectorySelection: (path: string, isChecked: boolean) => void;
+  toggleDirectorySelection: (
+    path: string,
+    isChecked: boolean
+  ) => Promise<void>;
   triggerFileTreeRefresh: () => void;
+  applyFsChange: (change: FsChangeEvent) => void;
+  takeModifiedPaths: () => string[];
 }
 
 interface PersistedState {
@@ -78,6 +93,10 @@ const initialState: Omit<
   | 'setRootPath'
   | 'closeProject'
   | 'loadFileTree'
+  | 'expandDirectory'
+  | 'loadSubtree'
+  | 'revealPaths'
+  | 'getFullFileTree'
   | 'setFileTree'
   | 'setActiveFilePath'
   | 'setSelectedFilePaths'
@@ -85,6 +104,8 @@ const initialState: Omit<
   | 'removeSelectedFilePath'
   | 'toggleDirectorySelection'
   | 'triggerFileTreeRefresh'
+  | 'applyFsChange'
+  | 'takeModifiedPaths'
 > = {
   rootPath: null,
   isInitialized: false,
@@ -96,6 +117,7 @@ const initialState: Omit<
   selectedFilePathIndex: new Map(),
   selectedCountByPath: new Map(),
   refreshCounter: 0,
+  pendingModifiedPaths: new Set(),
 };
 
 const buildSelectionIndex = (paths: string[]) => {
@@ -146,10 +168,84 @@ const updateCountsForPath = (
   }
 };
 
+/** A listing depth that reaches every file below a directory. */
+const WHOLE_SUBTREE_DEPTH = 1024;
+
+const getIgnoreSettings = () => {
+  const { respectGitignore, customIgnorePatterns } =
+    useSettingsStore.getState();
+  return { respectGitignore, customIgnorePatterns };
+};
+
+/** Lists each directory to its depth, parents first. */
+const listDirectories = async (
+  rootPath: string,
+  depths: Map<string, number>
+): Promise<FileNode[]> => {
+  const settings = getIgnoreSettings();
+  const results = await Promise.allSettled(
+    [...depths].map(([path, depth]) =>
+      projectService.loadDirectory(rootPath, path, settings, depth)
+    )
+  );
+  // Parents go first so that they don't replace their freshly listed children.
+  return results
+    .flatMap((result) => {
+      if (result.status === 'fulfilled') return [result.value];
+      // The directory may have gone since it was listed.
+      console.warn(new AppError('Failed to list directory', result.reason));
+      return [];
+    })
+    .sort((a, b) => a.path.length - b.path.length);
+};
+
+const graftNodes = (tree: FileNode, nodes: FileNode[]) =>
+  nodes.reduce(projectService.replaceNode, tree);
+
+/** Directories below the root whose children are showing. */
+const listedDirectories = (tree: FileNode): Map<string, number> => {
+  const depths = new Map<string, number>();
+  const walk = (node: FileNode) => {
+    for (const child of node.children ?? []) {
+      if (child.isDirectory && child.children) {
+        depths.set(child.path, 1);
+        walk(child);
+      }
+    }
+  };
+  walk(tree);
+  return depths;
+};
+
+let fullFileTree: {
+  rootPath: string;
+  refreshCounter: number;
+  settings: string;
+  tree: Promise<FileNode>;
+} | null = null;
+
 let persistenceUnsubscribe: (() => void) | null = null;
 let saveTimeout: NodeJS.Timeout | null = null;
 let tauriFileWatcherUnlisten: (() => void) | null = null;
 
+/** Reloads the project's config file, reports its errors and applies it to the settings. */
+const applyProjectConfig = async (rootPath: string) => {
+  try {
+    const status = await loadProjectConfig(rootPath);
+    if (status.errors.length > 0) {
+      const details = status.errors
+        .map((e) => `${status.path}:${e.line}:${e.column}: ${e.message}`)
+        .join('\n');
+      showErrorDialog(
+        new AppError('Project config file is invalid and was ignored', details)
+      );
+    }
+    await useSettingsStore.getState().resolveProjectSettings(rootPath);
+  } catch (e) {
+    console.warn(new AppError(`Failed to load project config: ${rootPath}`, e));
+  }
+};
+
 export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
   ...initialState,
 
@@ -221,24 +317,55 @@ export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
     });
 
     try {
-      const { respectGitignore, customIgnorePatterns } =
-        useSettingsStore.getState();
-      const settings = { respectGitignore, customIgnorePatterns };
-      await startWatching(rootPath, settings);
-      tauriFileWatcherUnlisten = await listen<string>(
-        'file-change-event',
-        (event) => {
-          if (event.payload === get().rootPath) {
+      // Listen first: a watcher that fails to start can be restarted later.
+      const unlistenFileChanges = await events.fsChangeEvent.listen(
+        (event) => get().applyFsChange(event.payload)
+      );
+      const unlistenConfigChanges =
+        await events.projectConfigChangedEvent.listen((event) => {
+          if (event.payload.rootPath === get().rootPath) {
+            applyProjectConfig(event.payload.rootPath);
             get().triggerFileTreeRefresh();
           }
+        });
+      const unlistenReviewTargets =
+        await events.reviewTargetChangedEvent.listen((event) => {
+          if (event.payload.rootPath === get().rootPath) {
+            useReviewStore.getState().markTargetsChanged(event.payload.changes);
+          }
+        });
+      // The backend updates a running watcher in place.
+      const unsubscribeIgnoreSettings = useSettingsStore.subscribe(
+        (state, prev) => {
+          if (
+            state.respectGitignore === prev.respectGitignore &&
+            state.customIgnorePatterns === prev.customIgnorePatterns
+          ) {
+            return;
+          }
+          startWatching(rootPath, {
+            respectGitignore: state.respectGitignore,
+            customIgnorePatterns: state.customIgnorePatterns,
+          }).catch(showErrorDialog);
         }
       );
+      tauriFileWatcherUnlisten = () => {
+        unlistenFileChanges();
+        unlistenConfigChanges();
+        unlistenReviewTargets();
+        unsubscribeIgnoreSettings();
+      };
+      await startWatching(rootPath, getIgnoreSettings());
     } catch (e) {
+      // Running out of watches comes with its own advice.
       showErrorDialog(
-        new AppError(`Failed to set up file watcher for ${rootPath}`, e)
+        getWatchLimitExceeded(e)
+          ? e
+          : new AppError(`Failed to set up file watcher for ${rootPath}`, e)
       );
     }
 
+    applyProjectConfig(rootPath);
     get().loadFileTree();
   },
 
@@ -267,6 +394,7 @@ export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
     useComposerStore.getState()._reset();
     useHistoryStore.getState()._reset();
     useReviewStore.getState().clearReviewSession();
+    useSettingsStore.getState().clearProjectSettings();
     set(initialState);
   },
 
@@ -284,10 +412,26 @@ export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
     }
 
     try {
-      const { respectGitignore, customIgnorePatterns } =
-        useSettingsStore.getState();
-      const settings = { respectGitignore, customIgnorePatterns };
-      const fileTree = await projectService.loadFileTree(rootPath, settings);
+      const previous = get().fileTree;
+      let fileTree = await projectService.loadDirectory(
+        rootPath,
+        rootPath,
+        getIgnoreSettings()
+      );
+      // Keep open directories open and selected files in the tree.
+      if (previous?.path === rootPath) {
+        const listed = listedDirectories(previous);
+        fileTree = graftNodes(fileTree, await listDirectories(rootPath, listed));
+      }
+      const hidden = findHiddenAncestors(
+        get().selectedFilePaths,
+        buildFileTreeIndex(fileTree)
+      );
+      if (hidden.size > 0) {
+        const nodes = await listDirectories(rootPath, hidden);
+        fileTree = graftNodes(fileTree, nodes);
+      }
+      if (get().rootPath !== rootPath) return;
       const fileIndex = buildFileTreeIndex(fileTree);
       const selectedFilePaths = normalizeSelectedPaths(
         get().selectedFilePaths,
@@ -307,6 +451,77 @@ export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
     }
   },
 
+  expandDirectory: async (path) => {
+    const { rootPath, fileTree } = get();
+    if (!rootPath || !fileTree) return;
+    try {
+      const node = await projectService.loadDirectory(
+        rootPath,
+        path,
+        getIgnoreSettings()
+      );
+      const current = get().fileTree;
+      if (get().rootPath !== rootPath || !current) return;
+      get().setFileTree(projectService.replaceNode(current, node));
+    } catch (error) {
+      showErrorDialog(error);
+    }
+  },
+
+  loadSubtree: async (path) => {
+    const { rootPath, fileTree, fileIndex } = get();
+    if (!rootPath || !fileTree || !fileIndex) return;
+    if (
+      !fileIndex.unloadedDirectories.has(path) &&
+      !fileIndex.incompleteDirectories.has(path)
+    ) {
+      return;
+    }
+    const node = await projectService.loadDirectory(
+      rootPath,
+      path,
+      getIgnoreSettings(),
+      WHOLE_SUBTREE_DEPTH
+    );
+    const current = get().fileTree;
+    if (get().rootPath !== rootPath || !current) return;
+    get().setFileTree(projectService.replaceNode(current, node));
+  },
+
+  revealPaths: async (paths) => {
+    const { rootPath, fileIndex } = get();
+    if (!rootPath || !fileIndex) return;
+    const hidden = findHiddenAncestors(paths, fileIndex);
+    if (hidden.size === 0) return;
+    const nodes = await listDirectories(rootPath, hidden);
+    const current = get().fileTree;
+    if (nodes.length === 0 || get().rootPath !== rootPath || !current) return;
+    get().setFileTree(graftNodes(current, nodes));
+  },
+
+  getFullFileTree: async () => {
+    const { rootPath, refreshCounter } = get();
+    if (!rootPath) return null;
+    const ignoreSettings = getIgnoreSettings();
+    const settings = JSON.stringify(ignoreSettings);
+    let cached = fullFileTree;
+    if (
+      !cached ||
+      cached.rootPath !== rootPath ||
+      cached.refreshCounter !== refreshCounter ||
+      cached.settings !== settings
+    ) {
+      const tree = projectService.loadFileTree(rootPath, ignoreSettings);
+      cached = { rootPath, refreshCounter, settings, tree };
+      fullFileTree = cached;
+      // A failed listing is retried next time rather than cached.
+      tree.catch(() => {
+        if (fullFileTree?.tree === tree) fullFileTree = null;
+      });
+    }
+    return cached.tree;
+  },
+
   setFileTree: (fileTree) => {
     if (!fileTree) {
       set({
@@ -343,6 +558,21 @@ export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
       selectedFilePathIndex: buildSelectionIndex(selectedFilePaths),
       selectedCountByPath: buildSelectedCounts(selectedFilePaths, fileIndex),
     });
+    // Paths in directories that haven't been listed join once they are.
+    if (fileIndex && findHiddenAncestors(paths, fileIndex).size > 0) {
+      get()
+        .revealPaths(paths)
+        .then(() => {
+          const state = get();
+          if (
+            state.fileIndex !== fileIndex &&
+            state.selectedFilePaths === selectedFilePaths
+          ) {
+            state.setSelectedFilePaths(paths);
+          }
+        })
+        .catch(showErrorDialog);
+    }
   },
   addSelectedFilePath: (path) => {
     const startTime = performance.now();
@@ -358,6 +588,16 @@ export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
     }
 
     if (!fileIndex.filePaths.has(path)) {
+      if (findHiddenAncestors([path], fileIndex).size > 0) {
+        get()
+          .revealPaths([path])
+          .then(() => {
+            if (get().fileIndex?.filePaths.has(path)) {
+              get().addSelectedFilePath(path);
+            }
+          })
+          .catch(showErrorDialog);
+      }
       return;
     }
 
@@ -412,7 +652,16 @@ export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
       selectedCount: nextPaths.length,
     });
   },
-  toggleDirectorySelection: (path, isChecked) => {
+  toggleDirectorySelection: async (path, isChecked) => {
+    // Selecting a directory takes every file below it, listed or not.
+    if (isChecked) {
+      try {
+        await get().loadSubtree(path);
+      } catch (error) {
+        showErrorDialog(error);
+        return;
+      }
+    }
     const startTime = performance.now();
     const {
       fileIndex,
@@ -466,4 +715,31 @@ export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
   },
   triggerFileTreeRefresh: () =>
     set((state) => ({ refreshCounter: state.refreshCounter + 1 })),
+  applyFsChange: (change) => {
+    if (change.rootPath !== get().rootPath) return;
+    // Batches can arrive faster than they are handled, so edits add up.
+    if (change.modified.length > 0) {
+      set((state) => ({
+        pendingModifiedPaths: new Set([
+          ...state.pendingModifiedPaths,
+          ...change.modified,
+        ]),
+      }));
+    }
+    // Edits leave the tree as it is; it needs reloading when paths come or go.
+    if (
+      change.created.length > 0 ||
+      change.removed.length > 0 ||
+      change.renamed.length > 0
+    ) {
+      get().triggerFileTreeRefresh();
+    }
+  },
+  takeModifiedPaths: () => {
+    const paths = [...get().pendingModifiedPaths];
+    if (paths.length > 0) {
+      set({ pendingModifiedPaths: new Set() });
+    }
+    return paths;
+  },
 }));
@@ -7,6 +7,8 @@ export interface ReviewChange {
   id: string;
   operation: ChangeOperation;
   status: ReviewStatus;
+  /** The backup to revert to when the change was re-run after the review's backup. */
+  baseBackupId?: string | null;
 }
 
 export const createReviewChange = (
@@ -14,4 +14,6 @@ export interface AppSettings {
   enableClipboardReview: boolean;
   showPasteResponseArea: boolean;
   theme: Theme;
+  /** Per project root, the fingerprint of the config-file formatters the user approved. */
+  approvedProjectFormatters: Record<string, string>;
 }